@@ -8,18 +8,61 @@ type RequestProxy = {[key in RequestMethod]: RequestFn};
 
 const methods: RequestMethod[] = ["get", "post", "patch", "put", "delete"];
 
+let refreshing: Promise<boolean> | undefined;
+
+// Access tokens are short lived: they are renewed with the refresh token,
+// once for all the requests refused at the same time.
+function refreshTokens(): Promise<boolean> {
+  const refreshToken = tokenStorage.getRefresh();
+  if (!refreshToken) return Promise.resolve(false);
+
+  if (!refreshing) {
+    refreshing = fetch(process.env.REACT_APP_API_URL + "/public/refresh", {
+      method: "post",
+      headers: new Headers([["Content-Type", "application/json"]]),
+      body: JSON.stringify({refreshToken}),
+    })
+      .then(async res => {
+        if (!res.ok) {
+          tokenStorage.clear();
+          return false;
+        }
+
+        const tokens: {token: string; refreshToken: string} = await res.json();
+        tokenStorage.set(tokens.token);
+        tokenStorage.setRefresh(tokens.refreshToken);
+        return true;
+      })
+      .catch(() => false)
+      .finally(() => (refreshing = undefined));
+  }
+
+  return refreshing;
+}
+
 const request = <T>(method: RequestMethod) => (path: string, data?: RequestData) => {
   loading$.next(true);
 
-  const headers = new Headers([["Authorization", `Bearer ${tokenStorage.get()}`]]);
-  let body = data;
+  const send = () => {
+    const headers = new Headers([["Authorization", `Bearer ${tokenStorage.get()}`]]);
+    let body = data;
 
-  if (data !== undefined && !(data instanceof FormData)) {
-    headers.append("Content-Type", "application/json");
-    body = JSON.stringify(data);
-  }
+    if (data !== undefined && !(data instanceof FormData)) {
+      headers.append("Content-Type", "application/json");
+      body = JSON.stringify(data);
+    }
+
+    return fetch(process.env.REACT_APP_API_URL + path, {method, credentials: "include", headers, body});
+  };
 
-  return fetch(process.env.REACT_APP_API_URL + path, {method, credentials: "include", headers, body})
+  return send()
+    .then(res => {
+      if (res.status === 401 && !path.startsWith("/public/")) {
+        return refreshTokens().then(refreshed => (refreshed ? send() : res));
+      }
+
+      return res;
+    })
     .then<T>(async res => {
       if (!res.ok) {
         throw new Error(await res.text());
@@ -6,6 +6,8 @@ import Typography from "antd/lib/typography";
 import ConfigProvider from "antd/lib/config-provider";
 import {
   AppstoreOutlined,
+  AuditOutlined,
+  BarChartOutlined,
   BuildOutlined,
   FontSizeOutlined,
   FormatPainterOutlined,
@@ -22,6 +24,7 @@ import frFR from "antd/lib/locale/fr_FR";
 import useAuth from "./auth/hook";
 import PrivateRoute from "./auth/private-route";
 import SignIn from "./auth/sign-in";
+import ResetPassword from "./auth/reset-password";
 import {AppPage} from "./application";
 import {UserPage} from "./user";
 import {MaterialPage} from "./material";
@@ -32,11 +35,14 @@ import {ShapePage} from "./shape";
 import {FontPage} from "./font";
 import {TemplateListPage, TemplateEditPage} from "./template";
 import {FixationListPage, FixationEditPage} from "./fixation";
+import {StatsPage} from "./stats";
+import {AuditPage} from "./audit";
 
 const withLayout = (Component: React.ComponentType) => () => {
   const history = useHistory();
   const path = history.location.pathname;
-  const {signOut} = useAuth();
+  const {auth, signOut} = useAuth();
+  const isAdmin = auth.type === "authenticated" && auth.isAdmin;
 
   const selectedKeys = (() => {
     if (path.startsWith("/app")) return ["/app"];
@@ -80,9 +86,11 @@ const withLayout = (Component: React.ComponentType) => () => {
           <Menu.Item key="/app" icon={<AppstoreOutlined />}>
             Applications
           </Menu.Item>
-          <Menu.Item key="/user" icon={<UserOutlined />}>
-            Utilisateurs
-          </Menu.Item>
+          {isAdmin && (
+            <Menu.Item key="/user" icon={<UserOutlined />}>
+              Utilisateurs
+            </Menu.Item>
+          )}
           <Menu.Item key="/material" icon={<FormatPainterOutlined />}>
             Matériaux
           </Menu.Item>
@@ -107,6 +115,14 @@ const withLayout = (Component: React.ComponentType) => () => {
           <Menu.Item key="/template" icon={<LayoutOutlined />}>
             Templates
           </Menu.Item>
+          <Menu.Item key="/stats" icon={<BarChartOutlined />}>
+            Statistiques
+          </Menu.Item>
+          {isAdmin && (
+            <Menu.Item key="/audit" icon={<AuditOutlined />}>
+              Journal
+            </Menu.Item>
+          )}
         </Menu>
         <Menu theme="dark" mode="inline">
           <Menu.Item icon={<LogoutOutlined />} onClick={signOut}>
@@ -127,6 +143,7 @@ export const App: FC = () => {
       <Router>
         <Switch>
           <Route path="/sign-in" component={SignIn} />
+          <Route path="/reset-password" component={ResetPassword} />
           <PrivateRoute path="/app" component={withLayout(AppPage)} />
           <PrivateRoute path="/user" component={withLayout(UserPage)} />
           <PrivateRoute path="/material" component={withLayout(MaterialPage)} />
@@ -139,6 +156,8 @@ export const App: FC = () => {
           <PrivateRoute path="/font" component={withLayout(FontPage)} />
           <PrivateRoute path="/template/edit/:id?" component={TemplateEditPage} />
           <PrivateRoute path="/template" component={withLayout(TemplateListPage)} />
+          <PrivateRoute path="/stats" component={withLayout(StatsPage)} />
+          <PrivateRoute path="/audit" component={withLayout(AuditPage)} />
           <Redirect to="/app" />
         </Switch>
       </Router>
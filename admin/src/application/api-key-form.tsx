@@ -0,0 +1,147 @@
+import React, {FC, useCallback, useEffect, useState} from "react";
+import Button from "antd/lib/button";
+import Form from "antd/lib/form";
+import Input from "antd/lib/input";
+import Modal from "antd/lib/modal";
+import Popconfirm from "antd/lib/popconfirm";
+import Select from "antd/lib/select";
+import Table from "antd/lib/table";
+import Typography from "antd/lib/typography";
+import {PlusOutlined, StopOutlined} from "@ant-design/icons";
+
+import $app from "./service";
+import {ApiKey, ApiKeyScope, apiKeyScopes, Application} from "./model";
+
+type NewApiKey = {
+  name: string;
+  scope: ApiKeyScope;
+};
+
+type EditAppApiKeysFormProps = {
+  app?: Application;
+  onClose: () => void;
+};
+
+export const EditAppApiKeysForm: FC<EditAppApiKeysFormProps> = ({app, onClose: close}) => {
+  const [form] = Form.useForm<NewApiKey>();
+  const [loading, setLoading] = useState(true);
+  const [keys, setKeys] = useState<ApiKey[]>([]);
+  const [token, setToken] = useState<string | undefined>();
+
+  const fetchKeys = useCallback(() => {
+    if (!app) return;
+    setLoading(true);
+    $app
+      .getApiKeys(app.id)
+      .then(setKeys)
+      .finally(() => setLoading(false));
+  }, [app]);
+
+  useEffect(() => {
+    setToken(undefined);
+    fetchKeys();
+  }, [fetchKeys]);
+
+  function create(key: NewApiKey) {
+    if (!app) return;
+    setLoading(true);
+    $app
+      .createApiKey(app.id, key)
+      .then(({token}) => {
+        form.resetFields();
+        setToken(token);
+        fetchKeys();
+      })
+      .catch(() => setLoading(false));
+  }
+
+  function revoke(keyId: number) {
+    if (!app) return;
+    setLoading(true);
+    $app.revokeApiKey(app.id, keyId).then(fetchKeys);
+  }
+
+  return (
+    <Modal
+      closable={!loading}
+      destroyOnClose
+      footer={null}
+      onCancel={() => !loading && close()}
+      title={`Clés d'API${app ? ` — ${app.name}` : ""}`}
+      visible={app !== undefined}
+      width="50rem"
+    >
+      <Form form={form} layout="inline" onFinish={create} initialValues={{scope: "quote"}}>
+        <Form.Item name="name" rules={[{required: true, message: "Nom requis"}]}>
+          <Input placeholder="Nom" disabled={loading} />
+        </Form.Item>
+        <Form.Item name="scope">
+          <Select disabled={loading} style={{width: "14rem"}}>
+            {apiKeyScopes.map(({value, label}) => (
+              <Select.Option key={value} value={value}>
+                {label}
+              </Select.Option>
+            ))}
+          </Select>
+        </Form.Item>
+        <Form.Item>
+          <Button type="primary" htmlType="submit" loading={loading}>
+            <PlusOutlined />
+            Créer
+          </Button>
+        </Form.Item>
+      </Form>
+      {token && (
+        <Typography.Paragraph style={{marginTop: "1rem"}}>
+          Copiez cette clé maintenant, elle ne sera plus affichée :{" "}
+          <Typography.Text code copyable>
+            {token}
+          </Typography.Text>
+        </Typography.Paragraph>
+      )}
+      <Table
+        bordered
+        size="small"
+        dataSource={keys}
+        loading={loading}
+        pagination={false}
+        rowKey="id"
+        style={{marginTop: "1rem"}}
+        columns={[
+          {title: <strong>Nom</strong>, dataIndex: "name"},
+          {title: <strong>Clé</strong>, render: (_, {prefix}) => <code>{`ck_${prefix}…`}</code>},
+          {
+            title: <strong>Portée</strong>,
+            render: (_, {scope}) => apiKeyScopes.find(({value}) => value === scope)?.label || scope,
+          },
+          {
+            title: <strong>Créée le</strong>,
+            render: (_, {createdAt}) => new Date(createdAt).toLocaleDateString(),
+          },
+          {
+            dataIndex: "actions",
+            align: "center",
+            render: (_, {id, revokedAt}) =>
+              revokedAt ? (
+                <Typography.Text type="secondary">Révoquée</Typography.Text>
+              ) : (
+                <Popconfirm
+                  title="Êtes-vous sûr de vouloir révoquer cette clé ?"
+                  placement="topRight"
+                  okText="Oui"
+                  cancelText="Non"
+                  onConfirm={() => revoke(id)}
+                >
+                  <Button type="link" danger>
+                    <StopOutlined />
+                  </Button>
+                </Popconfirm>
+              ),
+          },
+        ]}
+      />
+    </Modal>
+  );
+};
+
+export default EditAppApiKeysForm;
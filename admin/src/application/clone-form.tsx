@@ -0,0 +1,70 @@
+import React, {FC, useRef, useState} from "react";
+import Form, {FormInstance} from "antd/lib/form";
+import Input from "antd/lib/input";
+import Modal from "antd/lib/modal";
+import Checkbox from "antd/lib/checkbox";
+
+import $app from "./service";
+import {Application, CloneAppOptions} from "./model";
+
+type CloneAppFormProps = {
+  app?: Application;
+  onClose: (fetchNeeded: boolean) => void;
+};
+
+export const CloneAppForm: FC<CloneAppFormProps> = ({app, onClose: close}) => {
+  const [loading, setLoading] = useState(false);
+  const form = useRef<FormInstance>(null);
+
+  function submit() {
+    if (form.current) {
+      form.current.submit();
+    }
+  }
+
+  function clone(opts: CloneAppOptions) {
+    if (!app) return;
+    setLoading(true);
+    $app
+      .clone(app.id, opts)
+      .then(() => close(true))
+      .catch(() => {})
+      .finally(() => setLoading(false));
+  }
+
+  return (
+    <Modal
+      cancelText="Annuler"
+      closable={!loading}
+      confirmLoading={loading}
+      destroyOnClose
+      okText="Dupliquer"
+      onCancel={() => !loading && close(false)}
+      onOk={submit}
+      title={`Dupliquer l'application${app ? ` — ${app.name}` : ""}`}
+      visible={app !== undefined}
+    >
+      {app && (
+        <Form
+          ref={form}
+          onFinish={clone}
+          layout="vertical"
+          initialValues={{name: `${app.name} (copie)`, withUsers: false}}
+        >
+          <Form.Item label="Nom" name="name" rules={[{required: true, message: "Nom requis"}]}>
+            <Input disabled={loading} onPressEnter={submit} />
+          </Form.Item>
+          <Form.Item
+            name="withUsers"
+            valuePropName="checked"
+            help="Les produits, polices, transporteurs, paramètres, marges et taxes sont toujours copiés"
+          >
+            <Checkbox disabled={loading}>Copier les utilisateurs</Checkbox>
+          </Form.Item>
+        </Form>
+      )}
+    </Modal>
+  );
+};
+
+export default CloneAppForm;
@@ -2,12 +2,17 @@ import React, {FC, useState, useRef} from "react";
 import Form, {FormInstance} from "antd/lib/form";
 import Input from "antd/lib/input";
 import Modal from "antd/lib/modal";
+import Select from "antd/lib/select";
 
 import $app from "./service";
 import {Application, emptyApp} from "./model";
 import {SelectUsersFormItem} from "../user/form";
 import {SelectMaterialsFormItem} from "../material/form";
 import {SelectFontsFormItem} from "../font/form";
+import {SelectCarriersFormItem} from "../carrier/form";
+import {SelectTemplatesFormItem} from "../template/form";
+import {SelectPictosFormItem} from "../picto/form";
+import {SelectShapesFormItem} from "../shape/form";
 
 type EditAppFormProps = {
   app?: Application;
@@ -52,9 +57,24 @@ export const EditAppForm: FC<EditAppFormProps> = ({app, onClose: close}) => {
         <Form.Item label="Nom" name="name" rules={[{required: true, message: "Nom requis"}]}>
           <Input autoFocus disabled={loading} onPressEnter={submit} />
         </Form.Item>
+        {!createMode && (
+          <Form.Item label="Clé publique" name="publicKey">
+            <Input readOnly />
+          </Form.Item>
+        )}
+        <Form.Item label="Domaine" name="host" help="Domaine depuis lequel l'éditeur est servi (optionnel)">
+          <Input disabled={loading} placeholder="exemple.com" onPressEnter={submit} />
+        </Form.Item>
+        <Form.Item label="Origines autorisées" name="origins" help="Domaines autorisés à appeler l'API (sous-domaines inclus)">
+          <Select mode="tags" disabled={loading} placeholder="exemple.com" tokenSeparators={[",", " "]} />
+        </Form.Item>
         <SelectUsersFormItem />
         <SelectMaterialsFormItem />
         <SelectFontsFormItem />
+        <SelectCarriersFormItem />
+        <SelectTemplatesFormItem label="Templates privés" help="Invisibles pour les autres applications" />
+        <SelectPictosFormItem label="Pictogrammes privés" help="Invisibles pour les autres applications" />
+        <SelectShapesFormItem label="Formes privées" help="Invisibles pour les autres applications" />
       </Form>
     </Modal>
   );
@@ -0,0 +1,158 @@
+import React, {FC, useEffect, useState} from "react";
+import Form from "antd/lib/form";
+import InputNumber from "antd/lib/input-number";
+import Modal from "antd/lib/modal";
+import Table from "antd/lib/table";
+import Typography from "antd/lib/typography";
+
+import $app from "./service";
+import {Application, withMarkup} from "./model";
+import $material from "../material/service";
+import Material from "../material/model";
+import $fixation from "../fixation/service";
+import Fixation from "../fixation/model";
+
+type Overrides = Record<number, number | undefined>;
+
+type EditAppMarkupsFormProps = {
+  app?: Application;
+  onClose: () => void;
+};
+
+function formatPrice(price = 0) {
+  return `${price.toFixed(2)} €`;
+}
+
+export const EditAppMarkupsForm: FC<EditAppMarkupsFormProps> = ({app, onClose: close}) => {
+  const [loading, setLoading] = useState(true);
+  const [markup, setMarkup] = useState(0);
+  const [materialMarkups, setMaterialMarkups] = useState<Overrides>({});
+  const [fixationMarkups, setFixationMarkups] = useState<Overrides>({});
+  const [materials, setMaterials] = useState<Material[]>([]);
+  const [fixations, setFixations] = useState<Fixation[]>([]);
+
+  useEffect(() => {
+    if (!app) return;
+    setLoading(true);
+    Promise.all([$app.getMarkups(app.id), $material.get(), $fixation.get()])
+      .then(([markups, materials, fixations]) => {
+        if (markups) {
+          setMarkup(markups.markup);
+          setMaterialMarkups(Object.fromEntries(markups.materials.map(m => [m.materialId, m.markup])));
+          setFixationMarkups(Object.fromEntries(markups.fixations.map(f => [f.fixationId, f.markup])));
+        }
+        setMaterials(materials.filter(material => app.materialIds.includes(material.id)));
+        setFixations(fixations);
+      })
+      .finally(() => setLoading(false));
+  }, [app]);
+
+  function save() {
+    if (!app) return;
+    setLoading(true);
+    $app
+      .setMarkups(app.id, {
+        markup,
+        materials: materials
+          .filter(({id}) => materialMarkups[id] !== undefined)
+          .map(({id}) => ({materialId: id, markup: materialMarkups[id] || 0})),
+        fixations: fixations
+          .filter(({id}) => fixationMarkups[id] !== undefined)
+          .map(({id}) => ({fixationId: id, markup: fixationMarkups[id] || 0})),
+      })
+      .then(close)
+      .catch(() => {})
+      .finally(() => setLoading(false));
+  }
+
+  function markupOf(overrides: Overrides, id: number) {
+    const override = overrides[id];
+    return override === undefined ? markup : override;
+  }
+
+  function renderOverride(overrides: Overrides, setOverrides: (overrides: Overrides) => void, id: number) {
+    return (
+      <InputNumber
+        min={0}
+        step={1}
+        disabled={loading}
+        placeholder={String(markup)}
+        value={overrides[id]}
+        onChange={value => setOverrides({...overrides, [id]: typeof value === "number" ? value : undefined})}
+      />
+    );
+  }
+
+  return (
+    <Modal
+      cancelText="Annuler"
+      closable={!loading}
+      confirmLoading={loading}
+      destroyOnClose
+      okText="Modifier"
+      onCancel={() => !loading && close()}
+      onOk={save}
+      title={`Marges${app ? ` — ${app.name}` : ""}`}
+      visible={app !== undefined}
+      width="60rem"
+    >
+      <Form layout="vertical">
+        <Form.Item label="Marge par défaut (%)" help="Appliquée aux produits et fixations sans marge spécifique">
+          <InputNumber min={0} step={1} disabled={loading} value={markup} onChange={value => setMarkup(Number(value) || 0)} />
+        </Form.Item>
+      </Form>
+      <Typography.Title level={4}>Produits</Typography.Title>
+      <Table
+        bordered
+        size="small"
+        dataSource={materials}
+        loading={loading}
+        pagination={false}
+        rowKey="id"
+        columns={[
+          {title: <strong>Produit</strong>, dataIndex: "title"},
+          {
+            title: <strong>Coût (fixe / m²)</strong>,
+            render: (_, {fixedPrice, surfacePrice}) => `${formatPrice(fixedPrice)} / ${formatPrice(surfacePrice)}`,
+          },
+          {
+            title: <strong>Marge (%)</strong>,
+            render: (_, {id}) => renderOverride(materialMarkups, setMaterialMarkups, id),
+          },
+          {
+            title: <strong>Prix de revente (fixe / m²)</strong>,
+            render: (_, {id, fixedPrice = 0, surfacePrice = 0}) => {
+              const markup = markupOf(materialMarkups, id);
+              return `${formatPrice(withMarkup(fixedPrice, markup))} / ${formatPrice(withMarkup(surfacePrice, markup))}`;
+            },
+          },
+        ]}
+      />
+      <Typography.Title level={4} style={{marginTop: "1rem"}}>
+        Fixations
+      </Typography.Title>
+      <Table
+        bordered
+        size="small"
+        dataSource={fixations}
+        loading={loading}
+        pagination={false}
+        rowKey="id"
+        columns={[
+          {title: <strong>Fixation</strong>, dataIndex: "name"},
+          {title: <strong>Coût</strong>, render: (_, {price}) => formatPrice(price)},
+          {
+            title: <strong>Marge (%)</strong>,
+            render: (_, {id}) => renderOverride(fixationMarkups, setFixationMarkups, id),
+          },
+          {
+            title: <strong>Prix de revente</strong>,
+            render: (_, {id, price}) => formatPrice(withMarkup(price, markupOf(fixationMarkups, id))),
+          },
+        ]}
+      />
+    </Modal>
+  );
+};
+
+export default EditAppMarkupsForm;
@@ -1,17 +1,125 @@
 export type Application = {
   id: number;
   name: string;
+  publicKey: string;
+  host?: string;
   materialIds: number[];
+  templateIds: number[];
   pictoIds: number[];
+  shapeIds: number[];
   userIds: number[];
+  carrierIds: number[];
+  origins: string[];
 };
 
 export function emptyApp(): Application {
   return {
     id: 0,
     name: "",
+    publicKey: "",
     materialIds: [],
+    templateIds: [],
     pictoIds: [],
+    shapeIds: [],
     userIds: [],
+    carrierIds: [],
+    origins: [],
   };
 }
+
+export type CloneAppOptions = {
+  name: string;
+  withUsers: boolean;
+};
+
+export type EditorStep = "product" | "dimension" | "fixation";
+
+export const editorSteps: {value: EditorStep; label: string}[] = [
+  {value: "product", label: "Produits"},
+  {value: "dimension", label: "Dimensions"},
+  {value: "fixation", label: "Fixations"},
+];
+
+export type AppSettings = {
+  logoUrl?: string;
+  palette: string[];
+  templateId?: number;
+  productId?: number;
+  steps: EditorStep[];
+};
+
+export type AppMaterialMarkup = {
+  materialId: number;
+  markup: number;
+};
+
+export type AppFixationMarkup = {
+  fixationId: number;
+  markup: number;
+};
+
+export type AppMarkups = {
+  markup: number;
+  materials: AppMaterialMarkup[];
+  fixations: AppFixationMarkup[];
+};
+
+export function withMarkup(price: number, markup: number): number {
+  return Math.round(price * (100 + markup)) / 100;
+}
+
+export type ApiKeyScope = "quote" | "order-create" | "catalog-read";
+
+export const apiKeyScopes: {value: ApiKeyScope; label: string}[] = [
+  {value: "quote", label: "Devis"},
+  {value: "order-create", label: "Création de commandes"},
+  {value: "catalog-read", label: "Lecture du catalogue"},
+];
+
+export type ApiKey = {
+  id: number;
+  appId: number;
+  name: string;
+  prefix: string;
+  scope: ApiKeyScope;
+  createdAt: string;
+  revokedAt?: string;
+};
+
+export type CreatedApiKey = ApiKey & {
+  token: string;
+};
+
+export type WebhookEvent = "order.created" | "order.status_changed" | "material.updated" | "material.deleted";
+
+export const webhookEvents: {value: WebhookEvent; label: string}[] = [
+  {value: "order.created", label: "Commande créée"},
+  {value: "order.status_changed", label: "Statut de commande modifié"},
+  {value: "material.updated", label: "Produit modifié"},
+  {value: "material.deleted", label: "Produit supprimé"},
+];
+
+export type Webhook = {
+  id: number;
+  appId: number;
+  url: string;
+  secret: string;
+  events: WebhookEvent[];
+  active: boolean;
+  createdAt: string;
+};
+
+export type WebhookDeliveryStatus = "pending" | "delivered" | "failed";
+
+export type WebhookDelivery = {
+  id: number;
+  webhookId: number;
+  event: WebhookEvent;
+  status: WebhookDeliveryStatus;
+  attempts: number;
+  nextAttemptAt?: string;
+  lastStatusCode?: number;
+  lastError?: string;
+  createdAt: string;
+  deliveredAt?: string;
+};
@@ -3,16 +3,35 @@ import Typography from "antd/lib/typography";
 import Table from "antd/lib/table";
 import Button from "antd/lib/button";
 import Popconfirm from "antd/lib/popconfirm";
-import {PlusOutlined, DeleteOutlined, EditOutlined} from "@ant-design/icons";
+import {
+  PlusOutlined,
+  CopyOutlined,
+  DeleteOutlined,
+  EditOutlined,
+  KeyOutlined,
+  NotificationOutlined,
+  PercentageOutlined,
+  SettingOutlined,
+} from "@ant-design/icons";
 
 import $app from "./service";
 import {Application, emptyApp} from "./model";
 import EditAppForm from "./form";
+import EditAppSettingsForm from "./settings-form";
+import EditAppMarkupsForm from "./markup-form";
+import EditAppApiKeysForm from "./api-key-form";
+import CloneAppForm from "./clone-form";
+import EditAppWebhooksForm from "./webhook-form";
 
 export const AppPage: FC = () => {
   const [loading, setLoading] = useState(true);
   const [apps, setApps] = useState<Application[]>([]);
   const [editedApp, editApp] = useState<Application | undefined>();
+  const [configuredApp, configureApp] = useState<Application | undefined>();
+  const [pricedApp, priceApp] = useState<Application | undefined>();
+  const [keyedApp, keyApp] = useState<Application | undefined>();
+  const [clonedApp, cloneApp] = useState<Application | undefined>();
+  const [hookedApp, hookApp] = useState<Application | undefined>();
 
   const fetchApps = useCallback(() => {
     $app
@@ -23,6 +42,7 @@ export const AppPage: FC = () => {
 
   function handleEditAppFormClose(fetchNeeded: boolean) {
     editApp(undefined);
+    cloneApp(undefined);
 
     if (fetchNeeded) {
       setLoading(true)
@@ -59,12 +79,27 @@ export const AppPage: FC = () => {
             ),
             dataIndex: "actions",
             align: "center",
-            width: "10rem",
+            width: "23rem",
             render: (_, app) => (
               <>
                 <Button type="link" onClick={() => editApp(app)}>
                   <EditOutlined />
                 </Button>
+                <Button type="link" onClick={() => cloneApp(app)}>
+                  <CopyOutlined />
+                </Button>
+                <Button type="link" onClick={() => configureApp(app)}>
+                  <SettingOutlined />
+                </Button>
+                <Button type="link" onClick={() => priceApp(app)}>
+                  <PercentageOutlined />
+                </Button>
+                <Button type="link" onClick={() => keyApp(app)}>
+                  <KeyOutlined />
+                </Button>
+                <Button type="link" onClick={() => hookApp(app)}>
+                  <NotificationOutlined />
+                </Button>
                 <Popconfirm
                   title="Êtes-vous sûr de vouloir supprimer cette application ?"
                   placement="topRight"
@@ -82,6 +117,11 @@ export const AppPage: FC = () => {
         ]}
       />
       <EditAppForm app={editedApp} onClose={handleEditAppFormClose} />
+      <CloneAppForm app={clonedApp} onClose={handleEditAppFormClose} />
+      <EditAppSettingsForm app={configuredApp} onClose={() => configureApp(undefined)} />
+      <EditAppMarkupsForm app={pricedApp} onClose={() => priceApp(undefined)} />
+      <EditAppApiKeysForm app={keyedApp} onClose={() => keyApp(undefined)} />
+      <EditAppWebhooksForm app={hookedApp} onClose={() => hookApp(undefined)} />
     </>
   );
 };
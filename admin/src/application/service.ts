@@ -1,7 +1,18 @@
 import notification from "antd/lib/notification";
 
 import request from "../_shared/request";
-import {Application} from "./model";
+import {
+  ApiKey,
+  ApiKeyScope,
+  Application,
+  AppMarkups,
+  AppSettings,
+  CloneAppOptions,
+  CreatedApiKey,
+  Webhook,
+  WebhookDelivery,
+  WebhookEvent,
+} from "./model";
 
 export async function get(): Promise<Application[]> {
   return request.get<Application[]>("/app").catch(err => {
@@ -27,5 +38,131 @@ export async function del(id: number): Promise<void> {
     .catch(err => notification.error({message: "Error", description: err.message}));
 }
 
-export const $app = {get, set, del};
+export async function clone(id: number, opts: CloneAppOptions): Promise<void> {
+  return request
+    .post(`/app/${id}/clone`, opts)
+    .then(() => notification.success({message: "Succès", description: "Application dupliquée avec succès."}))
+    .catch(err => {
+      notification.error({message: "Error", description: err.message});
+      throw err;
+    });
+}
+
+export async function getSettings(id: number): Promise<AppSettings | undefined> {
+  return request.get<AppSettings>(`/app/${id}/settings`).catch(err => {
+    notification.error({message: "Error", description: err.message});
+    return undefined;
+  });
+}
+
+export async function setSettings(id: number, settings: AppSettings): Promise<void> {
+  return request
+    .put(`/app/${id}/settings`, settings)
+    .then(() => notification.success({message: "Succès", description: "Paramètres enregistrés avec succès."}))
+    .catch(err => {
+      notification.error({message: "Error", description: err.message});
+      throw err;
+    });
+}
+
+export async function getMarkups(id: number): Promise<AppMarkups | undefined> {
+  return request.get<AppMarkups>(`/app/${id}/markups`).catch(err => {
+    notification.error({message: "Error", description: err.message});
+    return undefined;
+  });
+}
+
+export async function setMarkups(id: number, markups: AppMarkups): Promise<void> {
+  return request
+    .put(`/app/${id}/markups`, markups)
+    .then(() => notification.success({message: "Succès", description: "Marges enregistrées avec succès."}))
+    .catch(err => {
+      notification.error({message: "Error", description: err.message});
+      throw err;
+    });
+}
+
+export async function getApiKeys(id: number): Promise<ApiKey[]> {
+  return request.get<ApiKey[]>(`/app/${id}/api-key`).catch(err => {
+    notification.error({message: "Error", description: err.message});
+    return [];
+  });
+}
+
+export async function createApiKey(id: number, key: {name: string; scope: ApiKeyScope}): Promise<CreatedApiKey> {
+  return request.post<CreatedApiKey>(`/app/${id}/api-key`, key).catch(err => {
+    notification.error({message: "Error", description: err.message});
+    throw err;
+  });
+}
+
+export async function revokeApiKey(id: number, keyId: number): Promise<void> {
+  return request
+    .delete(`/app/${id}/api-key/${keyId}`)
+    .then(() => notification.success({message: "Succès", description: "Clé d'API révoquée avec succès."}))
+    .catch(err => notification.error({message: "Error", description: err.message}));
+}
+
+export async function getWebhooks(id: number): Promise<Webhook[]> {
+  return request.get<Webhook[]>(`/app/${id}/webhook`).catch(err => {
+    notification.error({message: "Error", description: err.message});
+    return [];
+  });
+}
+
+export async function setWebhook(
+  id: number,
+  webhook: {id: number; url: string; events: WebhookEvent[]; active: boolean},
+): Promise<Webhook> {
+  return request
+    .put<Webhook>(`/app/${id}/webhook`, webhook)
+    .then(webhook => {
+      notification.success({message: "Succès", description: "Webhook enregistré avec succès."});
+      return webhook;
+    })
+    .catch(err => {
+      notification.error({message: "Error", description: err.message});
+      throw err;
+    });
+}
+
+export async function delWebhook(id: number, webhookId: number): Promise<void> {
+  return request
+    .delete(`/app/${id}/webhook/${webhookId}`)
+    .then(() => notification.success({message: "Succès", description: "Webhook supprimé avec succès."}))
+    .catch(err => notification.error({message: "Error", description: err.message}));
+}
+
+export async function getWebhookDeliveries(id: number, webhookId: number): Promise<WebhookDelivery[]> {
+  return request.get<WebhookDelivery[]>(`/app/${id}/webhook/${webhookId}/delivery`).catch(err => {
+    notification.error({message: "Error", description: err.message});
+    return [];
+  });
+}
+
+export async function redeliverWebhook(id: number, webhookId: number, deliveryId: number): Promise<void> {
+  return request
+    .post(`/app/${id}/webhook/${webhookId}/delivery/${deliveryId}/redeliver`)
+    .then(() => notification.success({message: "Succès", description: "Envoi programmé avec succès."}))
+    .catch(err => notification.error({message: "Error", description: err.message}));
+}
+
+export const $app = {
+  get,
+  set,
+  del,
+  clone,
+  getSettings,
+  setSettings,
+  getMarkups,
+  setMarkups,
+  getApiKeys,
+  createApiKey,
+  revokeApiKey,
+  getWebhooks,
+  setWebhook,
+  delWebhook,
+  getWebhookDeliveries,
+  redeliverWebhook,
+};
 export default $app;
@@ -0,0 +1,102 @@
+import React, {FC, useEffect, useRef, useState} from "react";
+import Form, {FormInstance} from "antd/lib/form";
+import Input from "antd/lib/input";
+import Modal from "antd/lib/modal";
+import Select from "antd/lib/select";
+import Checkbox from "antd/lib/checkbox";
+
+import $app from "./service";
+import {Application, AppSettings, editorSteps} from "./model";
+import $material from "../material/service";
+import Material from "../material/model";
+import $template from "../template/service";
+import Template from "../template/model";
+
+type EditAppSettingsFormProps = {
+  app?: Application;
+  onClose: () => void;
+};
+
+export const EditAppSettingsForm: FC<EditAppSettingsFormProps> = ({app, onClose: close}) => {
+  const [loading, setLoading] = useState(true);
+  const [settings, setSettings] = useState<AppSettings>();
+  const [materials, setMaterials] = useState<Material[]>([]);
+  const [templates, setTemplates] = useState<Template[]>([]);
+  const form = useRef<FormInstance>(null);
+
+  useEffect(() => {
+    if (!app) return;
+    setLoading(true);
+    Promise.all([$app.getSettings(app.id), $material.get(), $template.get()])
+      .then(([settings, materials, templates]) => {
+        setSettings(settings);
+        setMaterials(materials.filter(material => app.materialIds.includes(material.id)));
+        setTemplates(templates);
+      })
+      .finally(() => setLoading(false));
+  }, [app]);
+
+  function submit() {
+    if (form.current) {
+      form.current.submit();
+    }
+  }
+
+  function save(settings: AppSettings) {
+    if (!app) return;
+    setLoading(true);
+    $app
+      .setSettings(app.id, settings)
+      .then(close)
+      .catch(() => {})
+      .finally(() => setLoading(false));
+  }
+
+  return (
+    <Modal
+      cancelText="Annuler"
+      closable={!loading}
+      confirmLoading={loading}
+      destroyOnClose
+      okText="Modifier"
+      onCancel={() => !loading && close()}
+      onOk={submit}
+      title={`Paramètres de l'éditeur${app ? ` — ${app.name}` : ""}`}
+      visible={app !== undefined}
+    >
+      {settings && (
+        <Form ref={form} onFinish={save} layout="vertical" initialValues={settings}>
+          <Form.Item label="Logo (URL)" name="logoUrl">
+            <Input disabled={loading} onPressEnter={submit} />
+          </Form.Item>
+          <Form.Item label="Palette de couleurs" name="palette" help="Couleurs au format #rrggbb">
+            <Select mode="tags" disabled={loading} placeholder="#3240ff" tokenSeparators={[",", " "]} />
+          </Form.Item>
+          <Form.Item label="Produit par défaut" name="productId">
+            <Select allowClear disabled={loading}>
+              {materials.map(({id, title}) => (
+                <Select.Option key={id} value={id}>
+                  {title}
+                </Select.Option>
+              ))}
+            </Select>
+          </Form.Item>
+          <Form.Item label="Template par défaut" name="templateId">
+            <Select allowClear showSearch optionFilterProp="children" disabled={loading}>
+              {templates.map(({id, name}) => (
+                <Select.Option key={id} value={id}>
+                  {name}
+                </Select.Option>
+              ))}
+            </Select>
+          </Form.Item>
+          <Form.Item label="Étapes de l'éditeur" name="steps">
+            <Checkbox.Group options={editorSteps} disabled={loading} />
+          </Form.Item>
+        </Form>
+      )}
+    </Modal>
+  );
+};
+
+export default EditAppSettingsForm;
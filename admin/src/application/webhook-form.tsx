@@ -0,0 +1,257 @@
+import React, {FC, useCallback, useEffect, useState} from "react";
+import Button from "antd/lib/button";
+import Form from "antd/lib/form";
+import Input from "antd/lib/input";
+import Modal from "antd/lib/modal";
+import Popconfirm from "antd/lib/popconfirm";
+import Select from "antd/lib/select";
+import Switch from "antd/lib/switch";
+import Table from "antd/lib/table";
+import Tag from "antd/lib/tag";
+import Typography from "antd/lib/typography";
+import {DeleteOutlined, EditOutlined, HistoryOutlined, RedoOutlined, SaveOutlined} from "@ant-design/icons";
+
+import $app from "./service";
+import {Application, Webhook, WebhookDelivery, WebhookDeliveryStatus, WebhookEvent, webhookEvents} from "./model";
+
+type WebhookFields = {
+  id: number;
+  url: string;
+  events: WebhookEvent[];
+  active: boolean;
+};
+
+const emptyWebhook: WebhookFields = {id: 0, url: "", events: [], active: true};
+
+const deliveryStatuses: {[status in WebhookDeliveryStatus]: {color: string; label: string}} = {
+  pending: {color: "blue", label: "En attente"},
+  delivered: {color: "green", label: "Envoyé"},
+  failed: {color: "red", label: "Échec"},
+};
+
+function eventLabel(event: WebhookEvent) {
+  return webhookEvents.find(({value}) => value === event)?.label || event;
+}
+
+type WebhookDeliveriesProps = {
+  app: Application;
+  webhook: Webhook;
+};
+
+const WebhookDeliveries: FC<WebhookDeliveriesProps> = ({app, webhook}) => {
+  const [loading, setLoading] = useState(true);
+  const [deliveries, setDeliveries] = useState<WebhookDelivery[]>([]);
+
+  const fetchDeliveries = useCallback(() => {
+    setLoading(true);
+    $app
+      .getWebhookDeliveries(app.id, webhook.id)
+      .then(setDeliveries)
+      .finally(() => setLoading(false));
+  }, [app, webhook]);
+
+  useEffect(() => {
+    fetchDeliveries();
+  }, [fetchDeliveries]);
+
+  function redeliver(deliveryId: number) {
+    setLoading(true);
+    $app.redeliverWebhook(app.id, webhook.id, deliveryId).then(fetchDeliveries);
+  }
+
+  return (
+    <Table
+      size="small"
+      dataSource={deliveries}
+      loading={loading}
+      pagination={false}
+      rowKey="id"
+      columns={[
+        {
+          title: <strong>Date</strong>,
+          render: (_, {createdAt}) => new Date(createdAt).toLocaleString(),
+        },
+        {title: <strong>Événement</strong>, render: (_, {event}) => eventLabel(event)},
+        {
+          title: <strong>Statut</strong>,
+          render: (_, {status}) => <Tag color={deliveryStatuses[status].color}>{deliveryStatuses[status].label}</Tag>,
+        },
+        {title: <strong>Tentatives</strong>, dataIndex: "attempts", align: "center"},
+        {
+          title: <strong>Réponse</strong>,
+          render: (_, {lastStatusCode, lastError}) => lastError || lastStatusCode,
+        },
+        {
+          dataIndex: "actions",
+          align: "center",
+          render: (_, {id}) => (
+            <Button type="link" title="Renvoyer" onClick={() => redeliver(id)}>
+              <RedoOutlined />
+            </Button>
+          ),
+        },
+      ]}
+    />
+  );
+};
+
+type EditAppWebhooksFormProps = {
+  app?: Application;
+  onClose: () => void;
+};
+
+export const EditAppWebhooksForm: FC<EditAppWebhooksFormProps> = ({app, onClose: close}) => {
+  const [form] = Form.useForm<WebhookFields>();
+  const [loading, setLoading] = useState(true);
+  const [webhooks, setWebhooks] = useState<Webhook[]>([]);
+  const [inspectedWebhook, inspectWebhook] = useState<Webhook | undefined>();
+
+  const fetchWebhooks = useCallback(() => {
+    if (!app) return;
+    setLoading(true);
+    $app
+      .getWebhooks(app.id)
+      .then(setWebhooks)
+      .finally(() => setLoading(false));
+  }, [app]);
+
+  useEffect(() => {
+    inspectWebhook(undefined);
+    fetchWebhooks();
+  }, [fetchWebhooks]);
+
+  function save(webhook: WebhookFields) {
+    if (!app) return;
+    setLoading(true);
+    $app
+      .setWebhook(app.id, webhook)
+      .then(() => {
+        form.setFieldsValue(emptyWebhook);
+        fetchWebhooks();
+      })
+      .catch(() => setLoading(false));
+  }
+
+  function del(webhookId: number) {
+    if (!app) return;
+    setLoading(true);
+    inspectWebhook(undefined);
+    $app.delWebhook(app.id, webhookId).then(fetchWebhooks);
+  }
+
+  return (
+    <Modal
+      closable={!loading}
+      destroyOnClose
+      footer={null}
+      onCancel={() => !loading && close()}
+      title={`Webhooks${app ? ` — ${app.name}` : ""}`}
+      visible={app !== undefined}
+      width="60rem"
+    >
+      <Form form={form} layout="vertical" onFinish={save} initialValues={emptyWebhook}>
+        <Form.Item name="id" hidden>
+          <Input />
+        </Form.Item>
+        <Form.Item
+          label="URL"
+          name="url"
+          rules={[{required: true, pattern: /^https?:\/\//, message: "URL http(s) requise"}]}
+        >
+          <Input placeholder="https://" disabled={loading} />
+        </Form.Item>
+        <Form.Item
+          label="Événements"
+          name="events"
+          rules={[{required: true, message: "Au moins un événement requis"}]}
+        >
+          <Select mode="multiple" disabled={loading}>
+            {webhookEvents.map(({value, label}) => (
+              <Select.Option key={value} value={value}>
+                {label}
+              </Select.Option>
+            ))}
+          </Select>
+        </Form.Item>
+        <Form.Item label="Actif" name="active" valuePropName="checked">
+          <Switch disabled={loading} />
+        </Form.Item>
+        <Form.Item>
+          <Button type="primary" htmlType="submit" loading={loading}>
+            <SaveOutlined />
+            Enregistrer
+          </Button>
+          <Button type="link" disabled={loading} onClick={() => form.setFieldsValue(emptyWebhook)}>
+            Nouveau
+          </Button>
+        </Form.Item>
+      </Form>
+      <Table
+        bordered
+        size="small"
+        dataSource={webhooks}
+        loading={loading}
+        pagination={false}
+        rowKey="id"
+        columns={[
+          {
+            title: <strong>URL</strong>,
+            render: (_, {url, active}) => (
+              <Typography.Text type={active ? undefined : "secondary"} delete={!active}>
+                {url}
+              </Typography.Text>
+            ),
+          },
+          {
+            title: <strong>Événements</strong>,
+            render: (_, {events}) => events.map(event => <Tag key={event}>{eventLabel(event)}</Tag>),
+          },
+          {
+            title: <strong>Secret</strong>,
+            render: (_, {secret}) => (
+              <Typography.Text code copyable>
+                {secret}
+              </Typography.Text>
+            ),
+          },
+          {
+            dataIndex: "actions",
+            align: "center",
+            width: "10rem",
+            render: (_, webhook) => (
+              <>
+                <Button type="link" onClick={() => form.setFieldsValue(webhook)}>
+                  <EditOutlined />
+                </Button>
+                <Button type="link" onClick={() => inspectWebhook(webhook)}>
+                  <HistoryOutlined />
+                </Button>
+                <Popconfirm
+                  title="Êtes-vous sûr de vouloir supprimer ce webhook ?"
+                  placement="topRight"
+                  okText="Oui"
+                  cancelText="Non"
+                  onConfirm={() => del(webhook.id)}
+                >
+                  <Button type="link" danger>
+                    <DeleteOutlined />
+                  </Button>
+                </Popconfirm>
+              </>
+            ),
+          },
+        ]}
+      />
+      {app && inspectedWebhook && (
+        <>
+          <Typography.Title level={5} style={{marginTop: "1rem"}}>
+            Envois — {inspectedWebhook.url}
+          </Typography.Title>
+          <WebhookDeliveries app={app} webhook={inspectedWebhook} />
+        </>
+      )}
+    </Modal>
+  );
+};
+
+export default EditAppWebhooksForm;
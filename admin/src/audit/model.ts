@@ -0,0 +1,59 @@
+export type AuditAction = "create" | "update" | "delete";
+
+export const auditActions: {[action in AuditAction]: {color: string; label: string}} = {
+  create: {color: "green", label: "Création"},
+  update: {color: "blue", label: "Modification"},
+  delete: {color: "red", label: "Suppression"},
+};
+
+export const auditEntities: {value: string; label: string}[] = [
+  {value: "app", label: "Application"},
+  {value: "app_settings", label: "Paramètres d'application"},
+  {value: "app_markups", label: "Marges d'application"},
+  {value: "api_key", label: "Clé d'API"},
+  {value: "webhook", label: "Webhook"},
+  {value: "badge", label: "Badge"},
+  {value: "carrier", label: "Transporteur"},
+  {value: "dimension", label: "Dimension"},
+  {value: "discount", label: "Remise"},
+  {value: "fixation", label: "Fixation"},
+  {value: "folder", label: "Dossier"},
+  {value: "font", label: "Police"},
+  {value: "holiday", label: "Jour férié"},
+  {value: "material", label: "Matériau"},
+  {value: "order", label: "Commande"},
+  {value: "picto", label: "Pictogramme"},
+  {value: "shape", label: "Forme"},
+  {value: "tax_rate", label: "Taux de TVA"},
+  {value: "template", label: "Template"},
+  {value: "upload", label: "Fichier"},
+  {value: "user", label: "Utilisateur"},
+  {value: "user_lock", label: "Verrouillage d'utilisateur"},
+  {value: "user_sessions", label: "Sessions d'utilisateur"},
+];
+
+export type AuditChange = {
+  before: any;
+  after: any;
+};
+
+export type AuditLogQuery = {
+  entity?: string;
+  entityId?: number;
+  userId?: number;
+};
+
+export type AuditLog = {
+  id: number;
+  userId: number | null;
+  subject: string;
+  entity: string;
+  entityId: number | null;
+  action: AuditAction;
+  method: string;
+  path: string;
+  changes: {[field: string]: AuditChange};
+  createdAt: string;
+};
+
+export default AuditLog;
@@ -0,0 +1,136 @@
+import React, {FC, useCallback, useEffect, useState} from "react";
+import Typography from "antd/lib/typography";
+import Table from "antd/lib/table";
+import Tag from "antd/lib/tag";
+import Button from "antd/lib/button";
+import Form from "antd/lib/form";
+import InputNumber from "antd/lib/input-number";
+import Select from "antd/lib/select";
+import {SearchOutlined} from "@ant-design/icons";
+
+import AuditLog, {AuditChange, AuditLogQuery, auditActions, auditEntities} from "./model";
+import $audit from "./service";
+import User from "../user/model";
+import $user from "../user/service";
+
+function entityLabel(entity: string) {
+  return auditEntities.find(({value}) => value === entity)?.label || entity;
+}
+
+function stringify(value: any) {
+  return value === undefined || value === null ? "—" : JSON.stringify(value);
+}
+
+const AuditChanges: FC<{changes: {[field: string]: AuditChange}}> = ({changes}) => (
+  <Table
+    size="small"
+    dataSource={Object.entries(changes).map(([field, change]) => ({field, ...change}))}
+    pagination={false}
+    rowKey="field"
+    columns={[
+      {title: <strong>Champ</strong>, dataIndex: "field"},
+      {
+        title: <strong>Avant</strong>,
+        render: (_, {before}) => <Typography.Text code>{stringify(before)}</Typography.Text>,
+      },
+      {
+        title: <strong>Après</strong>,
+        render: (_, {after}) => <Typography.Text code>{stringify(after)}</Typography.Text>,
+      },
+    ]}
+  />
+);
+
+export const AuditPage: FC = () => {
+  const [loading, setLoading] = useState(true);
+  const [query, setQuery] = useState<AuditLogQuery>({});
+  const [logs, setLogs] = useState<AuditLog[]>([]);
+  const [users, setUsers] = useState<User[]>([]);
+
+  const fetchLogs = useCallback(() => {
+    setLoading(true);
+    $audit
+      .get(query)
+      .then(setLogs)
+      .finally(() => setLoading(false));
+  }, [query]);
+
+  useEffect(() => {
+    fetchLogs();
+  }, [fetchLogs]);
+
+  useEffect(() => {
+    $user.get().then(setUsers);
+  }, []);
+
+  function userLabel({userId, subject}: AuditLog) {
+    return users.find(({id}) => id === userId)?.username || subject;
+  }
+
+  return (
+    <>
+      <Typography.Title level={1}>Journal des modifications</Typography.Title>
+      <Form layout="inline" initialValues={query} onFinish={setQuery} style={{marginBottom: "1rem"}}>
+        <Form.Item label="Élément" name="entity">
+          <Select allowClear placeholder="Tous" style={{width: "14rem"}}>
+            {auditEntities.map(({value, label}) => (
+              <Select.Option key={value} value={value}>
+                {label}
+              </Select.Option>
+            ))}
+          </Select>
+        </Form.Item>
+        <Form.Item label="Identifiant" name="entityId">
+          <InputNumber min={1} />
+        </Form.Item>
+        <Form.Item label="Utilisateur" name="userId">
+          <Select allowClear placeholder="Tous" style={{width: "12rem"}}>
+            {users.map(({id, username}) => (
+              <Select.Option key={id} value={id}>
+                {username}
+              </Select.Option>
+            ))}
+          </Select>
+        </Form.Item>
+        <Form.Item>
+          <Button type="primary" htmlType="submit" loading={loading}>
+            <SearchOutlined />
+            Rechercher
+          </Button>
+        </Form.Item>
+      </Form>
+      <Table
+        bordered
+        dataSource={logs}
+        loading={loading}
+        pagination={false}
+        rowKey="id"
+        expandable={{
+          expandedRowRender: ({changes}) => <AuditChanges changes={changes} />,
+          rowExpandable: ({changes}) => Object.keys(changes).length > 0,
+        }}
+        columns={[
+          {
+            title: <strong>Date</strong>,
+            render: (_, {createdAt}) => new Date(createdAt).toLocaleString(),
+          },
+          {title: <strong>Utilisateur</strong>, render: (_, log) => userLabel(log)},
+          {
+            title: <strong>Action</strong>,
+            render: (_, {action}) => <Tag color={auditActions[action].color}>{auditActions[action].label}</Tag>,
+          },
+          {
+            title: <strong>Élément</strong>,
+            render: (_, {entity, entityId}) => `${entityLabel(entity)}${entityId ? ` #${entityId}` : ""}`,
+          },
+          {
+            title: <strong>Champs modifiés</strong>,
+            render: (_, {changes}) => Object.keys(changes).join(", ") || "—",
+          },
+        ]}
+      />
+    </>
+  );
+};
+
+export default AuditPage;
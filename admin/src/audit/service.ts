@@ -0,0 +1,19 @@
+import notification from "antd/lib/notification";
+
+import request from "../_shared/request";
+import AuditLog, {AuditLogQuery} from "./model";
+
+export async function get({entity, entityId, userId}: AuditLogQuery): Promise<AuditLog[]> {
+  const params = new URLSearchParams();
+  if (entity) params.append("entity", entity);
+  if (entityId) params.append("entityId", entityId.toString());
+  if (userId) params.append("userId", userId.toString());
+
+  return request.get<AuditLog[]>(`/audit-log?${params}`).catch(err => {
+    notification.error({message: "Erreur", description: err.message});
+    return [];
+  });
+}
+
+const $audit = {get};
+export default $audit;
@@ -18,19 +18,28 @@ type NotAuthenticated = {
 type Authenticated = {
   type: "authenticated";
   userId: number;
+  isAdmin: boolean;
+};
+
+type Claims = {
+  sub: string;
+  role: "admin" | "member";
+  apps: number[];
 };
 
 async function signIn<T>(data: T) {
   return request
-    .post<{token: string; user_id: number}>("/public/sign-in", data)
+    .post<{token: string; refreshToken: string; userId: number; isAdmin: boolean}>("/public/sign-in", data)
     .then(res => {
       tokenStorage.set(res.token);
-      auth$.next({type: "authenticated", userId: res.user_id});
+      tokenStorage.setRefresh(res.refreshToken);
+      auth$.next({type: "authenticated", userId: res.userId, isAdmin: res.isAdmin});
     })
     .catch(err => notification.error({message: "Erreur", description: err.message}));
 }
 
 async function signOut() {
+  await request.post("/logout").catch(() => undefined);
   tokenStorage.clear();
   window.location.reload();
 }
@@ -47,12 +56,11 @@ export const useAuth = () => {
 };
 
 request
-  .get("/auth-check")
-  .then(() => {
+  .get<Claims>("/auth-check")
+  .then(claims => {
     const token = tokenStorage.get();
     if (!token) throw new Error();
-    // TODO: take user id from token
-    auth$.next({type: "authenticated", userId: 0});
+    auth$.next({type: "authenticated", userId: Number(claims.sub), isAdmin: claims.role === "admin"});
   })
   .catch(() => auth$.next({type: "not-authenticated"}));
 
@@ -0,0 +1,101 @@
+import React, {FC, useState} from "react";
+import {Link, useHistory, useLocation} from "react-router-dom";
+import Alert from "antd/lib/alert";
+import Button from "antd/lib/button";
+import Card from "antd/lib/card";
+import Form from "antd/lib/form";
+import Input from "antd/lib/input";
+import Layout from "antd/lib/layout";
+import notification from "antd/lib/notification";
+
+import useLoading from "../_shared/loading";
+import request from "../_shared/request";
+
+export const ResetPassword: FC = () => {
+  const history = useHistory();
+  const {search} = useLocation();
+  const [loading] = useLoading();
+  const [requested, setRequested] = useState(false);
+  const token = new URLSearchParams(search).get("token");
+
+  function requestReset({login}: {login: string}) {
+    request
+      .post("/public/password-reset", {login})
+      .then(() => setRequested(true))
+      .catch(err => notification.error({message: "Erreur", description: err.message}));
+  }
+
+  function reset({password}: {password: string}) {
+    request
+      .put("/public/password-reset", {token, password})
+      .then(() => {
+        notification.success({message: "Succès", description: "Mot de passe modifié avec succès"});
+        history.replace("/sign-in");
+      })
+      .catch(err => notification.error({message: "Erreur", description: err.message}));
+  }
+
+  return (
+    <Layout style={{minHeight: "100vh", justifyContent: "center", alignItems: "center", overflow: "hidden"}}>
+      <Card style={{width: "30rem"}}>
+        {token ? (
+          <Form name="reset" onFinish={reset} layout="vertical">
+            <Form.Item
+              label="Nouveau mot de passe"
+              name="password"
+              rules={[{required: true, message: "Mot de passe requis."}]}
+            >
+              <Input.Password autoFocus disabled={loading} />
+            </Form.Item>
+            <Form.Item
+              label="Confirmation"
+              name="confirmation"
+              dependencies={["password"]}
+              rules={[
+                {required: true, message: "Confirmation requise."},
+                ({getFieldValue}) => ({
+                  validator: (_, value) =>
+                    !value || getFieldValue("password") === value
+                      ? Promise.resolve()
+                      : Promise.reject(new Error("Les mots de passe ne correspondent pas.")),
+                }),
+              ]}
+            >
+              <Input.Password disabled={loading} />
+            </Form.Item>
+            <Form.Item style={{margin: 0}}>
+              <Button type="primary" htmlType="submit" loading={loading}>
+                Modifier le mot de passe
+              </Button>
+            </Form.Item>
+          </Form>
+        ) : requested ? (
+          <Alert
+            type="success"
+            message="Si un compte correspond, un lien de réinitialisation a été envoyé à son adresse email."
+          />
+        ) : (
+          <Form name="request-reset" onFinish={requestReset} layout="vertical">
+            <Form.Item
+              label="Identifiant ou email"
+              name="login"
+              rules={[{required: true, message: "Identifiant requis."}]}
+            >
+              <Input autoFocus disabled={loading} />
+            </Form.Item>
+            <Form.Item style={{margin: 0}}>
+              <Button type="primary" htmlType="submit" loading={loading}>
+                Envoyer le lien
+              </Button>
+              <Link to="/sign-in" style={{marginLeft: "1rem"}}>
+                Retour
+              </Link>
+            </Form.Item>
+          </Form>
+        )}
+      </Card>
+    </Layout>
+  );
+};
+
+export default ResetPassword;
@@ -1,5 +1,5 @@
 import React, {FC, useEffect} from "react";
-import {useHistory} from "react-router-dom";
+import {Link, useHistory} from "react-router-dom";
 import Button from "antd/lib/button";
 import Card from "antd/lib/card";
 import Form from "antd/lib/form";
@@ -38,6 +38,9 @@ export const SignIn: FC = () => {
               <Button type="primary" htmlType="submit" loading={loading}>
                 Se connecter
               </Button>
+              <Link to="/reset-password" style={{marginLeft: "1rem"}}>
+                Mot de passe oublié ?
+              </Link>
             </Form.Item>
           </Form>
         </Card>
@@ -1,4 +1,5 @@
 const AUTH_TOKEN_STORAGE_KEY = "auth-token";
+const REFRESH_TOKEN_STORAGE_KEY = "refresh-token";
 
 export const handlers = {
   get() {
@@ -7,8 +8,15 @@ export const handlers = {
   set(val: string) {
     localStorage.setItem(AUTH_TOKEN_STORAGE_KEY, val);
   },
+  getRefresh() {
+    return localStorage.getItem(REFRESH_TOKEN_STORAGE_KEY);
+  },
+  setRefresh(val: string) {
+    localStorage.setItem(REFRESH_TOKEN_STORAGE_KEY, val);
+  },
   clear() {
     localStorage.removeItem(AUTH_TOKEN_STORAGE_KEY);
+    localStorage.removeItem(REFRESH_TOKEN_STORAGE_KEY);
   },
 };
 
@@ -0,0 +1,32 @@
+import React, {FC, useEffect, useState} from "react";
+import Form from "antd/lib/form";
+import Select from "antd/lib/select";
+
+import Carrier from "./model";
+import $carrier from "./service";
+
+export const SelectCarriersFormItem: FC = () => {
+  const [loading, setLoading] = useState(true);
+  const [carriers, setCarriers] = useState<Carrier[]>([]);
+
+  useEffect(() => {
+    $carrier
+      .get()
+      .then(setCarriers)
+      .then(() => setLoading(false));
+  }, []);
+
+  return (
+    <Form.Item label="Transporteurs" name="carrierIds" hasFeedback>
+      <Select mode="multiple" disabled={loading}>
+        {carriers.map(({id, name}) => (
+          <Select.Option key={id} value={id}>
+            {name}
+          </Select.Option>
+        ))}
+      </Select>
+    </Form.Item>
+  );
+};
+
+export default SelectCarriersFormItem;
@@ -0,0 +1,17 @@
+export class ShippingBracket {
+  id: number = 0;
+  carrierId: number = 0;
+  maxWeight: number = 0;
+  price: number = 0;
+}
+
+export class Carrier {
+  id: number = 0;
+  name: string = "";
+  maxLength: number = 0;
+  maxWidth: number = 0;
+  transitDays: number = 2;
+  brackets: ShippingBracket[] = [];
+}
+
+export default Carrier;
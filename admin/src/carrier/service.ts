@@ -0,0 +1,31 @@
+import notification from "antd/lib/notification";
+
+import request from "../_shared/request";
+import Carrier from "./model";
+
+export async function get(): Promise<Carrier[]> {
+  return request.get<Carrier[]>("/carrier").catch(err => {
+    notification.error({message: "Erreur", description: err.message});
+    return [];
+  });
+}
+
+export async function set(carrier: Carrier): Promise<void> {
+  return request
+    .put("/carrier", carrier)
+    .then(() => notification.success({message: "Succès", description: "Transporteur enregistré avec succès"}))
+    .catch(err => {
+      notification.error({message: "Erreur", description: err.message});
+      throw err;
+    });
+}
+
+export async function del(id: number): Promise<void> {
+  return request
+    .delete(`/carrier/${id}`)
+    .then(() => notification.success({message: "Succès", description: "Transporteur supprimé avec succès"}))
+    .catch(err => notification.error({message: "Erreur", description: err.message}));
+}
+
+const $carrier = {get, set, del};
+export default $carrier;
@@ -14,12 +14,12 @@ export async function get(): Promise<Fixation[]>;
 export async function get(id: number): Promise<FixationResponse>;
 export async function get(id?: number): Promise<Fixation[] | FixationResponse> {
   if (typeof id === "number") {
-    return request.get<FixationResponse>(`/public/fixation/${id}/conditions`).catch(err => {
+    return request.get<FixationResponse>(`/fixation/${id}/conditions`).catch(err => {
       notification.error({message: "Erreur", description: err.message});
       return {fixation: new Fixation(), conditions: [], shapes: []};
     });
   } else {
-    return request.get<Fixation[]>("/public/fixation").catch(err => {
+    return request.get<Fixation[]>("/fixation").catch(err => {
       notification.error({message: "Erreur", description: err.message});
       return [];
     });
@@ -4,7 +4,7 @@ import request from "../_shared/request";
 import Material from "./model";
 
 export async function get(): Promise<Material[]> {
-  return request.get<Material[]>("/public/material").catch(err => {
+  return request.get<Material[]>("/material").catch(err => {
     notification.error({message: "Erreur", description: err.message});
     return [];
   });
@@ -5,12 +5,14 @@ import Form, {FormInstance} from "antd/lib/form";
 import Input from "antd/lib/input";
 import Modal from "antd/lib/modal";
 import Popconfirm from "antd/lib/popconfirm";
+import Select from "antd/lib/select";
 import Upload, {UploadChangeParam} from "antd/lib/upload";
 
 import tokenStorage from "../auth/token-storage";
 import $picto from "./service";
 import Picto from "./model";
 import useLoading from "../_shared/loading";
+import {Folder, isFolder} from "../_shared/folder";
 
 type EditPictoFormProps = {
   picto?: Picto;
@@ -139,4 +141,38 @@ export const EditPictoForm: FC<EditPictoFormProps> = ({picto, onClose}) => {
   );
 };
 
+function flatten(folder: Folder<Picto>): Picto[] {
+  return folder.children.flatMap(node => (isFolder(node) ? flatten(node.folder) : [node.item]));
+}
+
+type SelectPictosFormItemProps = {
+  label?: string;
+  help?: string;
+};
+
+export const SelectPictosFormItem: FC<SelectPictosFormItemProps> = ({label = "Pictos", help}) => {
+  const [loading, setLoading] = useState(true);
+  const [pictos, setPictos] = useState<Picto[]>([]);
+
+  useEffect(() => {
+    $picto
+      .get()
+      .then(flatten)
+      .then(setPictos)
+      .then(() => setLoading(false));
+  }, []);
+
+  return (
+    <Form.Item label={label} name="pictoIds" help={help} hasFeedback>
+      <Select mode="multiple" disabled={loading}>
+        {pictos.map(({id, url}) => (
+          <Select.Option key={id} value={id}>
+            <img src={`${process.env.REACT_APP_API_URL}/public/${url}`} style={{width: "1rem"}} />
+          </Select.Option>
+        ))}
+      </Select>
+    </Form.Item>
+  );
+};
+
 export default EditPictoForm;
@@ -6,7 +6,7 @@ import Picto from "./model";
 
 export async function get(): Promise<Folder<Picto>> {
   return request
-    .get<TreeNode<Picto>[]>("/public/picto")
+    .get<TreeNode<Picto>[]>("/picto")
     .catch(err => {
       notification.error({message: "Erreur", description: err.message});
       return [];
@@ -140,7 +140,12 @@ export const EditShapeForm: FC<EditShapeFormProps> = ({shape, onClose}) => {
   );
 };
 
-export const SelectShapesFormItem: FC = () => {
+type SelectShapesFormItemProps = {
+  label?: string;
+  help?: string;
+};
+
+export const SelectShapesFormItem: FC<SelectShapesFormItemProps> = ({label = "Shapes", help}) => {
   const [loading, setLoading] = useState(true);
   const [shapes, setShapes] = useState<Shape[]>([]);
 
@@ -152,7 +157,7 @@ export const SelectShapesFormItem: FC = () => {
   }, []);
 
   return (
-    <Form.Item label="Shapes" name="shapeIds" hasFeedback>
+    <Form.Item label={label} name="shapeIds" help={help} hasFeedback>
       <Select mode="multiple" disabled={loading}>
         {shapes.map(({id, url}) => (
           <Select.Option key={id} value={id}>
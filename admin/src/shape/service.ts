@@ -5,7 +5,7 @@ import {TreeNode, Folder} from "../_shared/folder";
 import Shape from "./model";
 
 export async function get(): Promise<Shape[]> {
-  return request.get<Shape[]>("/public/shape").catch(err => {
+  return request.get<Shape[]>("/shape").catch(err => {
     notification.error({message: "Erreur", description: err.message});
     return [];
   });
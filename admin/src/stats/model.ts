@@ -0,0 +1,30 @@
+export type StatsDimension = "app" | "material" | "shape" | "fixation";
+
+export const statsDimensions: {value: StatsDimension; label: string}[] = [
+  {value: "app", label: "Application"},
+  {value: "material", label: "Matériau"},
+  {value: "shape", label: "Forme"},
+  {value: "fixation", label: "Fixation"},
+];
+
+export type QuoteStatsQuery = {
+  from: string;
+  to: string;
+  appId?: number;
+  groupBy: StatsDimension[];
+};
+
+export type QuoteStats = {
+  appId?: number;
+  materialId?: number;
+  shapeId?: number;
+  fixationId?: number;
+  quotes: number;
+  avgWidth: number;
+  avgHeight: number;
+  avgQuantity: number;
+  orders: number;
+  conversion: number;
+};
+
+export default QuoteStats;
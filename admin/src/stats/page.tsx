@@ -0,0 +1,172 @@
+import React, {FC, useCallback, useEffect, useState} from "react";
+import Typography from "antd/lib/typography";
+import Table from "antd/lib/table";
+import Button from "antd/lib/button";
+import Checkbox from "antd/lib/checkbox";
+import Form from "antd/lib/form";
+import Input from "antd/lib/input";
+import Select from "antd/lib/select";
+import {SearchOutlined} from "@ant-design/icons";
+
+import QuoteStats, {QuoteStatsQuery, statsDimensions} from "./model";
+import $stats from "./service";
+import {Application} from "../application/model";
+import $app from "../application/service";
+import Material from "../material/model";
+import $material from "../material/service";
+import Fixation from "../fixation/model";
+import $fixation from "../fixation/service";
+import Shape from "../shape/model";
+import $shape from "../shape/service";
+
+function isoDate(date: Date) {
+  return date.toISOString().slice(0, 10);
+}
+
+function defaultQuery(): QuoteStatsQuery {
+  const to = new Date();
+  const from = new Date(to.getTime() - 30 * 24 * 60 * 60 * 1000);
+  return {from: isoDate(from), to: isoDate(to), groupBy: ["app", "material"]};
+}
+
+function percent(ratio: number) {
+  return `${Math.round(ratio * 1000) / 10} %`;
+}
+
+export const StatsPage: FC = () => {
+  const [loading, setLoading] = useState(true);
+  const [query, setQuery] = useState<QuoteStatsQuery>(defaultQuery);
+  const [stats, setStats] = useState<QuoteStats[]>([]);
+  const [apps, setApps] = useState<Application[]>([]);
+  const [materials, setMaterials] = useState<Material[]>([]);
+  const [shapes, setShapes] = useState<Shape[]>([]);
+  const [fixations, setFixations] = useState<Fixation[]>([]);
+
+  const fetchStats = useCallback(() => {
+    setLoading(true);
+    $stats
+      .getQuoteStats(query)
+      .then(setStats)
+      .finally(() => setLoading(false));
+  }, [query]);
+
+  useEffect(() => {
+    fetchStats();
+  }, [fetchStats]);
+
+  useEffect(() => {
+    $app.get().then(setApps);
+    $material.get().then(setMaterials);
+    $shape.get().then(setShapes);
+    $fixation.get().then(setFixations);
+  }, []);
+
+  const unknown = <Typography.Text type="secondary">Supprimé</Typography.Text>;
+  const grouped = (dimension: string) => query.groupBy.some(value => value === dimension);
+
+  return (
+    <>
+      <Typography.Title level={1}>Statistiques des devis</Typography.Title>
+      <Form layout="inline" initialValues={query} onFinish={setQuery} style={{marginBottom: "1rem"}}>
+        <Form.Item label="Du" name="from" rules={[{required: true, message: "Date requise"}]}>
+          <Input type="date" />
+        </Form.Item>
+        <Form.Item label="Au" name="to" rules={[{required: true, message: "Date requise"}]}>
+          <Input type="date" />
+        </Form.Item>
+        <Form.Item label="Application" name="appId">
+          <Select allowClear placeholder="Toutes" style={{width: "12rem"}}>
+            {apps.map(({id, name}) => (
+              <Select.Option key={id} value={id}>
+                {name}
+              </Select.Option>
+            ))}
+          </Select>
+        </Form.Item>
+        <Form.Item label="Par" name="groupBy">
+          <Checkbox.Group options={statsDimensions} />
+        </Form.Item>
+        <Form.Item>
+          <Button type="primary" htmlType="submit" loading={loading}>
+            <SearchOutlined />
+            Rechercher
+          </Button>
+        </Form.Item>
+      </Form>
+      <Table
+        bordered
+        dataSource={stats}
+        loading={loading}
+        pagination={false}
+        rowKey={({appId, materialId, shapeId, fixationId}) => [appId, materialId, shapeId, fixationId].join("-")}
+        columns={[
+          ...(grouped("app")
+            ? [
+                {
+                  title: <strong>Application</strong>,
+                  render: (_: any, {appId}: QuoteStats) => apps.find(({id}) => id === appId)?.name || unknown,
+                },
+              ]
+            : []),
+          ...(grouped("material")
+            ? [
+                {
+                  title: <strong>Matériau</strong>,
+                  render: (_: any, {materialId}: QuoteStats) =>
+                    materials.find(({id}) => id === materialId)?.title || unknown,
+                },
+              ]
+            : []),
+          ...(grouped("shape")
+            ? [
+                {
+                  title: <strong>Forme</strong>,
+                  render: (_: any, {shapeId}: QuoteStats) => {
+                    if (!shapeId) return "—";
+                    const shape = shapes.find(({id}) => id === shapeId);
+                    return shape ? <img src={shape.url} alt={shape.tags} style={{height: "1.5rem"}} /> : unknown;
+                  },
+                },
+              ]
+            : []),
+          ...(grouped("fixation")
+            ? [
+                {
+                  title: <strong>Fixation</strong>,
+                  render: (_: any, {fixationId}: QuoteStats) =>
+                    fixationId ? fixations.find(({id}) => id === fixationId)?.name || unknown : "—",
+                },
+              ]
+            : []),
+          {
+            title: <strong>Devis</strong>,
+            dataIndex: "quotes",
+            align: "right" as const,
+          },
+          {
+            title: <strong>Taille moyenne (mm)</strong>,
+            align: "right" as const,
+            render: (_: any, {avgWidth, avgHeight}: QuoteStats) => `${Math.round(avgWidth)} × ${Math.round(avgHeight)}`,
+          },
+          {
+            title: <strong>Quantité moyenne</strong>,
+            align: "right" as const,
+            render: (_: any, {avgQuantity}: QuoteStats) => Math.round(avgQuantity * 10) / 10,
+          },
+          {
+            title: <strong>Commandes</strong>,
+            dataIndex: "orders",
+            align: "right" as const,
+          },
+          {
+            title: <strong>Conversion</strong>,
+            align: "right" as const,
+            render: (_: any, {conversion}: QuoteStats) => percent(conversion),
+          },
+        ]}
+      />
+    </>
+  );
+};
+
+export default StatsPage;
@@ -0,0 +1,17 @@
+import notification from "antd/lib/notification";
+
+import request from "../_shared/request";
+import QuoteStats, {QuoteStatsQuery} from "./model";
+
+export async function getQuoteStats({from, to, appId, groupBy}: QuoteStatsQuery): Promise<QuoteStats[]> {
+  const params = new URLSearchParams({from, to, groupBy: groupBy.join(",")});
+  if (appId) params.append("appId", appId.toString());
+
+  return request.get<QuoteStats[]>(`/quote-event/stats?${params}`).catch(err => {
+    notification.error({message: "Erreur", description: err.message});
+    return [];
+  });
+}
+
+const $stats = {getQuoteStats};
+export default $stats;
@@ -100,7 +100,12 @@ export const EditTemplateForm: FC<EditTemplateFormProps> = ({template, encodedPa
   );
 };
 
-export const SelectTemplatesFormItem: FC = () => {
+type SelectTemplatesFormItemProps = {
+  label?: string;
+  help?: string;
+};
+
+export const SelectTemplatesFormItem: FC<SelectTemplatesFormItemProps> = ({label = "Templates", help}) => {
   const [loading, setLoading] = useState(true);
   const [templates, setTemplates] = useState<Template[]>([]);
 
@@ -112,7 +117,7 @@ export const SelectTemplatesFormItem: FC = () => {
   }, []);
 
   return (
-    <Form.Item label="Templates" name="templateIds" hasFeedback>
+    <Form.Item label={label} name="templateIds" help={help} hasFeedback>
       <Select mode="multiple" disabled={loading}>
         {templates.map(({id, name}) => (
           <Select.Option key={id} value={id}>
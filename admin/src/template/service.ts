@@ -8,12 +8,12 @@ export async function get(): Promise<Template[]>;
 export async function get(id: number): Promise<Template>;
 export async function get(id?: number): Promise<Template[] | Template> {
   if (typeof id === "number") {
-    return request.get<Template>(`/public/template/${id}`).catch(err => {
+    return request.get<Template>(`/template/${id}`).catch(err => {
       notification.error({message: "Erreur", description: err.message});
       return new Template(null);
     });
   } else {
-    return request.get<Template[]>("/public/template").catch(err => {
+    return request.get<Template[]>("/template").catch(err => {
       notification.error({message: "Erreur", description: err.message});
       return [];
     });
@@ -51,6 +51,9 @@ export const EditUserForm: FC<EditUserFormProps> = ({user, onClose: close}) => {
         <Form.Item label="Identifiant" name="username" rules={[{required: true, message: "Identifiant requis."}]}>
           <Input autoFocus disabled={loading} onPressEnter={submit} />
         </Form.Item>
+        <Form.Item label="Email" name="email" rules={[{type: "email", message: "Email invalide."}]}>
+          <Input type="email" disabled={loading} onPressEnter={submit} />
+        </Form.Item>
         <Form.Item
           label="Mot de passe"
           name="password"
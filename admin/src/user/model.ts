@@ -3,6 +3,7 @@ export class User {
   username: string = "";
   password: string = "";
   isAdmin: boolean = false;
+  email: string | null = null;
 }
 
 export default User;
@@ -3,7 +3,7 @@ import Typography from "antd/lib/typography";
 import Table from "antd/lib/table";
 import Button from "antd/lib/button";
 import Popconfirm from "antd/lib/popconfirm";
-import {PlusOutlined, DeleteOutlined, EditOutlined} from "@ant-design/icons";
+import {PlusOutlined, DeleteOutlined, EditOutlined, LogoutOutlined, UnlockOutlined} from "@ant-design/icons";
 
 import User from "./model";
 import $user from "./service";
@@ -53,6 +53,10 @@ export const UserPage: FC = () => {
             title: <strong>Identifiant</strong>,
             dataIndex: "username",
           },
+          {
+            title: <strong>Email</strong>,
+            dataIndex: "email",
+          },
           {
             title: () => (
               <>
@@ -64,12 +68,26 @@ export const UserPage: FC = () => {
             ),
             dataIndex: "actions",
             align: "center",
-            width: "10rem",
+            width: "16rem",
             render: (_, user) => (
               <>
                 <Button type="link" onClick={() => editUser(user)}>
                   <EditOutlined />
                 </Button>
+                <Button type="link" title="Déverrouiller" onClick={() => $user.unlock(user.id)}>
+                  <UnlockOutlined />
+                </Button>
+                <Popconfirm
+                  title="Êtes-vous sûr de vouloir déconnecter cet utilisateur partout ?"
+                  placement="topRight"
+                  okText="Oui"
+                  cancelText="Non"
+                  onConfirm={() => $user.revokeSessions(user.id)}
+                >
+                  <Button type="link" title="Révoquer les sessions">
+                    <LogoutOutlined />
+                  </Button>
+                </Popconfirm>
                 <Popconfirm
                   title="Êtes-vous sûr de vouloir supprimer cet utilisateur ?"
                   placement="topRight"
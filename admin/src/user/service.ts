@@ -29,5 +29,19 @@ export async function del(id: number): Promise<void> {
     .catch(err => notification.error({message: "Erreur", description: err.message}));
 }
 
-const $user = {get, set, del};
+export async function revokeSessions(id: number): Promise<void> {
+  return request
+    .delete(`/user/${id}/session`)
+    .then(() => notification.success({message: "Succès", description: "Sessions de l'utilisateur révoquées avec succès"}))
+    .catch(err => notification.error({message: "Erreur", description: err.message}));
+}
+
+export async function unlock(id: number): Promise<void> {
+  return request
+    .delete(`/user/${id}/lock`)
+    .then(() => notification.success({message: "Succès", description: "Utilisateur déverrouillé avec succès"}))
+    .catch(err => notification.error({message: "Erreur", description: err.message}));
+}
+
+const $user = {get, set, del, revokeSessions, unlock};
 export default $user;
@@ -11,6 +11,7 @@ import {CanvasContextProvider, CanvasSpinner, PopoverContextProvider} from "./ca
 import {OrderContextProvider} from "./order";
 import {CreatorProps} from "./app.types";
 import {Stepper} from "./stepper";
+import {settings, editorSteps} from "./settings";
 import smallDeviceWarning from "./small-device-warning.png";
 
 import cs from "./app.module.scss";
@@ -34,26 +35,40 @@ fabric.Image.prototype.getSvgSrc = function (options) {
 };
 
 const Header: FC = () => {
-  return <header className={cs.header}>Creator</header>;
+  return (
+    <header className={cs.header}>
+      {settings.logoUrl ? <img className={cs.logo} src={settings.logoUrl} alt={settings.name} /> : "Creator"}
+    </header>
+  );
 };
 
 const App: FC<CreatorProps> = props => {
+  // Props set by the host page take precedence over the app settings.
+  const templateId = props.templateId !== undefined ? props.templateId : settings.templateId;
+  const productId = props.productId !== undefined ? props.productId : settings.productId;
+  const steps = editorSteps.filter(step => settings.steps.includes(step)).map(step => editorSteps.indexOf(step));
+  const step = props.step !== undefined ? props.step : steps[0];
+
   const isStepDefined = props.step !== undefined;
-  const isTemplateDefined = props.templateId !== undefined;
+  const isTemplateDefined = templateId !== undefined;
   const isConfigDefined = props.config !== undefined && Object.keys(props.config).length > 0;
 
   return (
     <AsyncContextProvider>
       <CanvasContextProvider>
         <PopoverContextProvider>
-          <OrderContextProvider templateId={props.templateId} config={props.config} productId={props.productId}>
+          <OrderContextProvider templateId={templateId} config={props.config} productId={productId}>
             <div className={cs.container}>
               <Header />
               <Nav />
               <Main />
               <CanvasSpinner />
               <Footer submitComponent={props.submitComponent} />
-              <Stepper step={props.step} enable={isStepDefined || (!isTemplateDefined && !isConfigDefined)} />
+              <Stepper
+                step={step}
+                steps={steps}
+                enable={steps.length > 0 && (isStepDefined || (!isTemplateDefined && !isConfigDefined))}
+              />
             </div>
             <div className={cs.smallDeviceWarning} style={{backgroundImage: `url(${smallDeviceWarning})`}} />
           </OrderContextProvider>
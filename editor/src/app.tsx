@@ -2,6 +2,7 @@ import React, {FC, Suspense, lazy} from "react";
 import "tippy.js/dist/tippy.css";
 
 import {CreatorProps} from "./app.types";
+import {setAppKey} from "./request";
 import "./index.scss";
 
 const Loader: FC = () => (
@@ -29,16 +30,22 @@ const LazyApp = lazy(
     new Promise<{default: FC<CreatorProps>}>(async resolve => {
       // TODO: import products, dimensions, shapes and fixations
       const app = await import("./app.lazy");
+      const $settings = await import("./settings/service");
+      await $settings.initSettings();
       const initFonts = await import("./font/service").then(m => m.initFonts);
-      await initFonts();
+      await initFonts($settings.settings.fonts.map(font => font.name));
       resolve(app);
     }),
 );
 
-export const App: FC<CreatorProps> = props => (
-  <Suspense fallback={<Loader />}>
-    <LazyApp {...props} />
-  </Suspense>
-);
+export const App: FC<CreatorProps> = props => {
+  setAppKey(props.appKey);
+
+  return (
+    <Suspense fallback={<Loader />}>
+      <LazyApp {...props} />
+    </Suspense>
+  );
+};
 
 export default App;
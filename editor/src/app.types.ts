@@ -22,6 +22,7 @@ export type CreatorSubmitComponentProps = {
 };
 
 export type CreatorProps = {
+  appKey?: string;
   templateId?: number;
   config?: Record<string, any>;
   step?: number;
@@ -1,8 +1,10 @@
+import qs from "query-string";
+
 import request from "../request";
 import {Discount} from "./model";
 
-export async function fetchDiscounts(sig: AbortSignal): Promise<Discount[]> {
-  return request.get(sig, "/public/discount/");
+export async function fetchDiscounts(sig: AbortSignal, materialId?: number): Promise<Discount[]> {
+  return request.get(sig, qs.stringifyUrl({url: "/public/discount/", query: {materialId}}));
 }
 
 export default {fetchDiscounts};
@@ -9,4 +9,11 @@ export class Fixation {
   videoUrl: string | null = null;
 }
 
+export type DrillHole = {
+  position: "tl" | "tc" | "tr" | "cl" | "cr" | "bl" | "bc" | "br";
+  x: number;
+  y: number;
+  diameter: number;
+};
+
 export default Fixation;
@@ -7,7 +7,7 @@ export let fontFamilies: string[];
 type ResponseFont = {family: string};
 type ResponseFonts = {items: ResponseFont[]};
 
-export async function initFonts() {
+async function fetchPopularFamilies(): Promise<string[]> {
   const url = qs.stringifyUrl({
     url: "https://www.googleapis.com/webfonts/v1/webfonts",
     query: {key: process.env.REACT_APP_GOOGLE_FONTS_API_KEY, sort: "popularity"},
@@ -17,8 +17,16 @@ export async function initFonts() {
       if (!res.ok) throw new Error(res.statusText);
       return res.json();
     })
-    .then((res: ResponseFonts) => res.items.slice(0, 50).map(item => item.family))
-    .then((families: string[]) => {
+    .then((res: ResponseFonts) => res.items.slice(0, 50).map(item => item.family));
+}
+
+/**
+ * Loads the given Google font families, or the 50 most popular ones when
+ * the app does not define its own font list.
+ */
+export async function initFonts(appFamilies: string[] = []) {
+  return (appFamilies.length > 0 ? Promise.resolve(appFamilies) : fetchPopularFamilies()).then(
+    (families: string[]) => {
       fontFamilies = families;
       families.forEach(family => {
         // @ts-ignore
@@ -33,7 +41,8 @@ export async function initFonts() {
           active: resolve,
         });
       });
-    });
+    },
+  );
 }
 
 export default {initFonts};
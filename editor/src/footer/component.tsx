@@ -21,10 +21,12 @@ export const Footer: FC<FooterProps> = props => {
   const [discounts, setDiscounts] = useState<Discount[]>([]);
   const [quantityStr, setQuantityStr] = useState(order && order.quantity ? order.quantity.toString() : "1");
 
+  const materialId = order && order.product ? order.product.id : undefined;
+
   useEffect(() => {
     const {signal} = new AbortController();
-    fetchDiscounts(signal).then(setDiscounts);
-  }, []);
+    fetchDiscounts(signal, materialId).then(setDiscounts);
+  }, [materialId]);
 
   function quantityDec() {
     if (!order) return;
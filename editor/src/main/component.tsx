@@ -345,8 +345,8 @@ export const Main: FC = () => {
           const HOLE_STROKE_WIDTH = 1;
           const holeWidth = (order.fixation.drillDiameter * 0.1 * canvas.clipPath.getScaledWidth()) / order.width;
           const holeRadius = holeWidth * 0.5 + HOLE_STROKE_WIDTH;
-          const holeOffsetH = fixationWidth * 0.5 + order.condition.paddingH;
-          const holeOffsetV = fixationWidth * 0.5 + order.condition.paddingV;
+          // Holes are computed by the server in mm from the top left corner
+          const mmToPx = overlayWidth / (order.width * 10);
 
           const holeObjProps: fabric.ICircleOptions = {
             name: "drill-hole",
@@ -360,26 +360,15 @@ export const Main: FC = () => {
             originY: "center",
           };
 
-          const addHoleIfPos = (pos: keyof FixationCondition, left: number, top: number) => {
-            if (order.condition && order.condition[pos]) {
-              objs.push(
-                new fabric.Circle({
-                  ...holeObjProps,
-                  left: overlayCenter.x + left,
-                  top: overlayCenter.y + top,
-                }),
-              );
-            }
-          };
-
-          addHoleIfPos("posTl", -overlayWidth * 0.5 + holeOffsetH, -overlayHeight * 0.5 + holeOffsetV);
-          addHoleIfPos("posTc", 0, -overlayHeight * 0.5 + holeOffsetV);
-          addHoleIfPos("posTr", overlayWidth * 0.5 - holeOffsetH, -overlayHeight * 0.5 + holeOffsetV);
-          addHoleIfPos("posCl", -overlayWidth * 0.5 + holeOffsetH, 0);
-          addHoleIfPos("posCr", overlayWidth * 0.5 - holeOffsetH, 0);
-          addHoleIfPos("posBl", -overlayWidth * 0.5 + holeOffsetH, overlayHeight * 0.5 - holeOffsetV);
-          addHoleIfPos("posBc", 0, overlayHeight * 0.5 - holeOffsetV);
-          addHoleIfPos("posBr", overlayWidth * 0.5 - holeOffsetH, overlayHeight * 0.5 - holeOffsetV);
+          order.holes.forEach(hole => {
+            objs.push(
+              new fabric.Circle({
+                ...holeObjProps,
+                left: overlayCenter.x - overlayWidth * 0.5 + hole.x * mmToPx,
+                top: overlayCenter.y - overlayHeight * 0.5 + hole.y * mmToPx,
+              }),
+            );
+          });
           canvas.add(...objs);
           objs.forEach(obj => obj.bringToFront());
 
@@ -2,7 +2,7 @@ import qs from "query-string";
 
 import request from "../request";
 import {Order, orderToStringifiableRecord} from "./model";
-import {Fixation, FixationCondition} from "../fixation";
+import {DrillHole, Fixation, FixationCondition} from "../fixation";
 
 type FetchOrderResponse = {
   discount: number;
@@ -13,6 +13,7 @@ type FetchOrderResponse = {
   totalTaxInclDiscounted: number;
   fixation: Fixation | null;
   condition: FixationCondition | null;
+  holes: DrillHole[];
 };
 
 export async function fetchOrder(sig: AbortSignal, order: Order): Promise<FetchOrderResponse> {
@@ -41,6 +41,7 @@ export const OrderContextProvider: FC<OrderContextProviderProps> = props => {
               totalTaxInclDiscounted: order.totalTaxInclDiscounted,
               fixation: order.fixation || null,
               condition: order.condition || null,
+              holes: order.holes || [],
             }),
           );
         });
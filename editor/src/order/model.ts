@@ -1,5 +1,5 @@
 import {Product} from "../product";
-import {Fixation, FixationCondition} from "../fixation";
+import {DrillHole, Fixation, FixationCondition} from "../fixation";
 import {Shape} from "../shape";
 
 export type Order = {
@@ -17,6 +17,7 @@ export type Order = {
   product?: Product;
   fixation?: Fixation;
   condition?: FixationCondition;
+  holes: DrillHole[];
   shape?: Shape;
 };
 
@@ -33,6 +34,7 @@ export function newOrder(): Order {
     width: 0,
     height: 0,
     weight: 0,
+    holes: [],
   };
 }
 
@@ -5,12 +5,22 @@ type RequestProxy = {[key in RequestMethod]: RequestFn};
 
 const methods: RequestMethod[] = ["get", "post", "patch", "put", "delete"];
 
+let appKey: string | undefined;
+
+export function setAppKey(key?: string) {
+  appKey = key;
+}
+
 const request =
   <T>(method: RequestMethod) =>
   async (signal: AbortSignal, path: string, data?: RequestData) => {
     const headers = new Headers();
     let body = data;
 
+    if (appKey) {
+      headers.append("X-App-Key", appKey);
+    }
+
     if (data !== undefined && !(data instanceof FormData)) {
       headers.append("Content-Type", "application/json");
       body = JSON.stringify(data);
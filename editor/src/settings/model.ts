@@ -0,0 +1,30 @@
+export type EditorStep = "product" | "dimension" | "fixation";
+
+export const editorSteps: EditorStep[] = ["product", "dimension", "fixation"];
+
+export type SettingsFont = {
+  id: number;
+  name: string;
+  url: string;
+};
+
+export type Settings = {
+  name: string;
+  logoUrl?: string;
+  palette: string[];
+  templateId?: number;
+  productId?: number;
+  steps: EditorStep[];
+  fonts: SettingsFont[];
+};
+
+export function emptySettings(): Settings {
+  return {
+    name: "",
+    palette: [],
+    steps: editorSteps,
+    fonts: [],
+  };
+}
+
+export default Settings;
@@ -0,0 +1,17 @@
+import request from "../request";
+import {Settings, emptySettings} from "./model";
+
+export let settings: Settings = emptySettings();
+
+export async function initSettings() {
+  return request
+    .get<Settings>(new AbortController().signal, "/public/settings/")
+    .then(res => {
+      settings = res;
+    })
+    .catch(() => {
+      settings = emptySettings();
+    });
+}
+
+export default {initSettings};
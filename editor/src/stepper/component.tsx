@@ -12,20 +12,51 @@ import {ReactComponent as IconPrev} from "./prev.svg";
 import {ReactComponent as IconNext} from "./next.svg";
 import cs from "./component.module.scss";
 
+const allSteps = [0, 1, 2];
+
 export type StepperProps = {
   enable: boolean;
   step?: number;
+  steps?: number[];
 };
 
 export const Stepper: FC<StepperProps> = props => {
+  const steps = props.steps || allSteps;
   const [enable, setEnable] = useState(props.enable);
-  const [stepIdx, setStepIdx] = useState(props.step || 0);
+  const [stepIdx, setStepIdx] = useState(props.step || steps[0] || 0);
   const order = useOrder();
 
   if (!enable) {
     return null;
   }
 
+  const prevStep = steps.filter(idx => idx < stepIdx).pop();
+  const nextStep = steps.find(idx => idx > stepIdx);
+  const isLastStep = nextStep === undefined;
+  const isLastStepOf = (idx: number) => steps.indexOf(idx) === steps.length - 1;
+
+  function goPrev() {
+    if (prevStep !== undefined) setStepIdx(prevStep);
+  }
+
+  function goNext() {
+    if (nextStep === undefined) setEnable(false);
+    else setStepIdx(nextStep);
+  }
+
+  const btnPrev = prevStep !== undefined && (
+    <button className={cs.btnPrev} onClick={goPrev}>
+      <IconPrev className={cs.btnPrevIcon} />
+    </button>
+  );
+
+  const btnNext = (
+    <button className={cs.btnNext} onClick={goNext}>
+      {isLastStep ? "Commencer la création" : "Étape suivante"}
+      <IconNext className={cs.btnNextIcon} />
+    </button>
+  );
+
   const currentStep = (() => {
     switch (stepIdx) {
       case 0: {
@@ -34,13 +65,11 @@ export const Stepper: FC<StepperProps> = props => {
             footer={() => {
               return (
                 <>
+                  {btnPrev}
                   <span>
                     Produit : <strong>{order.product && order.product.title}</strong>
                   </span>
-                  <button className={cs.btnNext} onClick={() => setStepIdx(1)}>
-                    Étape suivante
-                    <IconNext className={cs.btnNextIcon} />
-                  </button>
+                  {btnNext}
                 </>
               );
             }}
@@ -60,19 +89,14 @@ export const Stepper: FC<StepperProps> = props => {
             footer={() => {
               return (
                 <>
-                  <button className={cs.btnPrev} onClick={() => setStepIdx(0)}>
-                    <IconPrev className={cs.btnPrevIcon} />
-                  </button>
+                  {btnPrev}
                   <span>
                     Dimensions :{" "}
                     <strong>
                       {order.width} &times; {order.height} cm{orientationStr}
                     </strong>
                   </span>
-                  <button className={cs.btnNext} onClick={() => setStepIdx(2)}>
-                    Étape suivante
-                    <IconNext className={cs.btnNextIcon} />
-                  </button>
+                  {btnNext}
                 </>
               );
             }}
@@ -85,16 +109,11 @@ export const Stepper: FC<StepperProps> = props => {
             footer={() => {
               return (
                 <>
-                  <button className={cs.btnPrev} onClick={() => setStepIdx(1)}>
-                    <IconPrev className={cs.btnPrevIcon} />
-                  </button>
+                  {btnPrev}
                   <span>
                     Fixation : <strong>{order.fixation && order.fixation.name}</strong>
                   </span>
-                  <button className={cs.btnNext} onClick={() => setEnable(false)}>
-                    Commencer la création
-                    <IconNext className={cs.btnNextIcon} />
-                  </button>
+                  {btnNext}
                 </>
               );
             }}
@@ -106,24 +125,40 @@ export const Stepper: FC<StepperProps> = props => {
 
   return (
     <div className={cs.container}>
-      <div className={cs.stepper}>
-        <Step
-          idx={0}
-          stepIdx={stepIdx}
-          setStepIdx={() => setStepIdx(0)}
-          label="Produits"
-          icon={IconProduct}
-          separator
-        />
-        <Step
-          idx={1}
-          stepIdx={stepIdx}
-          setStepIdx={() => setStepIdx(1)}
-          label="Dimensions"
-          icon={IconDimension}
-          separator
-        />
-        <Step idx={2} stepIdx={stepIdx} setStepIdx={() => setStepIdx(2)} label="Fixations" icon={IconFixation} />
+      <div
+        className={cs.stepper}
+        style={{gridTemplateColumns: steps.map(() => "minmax(0, 1fr)").join(" min-content ")}}
+      >
+        {steps.includes(0) && (
+          <Step
+            idx={0}
+            stepIdx={stepIdx}
+            setStepIdx={() => setStepIdx(0)}
+            label="Produits"
+            icon={IconProduct}
+            separator={!isLastStepOf(0)}
+          />
+        )}
+        {steps.includes(1) && (
+          <Step
+            idx={1}
+            stepIdx={stepIdx}
+            setStepIdx={() => setStepIdx(1)}
+            label="Dimensions"
+            icon={IconDimension}
+            separator={!isLastStepOf(1)}
+          />
+        )}
+        {steps.includes(2) && (
+          <Step
+            idx={2}
+            stepIdx={stepIdx}
+            setStepIdx={() => setStepIdx(2)}
+            label="Fixations"
+            icon={IconFixation}
+            separator={!isLastStepOf(2)}
+          />
+        )}
       </div>
       {currentStep}
     </div>
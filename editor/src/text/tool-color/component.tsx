@@ -4,6 +4,7 @@ import tinycolor from "tinycolor2";
 import cn from "classnames";
 
 import {useCanvas, useActiveTextObj} from "../../canvas";
+import {settings} from "../../settings";
 import Tool, {useTool} from "../../toolbar/tool";
 import {ReactComponent as IconColor} from "./icon.svg";
 import {ReactComponent as IconColorSelected} from "./icon-selected.svg";
@@ -18,7 +19,7 @@ type Color = {
   style?: React.CSSProperties;
 };
 
-const colors: Color[] = [
+const defaultColors = [
   "#ffffff",
   "#a0a0a0",
   "#68676b",
@@ -61,13 +62,17 @@ const colors: Color[] = [
   "#8c7a72",
   "#4b4338",
   "#f6be00",
-].map(value => ({
-  value,
-  style: {
-    background: value,
-    borderColor: tinycolor(value).darken(15).toHexString(),
-  },
-}));
+];
+
+function toColor(value: string): Color {
+  return {
+    value,
+    style: {
+      background: value,
+      borderColor: tinycolor(value).darken(15).toHexString(),
+    },
+  };
+}
 
 export type ColorPaletteProps = {
   onClick: (color: string) => void;
@@ -79,6 +84,7 @@ export type ColorPaletteProps = {
 
 export const ColorPalette: FC<ColorPaletteProps> = props => {
   const customColors = props.colors || [];
+  const colors = (settings.palette.length > 0 ? settings.palette : defaultColors).map(toColor);
   return (
     <div className={cn(cs.grid, props.className)} style={props.style}>
       {colors.concat(customColors).map(({value: color, className, style}) => (
@@ -0,0 +1,45 @@
+CREATE TABLE orders (
+  "id" SERIAL PRIMARY KEY,
+  "status" TEXT NOT NULL DEFAULT 'pending',
+  "total_tax_excl" REAL NOT NULL,
+  "total_tax_incl" REAL NOT NULL,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE order_items (
+  "id" SERIAL PRIMARY KEY,
+  "order_id" INTEGER NOT NULL,
+  "material_id" INTEGER DEFAULT NULL,
+  "fixation_id" INTEGER DEFAULT NULL,
+  "shape_id" INTEGER DEFAULT NULL,
+  "quantity" SMALLINT NOT NULL,
+  "width" REAL NOT NULL,
+  "height" REAL NOT NULL,
+  "weight" REAL NOT NULL,
+  "discount" SMALLINT NOT NULL,
+  "unit_price_tax_excl" REAL NOT NULL,
+  "total_tax_excl" REAL NOT NULL,
+  "total_tax_incl" REAL NOT NULL,
+  "material" JSONB NOT NULL,
+  "fixation" JSONB DEFAULT NULL,
+  "condition" JSONB DEFAULT NULL,
+  "svg" TEXT NOT NULL,
+  "config" TEXT NOT NULL,
+  "preview" TEXT NOT NULL,
+  FOREIGN KEY ("order_id")
+    REFERENCES orders ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+  FOREIGN KEY ("material_id")
+    REFERENCES materials ("id")
+    ON UPDATE CASCADE
+    ON DELETE SET NULL,
+  FOREIGN KEY ("fixation_id")
+    REFERENCES fixations ("id")
+    ON UPDATE CASCADE
+    ON DELETE SET NULL,
+  FOREIGN KEY ("shape_id")
+    REFERENCES shapes ("id")
+    ON UPDATE CASCADE
+    ON DELETE SET NULL
+);
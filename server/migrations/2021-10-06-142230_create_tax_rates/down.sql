@@ -0,0 +1,4 @@
+ALTER TABLE order_items
+DROP COLUMN "tax_rate";
+
+DROP TABLE tax_rates;
@@ -0,0 +1,20 @@
+CREATE TABLE tax_rates (
+  "id" SERIAL PRIMARY KEY,
+  "app_id" INTEGER DEFAULT NULL,
+  "material_id" INTEGER DEFAULT NULL,
+  "country" TEXT NOT NULL,
+  "rate" REAL NOT NULL,
+  FOREIGN KEY ("app_id")
+    REFERENCES apps ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+  FOREIGN KEY ("material_id")
+    REFERENCES materials ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+INSERT INTO tax_rates (country, rate) VALUES ('FR', 20);
+
+ALTER TABLE order_items
+ADD COLUMN "tax_rate" REAL NOT NULL DEFAULT 20;
@@ -0,0 +1,15 @@
+ALTER TABLE materials
+ALTER COLUMN "fixed_price" TYPE REAL USING "fixed_price" / 100.0,
+ALTER COLUMN "surface_price" TYPE REAL USING "surface_price" / 100.0;
+
+ALTER TABLE fixations
+ALTER COLUMN "price" TYPE REAL USING "price" / 100.0;
+
+ALTER TABLE orders
+ALTER COLUMN "total_tax_excl" TYPE REAL USING "total_tax_excl" / 100.0,
+ALTER COLUMN "total_tax_incl" TYPE REAL USING "total_tax_incl" / 100.0;
+
+ALTER TABLE order_items
+ALTER COLUMN "unit_price_tax_excl" TYPE REAL USING "unit_price_tax_excl" / 100.0,
+ALTER COLUMN "total_tax_excl" TYPE REAL USING "total_tax_excl" / 100.0,
+ALTER COLUMN "total_tax_incl" TYPE REAL USING "total_tax_incl" / 100.0;
@@ -0,0 +1,15 @@
+ALTER TABLE materials
+ALTER COLUMN "fixed_price" TYPE BIGINT USING ROUND("fixed_price" * 100),
+ALTER COLUMN "surface_price" TYPE BIGINT USING ROUND("surface_price" * 100);
+
+ALTER TABLE fixations
+ALTER COLUMN "price" TYPE BIGINT USING ROUND("price" * 100);
+
+ALTER TABLE orders
+ALTER COLUMN "total_tax_excl" TYPE BIGINT USING ROUND("total_tax_excl" * 100),
+ALTER COLUMN "total_tax_incl" TYPE BIGINT USING ROUND("total_tax_incl" * 100);
+
+ALTER TABLE order_items
+ALTER COLUMN "unit_price_tax_excl" TYPE BIGINT USING ROUND("unit_price_tax_excl" * 100),
+ALTER COLUMN "total_tax_excl" TYPE BIGINT USING ROUND("total_tax_excl" * 100),
+ALTER COLUMN "total_tax_incl" TYPE BIGINT USING ROUND("total_tax_incl" * 100);
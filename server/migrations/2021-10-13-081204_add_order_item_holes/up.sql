@@ -0,0 +1,2 @@
+ALTER TABLE order_items
+ADD COLUMN "holes" JSONB NOT NULL DEFAULT '[]';
@@ -0,0 +1,3 @@
+DROP TABLE app_carriers;
+DROP TABLE shipping_brackets;
+DROP TABLE carriers;
@@ -0,0 +1,31 @@
+CREATE TABLE carriers (
+  "id" SERIAL PRIMARY KEY,
+  "name" TEXT NOT NULL,
+  "max_length" REAL NOT NULL DEFAULT 0,
+  "max_width" REAL NOT NULL DEFAULT 0
+);
+
+CREATE TABLE shipping_brackets (
+  "id" SERIAL PRIMARY KEY,
+  "carrier_id" INTEGER NOT NULL,
+  "max_weight" REAL NOT NULL,
+  "price" BIGINT NOT NULL,
+  FOREIGN KEY ("carrier_id")
+    REFERENCES carriers ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+CREATE TABLE app_carriers (
+  "app_id" INTEGER NOT NULL,
+  "carrier_id" INTEGER NOT NULL,
+  PRIMARY KEY ("app_id", "carrier_id"),
+  FOREIGN KEY ("app_id")
+    REFERENCES apps ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+  FOREIGN KEY ("carrier_id")
+    REFERENCES carriers ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+CREATE TABLE holidays (
+  "id" SERIAL PRIMARY KEY,
+  "date" DATE NOT NULL UNIQUE,
+  "name" TEXT NOT NULL DEFAULT ''
+);
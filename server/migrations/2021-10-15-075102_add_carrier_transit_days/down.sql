@@ -0,0 +1,2 @@
+ALTER TABLE carriers
+DROP COLUMN "transit_days";
@@ -0,0 +1,2 @@
+ALTER TABLE carriers
+ADD COLUMN "transit_days" SMALLINT NOT NULL DEFAULT 2;
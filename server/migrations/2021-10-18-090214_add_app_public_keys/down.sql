@@ -0,0 +1,6 @@
+ALTER TABLE orders
+DROP COLUMN "app_id";
+
+ALTER TABLE apps
+DROP COLUMN "host",
+DROP COLUMN "public_key";
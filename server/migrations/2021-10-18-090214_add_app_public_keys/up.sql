@@ -0,0 +1,10 @@
+ALTER TABLE apps
+ADD COLUMN "public_key" TEXT NOT NULL UNIQUE DEFAULT md5(random()::text),
+ADD COLUMN "host" TEXT DEFAULT NULL UNIQUE;
+
+ALTER TABLE orders
+ADD COLUMN "app_id" INTEGER DEFAULT NULL,
+ADD FOREIGN KEY ("app_id")
+  REFERENCES apps ("id")
+  ON UPDATE CASCADE
+  ON DELETE SET NULL;
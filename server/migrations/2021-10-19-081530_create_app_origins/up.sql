@@ -0,0 +1,13 @@
+-- No origin is seeded: the domains formerly allowed for every app belong to
+-- specific apps, and must be added to them from the admin. Until then, they
+-- can be kept allowed for all apps with the `CORS_ORIGINS` env var (see the
+-- README).
+CREATE TABLE app_origins (
+  "app_id" INTEGER NOT NULL,
+  "origin" TEXT NOT NULL,
+  PRIMARY KEY ("app_id", "origin"),
+  FOREIGN KEY ("app_id")
+    REFERENCES apps ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
@@ -0,0 +1,20 @@
+CREATE TABLE app_settings (
+  "app_id" INTEGER PRIMARY KEY,
+  "logo_url" TEXT DEFAULT NULL,
+  "palette" TEXT[] NOT NULL DEFAULT '{}',
+  "template_id" INTEGER DEFAULT NULL,
+  "product_id" INTEGER DEFAULT NULL,
+  "steps" TEXT[] NOT NULL DEFAULT '{product,dimension,fixation}',
+  FOREIGN KEY ("app_id")
+    REFERENCES apps ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+  FOREIGN KEY ("template_id")
+    REFERENCES templates ("id")
+    ON UPDATE CASCADE
+    ON DELETE SET NULL,
+  FOREIGN KEY ("product_id")
+    REFERENCES materials ("id")
+    ON UPDATE CASCADE
+    ON DELETE SET NULL
+);
@@ -0,0 +1,5 @@
+DROP TABLE app_fixation_markups;
+DROP TABLE app_material_markups;
+
+ALTER TABLE apps
+DROP COLUMN "markup";
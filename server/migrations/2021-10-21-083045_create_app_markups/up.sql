@@ -0,0 +1,32 @@
+ALTER TABLE apps
+ADD COLUMN "markup" REAL NOT NULL DEFAULT 0;
+
+CREATE TABLE app_material_markups (
+  "app_id" INTEGER NOT NULL,
+  "material_id" INTEGER NOT NULL,
+  "markup" REAL NOT NULL,
+  PRIMARY KEY ("app_id", "material_id"),
+  FOREIGN KEY ("app_id")
+    REFERENCES apps ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+  FOREIGN KEY ("material_id")
+    REFERENCES materials ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+CREATE TABLE app_fixation_markups (
+  "app_id" INTEGER NOT NULL,
+  "fixation_id" INTEGER NOT NULL,
+  "markup" REAL NOT NULL,
+  PRIMARY KEY ("app_id", "fixation_id"),
+  FOREIGN KEY ("app_id")
+    REFERENCES apps ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+  FOREIGN KEY ("fixation_id")
+    REFERENCES fixations ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
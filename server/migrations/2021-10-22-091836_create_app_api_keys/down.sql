@@ -0,0 +1 @@
+DROP TABLE app_api_keys;
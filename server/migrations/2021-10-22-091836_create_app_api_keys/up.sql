@@ -0,0 +1,14 @@
+CREATE TABLE app_api_keys (
+  "id" SERIAL PRIMARY KEY,
+  "app_id" INTEGER NOT NULL,
+  "name" TEXT NOT NULL,
+  "prefix" TEXT NOT NULL UNIQUE,
+  "secret_hash" TEXT NOT NULL,
+  "scope" TEXT NOT NULL,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW(),
+  "revoked_at" TIMESTAMP DEFAULT NULL,
+  FOREIGN KEY ("app_id")
+    REFERENCES apps ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
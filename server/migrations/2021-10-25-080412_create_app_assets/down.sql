@@ -0,0 +1,3 @@
+DROP TABLE app_shapes;
+DROP TABLE app_pictos;
+DROP TABLE app_templates;
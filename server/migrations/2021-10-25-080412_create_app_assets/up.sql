@@ -0,0 +1,41 @@
+CREATE TABLE app_templates (
+  "app_id" INTEGER NOT NULL,
+  "template_id" INTEGER NOT NULL,
+  PRIMARY KEY ("app_id", "template_id"),
+  FOREIGN KEY ("app_id")
+    REFERENCES apps ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+  FOREIGN KEY ("template_id")
+    REFERENCES templates ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+CREATE TABLE app_pictos (
+  "app_id" INTEGER NOT NULL,
+  "picto_id" INTEGER NOT NULL,
+  PRIMARY KEY ("app_id", "picto_id"),
+  FOREIGN KEY ("app_id")
+    REFERENCES apps ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+  FOREIGN KEY ("picto_id")
+    REFERENCES pictos ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+CREATE TABLE app_shapes (
+  "app_id" INTEGER NOT NULL,
+  "shape_id" INTEGER NOT NULL,
+  PRIMARY KEY ("app_id", "shape_id"),
+  FOREIGN KEY ("app_id")
+    REFERENCES apps ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+  FOREIGN KEY ("shape_id")
+    REFERENCES shapes ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
@@ -0,0 +1,35 @@
+CREATE TABLE webhooks (
+  "id" SERIAL PRIMARY KEY,
+  "app_id" INTEGER NOT NULL,
+  "url" TEXT NOT NULL,
+  "secret" TEXT NOT NULL,
+  "events" TEXT[] NOT NULL DEFAULT '{}',
+  "active" BOOLEAN NOT NULL DEFAULT TRUE,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW(),
+  FOREIGN KEY ("app_id")
+    REFERENCES apps ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+CREATE TABLE webhook_deliveries (
+  "id" SERIAL PRIMARY KEY,
+  "webhook_id" INTEGER NOT NULL,
+  "event" TEXT NOT NULL,
+  "payload" JSONB NOT NULL,
+  "status" TEXT NOT NULL DEFAULT 'pending',
+  "attempts" INTEGER NOT NULL DEFAULT 0,
+  "next_attempt_at" TIMESTAMP DEFAULT NOW(),
+  "last_status_code" INTEGER DEFAULT NULL,
+  "last_error" TEXT DEFAULT NULL,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW(),
+  "delivered_at" TIMESTAMP DEFAULT NULL,
+  FOREIGN KEY ("webhook_id")
+    REFERENCES webhooks ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+CREATE INDEX webhook_deliveries_pending_idx
+ON webhook_deliveries ("next_attempt_at")
+WHERE "status" = 'pending';
@@ -0,0 +1,31 @@
+CREATE TABLE quote_events (
+  "id" SERIAL PRIMARY KEY,
+  "app_id" INTEGER NOT NULL,
+  "material_id" INTEGER DEFAULT NULL,
+  "fixation_id" INTEGER DEFAULT NULL,
+  "shape_id" INTEGER DEFAULT NULL,
+  "quantity" SMALLINT NOT NULL,
+  "width" REAL NOT NULL,
+  "height" REAL NOT NULL,
+  "sample_weight" REAL NOT NULL DEFAULT 1,
+  "source" TEXT NOT NULL,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW(),
+  FOREIGN KEY ("app_id")
+    REFERENCES apps ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE,
+  FOREIGN KEY ("material_id")
+    REFERENCES materials ("id")
+    ON UPDATE CASCADE
+    ON DELETE SET NULL,
+  FOREIGN KEY ("fixation_id")
+    REFERENCES fixations ("id")
+    ON UPDATE CASCADE
+    ON DELETE SET NULL,
+  FOREIGN KEY ("shape_id")
+    REFERENCES shapes ("id")
+    ON UPDATE CASCADE
+    ON DELETE SET NULL
+);
+
+CREATE INDEX quote_events_created_at_idx ON quote_events ("created_at");
@@ -0,0 +1,16 @@
+CREATE TABLE refresh_tokens (
+  "id" SERIAL PRIMARY KEY,
+  "user_id" INTEGER NOT NULL,
+  "family" TEXT NOT NULL,
+  "token_hash" TEXT NOT NULL UNIQUE,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW(),
+  "expires_at" TIMESTAMP NOT NULL,
+  "used_at" TIMESTAMP DEFAULT NULL,
+  "revoked_at" TIMESTAMP DEFAULT NULL,
+  FOREIGN KEY ("user_id")
+    REFERENCES users ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
+
+CREATE INDEX refresh_tokens_family_idx ON refresh_tokens ("family");
@@ -0,0 +1,8 @@
+CREATE TABLE sign_in_throttles (
+  "scope" TEXT NOT NULL,
+  "subject" TEXT NOT NULL,
+  "failures" INTEGER NOT NULL DEFAULT 0,
+  "last_failed_at" TIMESTAMP NOT NULL DEFAULT NOW(),
+  "locked_until" TIMESTAMP DEFAULT NULL,
+  PRIMARY KEY ("scope", "subject")
+);
@@ -0,0 +1,14 @@
+ALTER TABLE users ADD COLUMN "email" TEXT DEFAULT NULL UNIQUE;
+
+CREATE TABLE password_reset_tokens (
+  "id" SERIAL PRIMARY KEY,
+  "user_id" INTEGER NOT NULL,
+  "token_hash" TEXT NOT NULL UNIQUE,
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW(),
+  "expires_at" TIMESTAMP NOT NULL,
+  "used_at" TIMESTAMP DEFAULT NULL,
+  FOREIGN KEY ("user_id")
+    REFERENCES users ("id")
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+);
@@ -0,0 +1,15 @@
+CREATE TABLE audit_logs (
+  "id" SERIAL PRIMARY KEY,
+  "user_id" INTEGER DEFAULT NULL,
+  "subject" TEXT NOT NULL,
+  "entity" TEXT NOT NULL,
+  "entity_id" INTEGER DEFAULT NULL,
+  "action" TEXT NOT NULL,
+  "method" TEXT NOT NULL,
+  "path" TEXT NOT NULL,
+  "changes" JSONB NOT NULL DEFAULT '{}',
+  "created_at" TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX audit_logs_entity_idx ON audit_logs ("entity", "entity_id");
+CREATE INDEX audit_logs_user_id_idx ON audit_logs ("user_id");
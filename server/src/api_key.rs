@@ -0,0 +1,317 @@
+use actix_web::{
+    self, delete,
+    dev::HttpResponseBuilder,
+    get,
+    http::{header, StatusCode},
+    post, web, HttpResponse,
+};
+use chrono::{Local, NaiveDateTime};
+use diesel::prelude::*;
+use error_chain::error_chain;
+use log::error;
+use ring::constant_time;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::app::App;
+use crate::database;
+use crate::schema::app_api_keys;
+use crate::shared::auth::{Admin, Claims, Role, Scope};
+use crate::shared::token::hash;
+
+/// Distinguishes API keys from JWTs in the `Authorization` header.
+pub const TOKEN_PREFIX: &str = "ck_";
+
+// Error management
+
+error_chain! {
+    errors {
+        GetDbConnErr {
+            description("Impossible de se connecter à la base de données")
+            display("Could not get db conn from pool")
+        }
+        SelectApiKeysErr(app_id: i32) {
+            description("Impossible de récupérer les clés d'API de l'application")
+            display("Could not select api keys of app `{}`", app_id)
+        }
+        InsertApiKeyErr(app_id: i32) {
+            description("Impossible de créer la clé d'API")
+            display("Could not insert api key for app `{}`", app_id)
+        }
+        RevokeApiKeyErr(id: i32) {
+            description("Impossible de révoquer la clé d'API")
+            display("Could not revoke api key `{}`", id)
+        }
+        FindApiKeyErr(prefix: String) {
+            description("Impossible de récupérer la clé d'API")
+            display("Could not select api key `{}`", prefix)
+        }
+        MalformedApiKeyErr {
+            description("Clé d'API invalide")
+            display("Could not parse api key")
+        }
+        UnknownApiKeyErr(prefix: String) {
+            description("Clé d'API invalide")
+            display("Could not find api key `{}`", prefix)
+        }
+        RevokedApiKeyErr(prefix: String) {
+            description("Clé d'API révoquée")
+            display("Api key `{}` has been revoked", prefix)
+        }
+        InvalidScopeErr(scope: String) {
+            description("Portée de la clé d'API inconnue")
+            display("Unknown api key scope `{}`", scope)
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        HttpResponseBuilder::new(self.status_code())
+            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.description().to_owned())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        status_code(self.kind())
+    }
+}
+
+pub fn status_code(kind: &ErrorKind) -> StatusCode {
+    match *kind {
+        ErrorKind::MalformedApiKeyErr
+        | ErrorKind::UnknownApiKeyErr(_)
+        | ErrorKind::RevokedApiKeyErr(_) => StatusCode::UNAUTHORIZED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+// Models
+
+/// A key letting a backend call the API on behalf of an app, limited to one
+/// scope. Only the hash of the secret is stored, the full token is given
+/// once at creation time.
+#[derive(Debug, Clone, Identifiable, Queryable, Associations, Serialize)]
+#[belongs_to(App)]
+#[table_name = "app_api_keys"]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKey {
+    pub id: i32,
+    pub app_id: i32,
+    pub name: String,
+    pub prefix: String,
+    #[serde(skip)]
+    pub secret_hash: String,
+    pub scope: String,
+    pub created_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+impl ApiKey {
+    pub fn claims(&self) -> Result<Claims> {
+        let scope = self
+            .scope
+            .parse::<Scope>()
+            .map_err(|_| ErrorKind::InvalidScopeErr(self.scope.to_owned()))?;
+
+        Ok(Claims {
+            sub: format!("{}{}", TOKEN_PREFIX, self.prefix),
+            scope: Some(scope),
+            ..Claims::new(0, Role::App, vec![self.app_id])
+        })
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "app_api_keys"]
+struct InsertableApiKey<'a> {
+    pub app_id: &'a i32,
+    pub name: &'a str,
+    pub prefix: &'a str,
+    pub secret_hash: &'a str,
+    pub scope: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiKeyJson {
+    pub name: String,
+    pub scope: Scope,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatedApiKey {
+    #[serde(flatten)]
+    key: ApiKey,
+    token: String,
+}
+
+// Helpers
+
+pub fn is_api_key(token: &str) -> bool {
+    token.starts_with(TOKEN_PREFIX)
+}
+
+/// Generates a new `(prefix, secret)` pair. The token given to the app is
+/// `ck_<prefix>.<secret>`.
+fn generate() -> (String, String) {
+    let prefix = Uuid::new_v4().to_simple().to_string()[..12].to_owned();
+    let secret = Uuid::new_v4().to_simple().to_string();
+    (prefix, secret)
+}
+
+fn to_token(prefix: &str, secret: &str) -> String {
+    format!("{}{}.{}", TOKEN_PREFIX, prefix, secret)
+}
+
+/// Splits a token into its `(prefix, secret)` parts.
+fn parse(token: &str) -> Option<(&str, &str)> {
+    token
+        .strip_prefix(TOKEN_PREFIX)?
+        .split_once('.')
+        .filter(|(prefix, secret)| !prefix.is_empty() && !secret.is_empty())
+}
+
+fn verify(secret: &str, secret_hash: &str) -> bool {
+    constant_time::verify_slices_are_equal(hash(secret).as_bytes(), secret_hash.as_bytes()).is_ok()
+}
+
+/// Finds the active key matching the token.
+pub fn authenticate(conn: &database::PooledConnection, token: &str) -> Result<ApiKey> {
+    let (prefix, secret) = parse(token).ok_or(ErrorKind::MalformedApiKeyErr)?;
+
+    let key = {
+        use crate::schema::app_api_keys::dsl;
+        dsl::app_api_keys
+            .filter(dsl::prefix.eq(prefix))
+            .first::<ApiKey>(conn)
+            .optional()
+    }
+    .chain_err(|| ErrorKind::FindApiKeyErr(prefix.to_owned()))?
+    .filter(|key| verify(secret, &key.secret_hash))
+    .ok_or_else(|| ErrorKind::UnknownApiKeyErr(prefix.to_owned()))?;
+
+    if key.revoked_at.is_some() {
+        return Err(ErrorKind::RevokedApiKeyErr(key.prefix).into());
+    }
+
+    Ok(key)
+}
+
+// Services
+
+#[get("/app/{id}/api-key")]
+async fn get(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    web::Path(app_id): web::Path<i32>,
+) -> Result<HttpResponse> {
+    use crate::schema::app_api_keys::dsl;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    let keys = dsl::app_api_keys
+        .filter(dsl::app_id.eq(app_id))
+        .order(dsl::created_at.desc())
+        .load::<ApiKey>(&conn)
+        .chain_err(|| ErrorKind::SelectApiKeysErr(app_id))?;
+
+    Ok(HttpResponse::Ok().json(keys))
+}
+
+#[post("/app/{id}/api-key")]
+async fn create(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    web::Path(app_id): web::Path<i32>,
+    key: web::Json<ApiKeyJson>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let (prefix, secret) = generate();
+
+    let new_key = InsertableApiKey {
+        app_id: &app_id,
+        name: key.name.trim(),
+        prefix: &prefix,
+        secret_hash: &hash(&secret),
+        scope: key.scope.as_str(),
+    };
+
+    let key = diesel::insert_into(app_api_keys::table)
+        .values(&new_key)
+        .get_result::<ApiKey>(&conn)
+        .chain_err(|| ErrorKind::InsertApiKeyErr(app_id))?;
+
+    Ok(HttpResponse::Created().json(CreatedApiKey {
+        key,
+        token: to_token(&prefix, &secret),
+    }))
+}
+
+#[delete("/app/{app_id}/api-key/{id}")]
+async fn revoke(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    web::Path((app_id, id)): web::Path<(i32, i32)>,
+) -> Result<HttpResponse> {
+    use crate::schema::app_api_keys::dsl;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    diesel::update(
+        dsl::app_api_keys
+            .filter(dsl::id.eq(id))
+            .filter(dsl::app_id.eq(app_id))
+            .filter(dsl::revoked_at.is_null()),
+    )
+    .set(dsl::revoked_at.eq(Local::now().naive_local()))
+    .execute(&conn)
+    .chain_err(|| ErrorKind::RevokeApiKeyErr(id))?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get).service(create).service(revoke);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_generated_tokens() {
+        let (prefix, secret) = generate();
+        let token = to_token(&prefix, &secret);
+        assert!(is_api_key(&token));
+        assert_eq!(parse(&token), Some((prefix.as_str(), secret.as_str())));
+    }
+
+    #[test]
+    fn refuses_malformed_tokens() {
+        assert_eq!(parse("eyJhbGciOiJIUzI1NiJ9.e30.sig"), None);
+        assert_eq!(parse("ck_abcdef"), None);
+        assert_eq!(parse("ck_.secret"), None);
+        assert_eq!(parse("ck_abcdef."), None);
+    }
+
+    #[test]
+    fn verifies_secrets_against_their_hash() {
+        let secret_hash = hash("secret");
+        assert_eq!(secret_hash.len(), 64);
+        assert!(verify("secret", &secret_hash));
+        assert!(!verify("Secret", &secret_hash));
+    }
+
+    #[test]
+    fn refuses_unknown_keys_but_fails_on_db_errors() {
+        let prefix = || "abcdef".to_owned();
+        assert_eq!(
+            status_code(&ErrorKind::UnknownApiKeyErr(prefix())),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status_code(&ErrorKind::FindApiKeyErr(prefix())),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
@@ -1,24 +1,45 @@
 use actix_web::{
     self, delete,
-    dev::HttpResponseBuilder,
+    dev::{HttpResponseBuilder, Payload},
     get,
     http::{header, StatusCode},
-    put, web, HttpResponse,
+    post, put, web, FromRequest, HttpRequest, HttpResponse,
 };
 use diesel::prelude::*;
 use error_chain::error_chain;
 use log::error;
 use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    future::{ready, Ready},
+};
 
+use crate::carrier::Carrier;
 use crate::database;
 use crate::font::Font;
 use crate::material::Material;
-use crate::schema::{app_fonts, app_materials, app_users, apps};
-use crate::user::User;
+use crate::picto::Picto;
+use crate::schema::{
+    app_carriers, app_fonts, app_materials, app_origins, app_pictos, app_shapes, app_templates,
+    app_users, apps,
+};
+use crate::shape::Shape;
+use crate::shared::auth::{self, Admin, Claims};
+use crate::shared::cors::{self, AllowedOrigins};
+use crate::template::Template;
+use crate::user::{self, User};
 
 // Error management
 
 error_chain! {
+    links {
+        Cors(cors::Error, cors::ErrorKind);
+        Auth(auth::Error, auth::ErrorKind);
+        User(user::Error, user::ErrorKind);
+    }
+    foreign_links {
+        Transaction(diesel::result::Error);
+    }
     errors {
         GetDbConnErr {
             description("Impossible de se connecter à la base de données")
@@ -28,6 +49,22 @@ error_chain! {
             description("Impossible de récupérer la liste des applications")
             display("Could not select apps")
         }
+        MissingAppErr {
+            description("Impossible d'identifier l'application")
+            display("Could not resolve app from request")
+        }
+        UnknownAppErr(key: String) {
+            description("Application inconnue")
+            display("Could not find app with public key `{}`", key)
+        }
+        CloneAppErr(id: i32) {
+            description("Impossible de dupliquer l'application")
+            display("Could not clone app `{}`", id)
+        }
+        FindAppErr(id: i32) {
+            description("Impossible de récupérer l'application")
+            display("Could not find app `{}`", id)
+        }
         InsertAppErr(id: i32) {
             description("Impossible de créer l'application")
             display("Could not insert app `{}`", id)
@@ -76,6 +113,66 @@ error_chain! {
             description("Impossible de détacher les polices de l'application")
             display("Could not delete app_fonts `{}`", id)
         }
+        SelectAppCarriersErr {
+            description("Impossible de récupérer la liste des transporteurs rattachés aux applications")
+            display("Could not select app_carriers")
+        }
+        InsertAppCarriersErr(id: i32) {
+            description("Impossible de rattacher les transporteurs à l'application")
+            display("Could not insert app_carriers `{}`", id)
+        }
+        DeleteAppCarriersErr(id: i32) {
+            description("Impossible de détacher les transporteurs de l'application")
+            display("Could not delete app_carriers `{}`", id)
+        }
+        SelectAppTemplatesErr {
+            description("Impossible de récupérer la liste des templates rattachés aux applications")
+            display("Could not select app_templates")
+        }
+        InsertAppTemplatesErr(id: i32) {
+            description("Impossible de rattacher les templates à l'application")
+            display("Could not insert app_templates `{}`", id)
+        }
+        DeleteAppTemplatesErr(id: i32) {
+            description("Impossible de détacher les templates de l'application")
+            display("Could not delete app_templates `{}`", id)
+        }
+        SelectAppPictosErr {
+            description("Impossible de récupérer la liste des pictogrammes rattachés aux applications")
+            display("Could not select app_pictos")
+        }
+        InsertAppPictosErr(id: i32) {
+            description("Impossible de rattacher les pictogrammes à l'application")
+            display("Could not insert app_pictos `{}`", id)
+        }
+        DeleteAppPictosErr(id: i32) {
+            description("Impossible de détacher les pictogrammes de l'application")
+            display("Could not delete app_pictos `{}`", id)
+        }
+        SelectAppShapesErr {
+            description("Impossible de récupérer la liste des formes rattachées aux applications")
+            display("Could not select app_shapes")
+        }
+        InsertAppShapesErr(id: i32) {
+            description("Impossible de rattacher les formes à l'application")
+            display("Could not insert app_shapes `{}`", id)
+        }
+        DeleteAppShapesErr(id: i32) {
+            description("Impossible de détacher les formes de l'application")
+            display("Could not delete app_shapes `{}`", id)
+        }
+        SelectAppOriginsErr {
+            description("Impossible de récupérer la liste des origines autorisées des applications")
+            display("Could not select app_origins")
+        }
+        InsertAppOriginsErr(id: i32) {
+            description("Impossible d'autoriser les origines de l'application")
+            display("Could not insert app_origins `{}`", id)
+        }
+        DeleteAppOriginsErr(id: i32) {
+            description("Impossible de retirer les origines autorisées de l'application")
+            display("Could not delete app_origins `{}`", id)
+        }
     }
 }
 
@@ -89,6 +186,9 @@ impl actix_web::error::ResponseError for Error {
 
     fn status_code(&self) -> StatusCode {
         match *self.kind() {
+            ErrorKind::MissingAppErr => StatusCode::BAD_REQUEST,
+            ErrorKind::UnknownAppErr(_) => StatusCode::NOT_FOUND,
+            ErrorKind::Auth(ref kind) => auth::status_code(kind),
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -96,23 +196,33 @@ impl actix_web::error::ResponseError for Error {
 
 // Models
 
-#[derive(Identifiable, Queryable, Associations, Serialize, Deserialize)]
+/// A white-label app. The public API identifies the calling app by its
+/// `public_key`, or by its `host` when served from the app domain.
+#[derive(Debug, Clone, Identifiable, Queryable, Associations, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct App {
     pub id: i32,
     pub name: String,
+    pub public_key: String,
+    pub host: Option<String>,
+    /// The default markup applied on the base prices, in percent.
+    pub markup: f32,
 }
 
 #[derive(Insertable)]
 #[table_name = "apps"]
 pub struct InsertableApp<'a> {
     pub name: &'a str,
+    pub host: Option<&'a String>,
 }
 
 #[derive(Identifiable, AsChangeset)]
 #[table_name = "apps"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct UpdatableApp<'a> {
     pub id: &'a i32,
     pub name: &'a str,
+    pub host: Option<&'a String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -121,11 +231,36 @@ pub struct AppJson {
     pub id: i32,
     pub name: String,
     #[serde(default)]
+    pub public_key: String,
+    #[serde(default)]
+    pub host: Option<String>,
+    #[serde(default)]
     pub user_ids: Vec<i32>,
     #[serde(default)]
     pub material_ids: Vec<i32>,
     #[serde(default)]
     pub font_ids: Vec<i32>,
+    #[serde(default)]
+    pub carrier_ids: Vec<i32>,
+    #[serde(default)]
+    pub origins: Vec<String>,
+    #[serde(default)]
+    pub template_ids: Vec<i32>,
+    #[serde(default)]
+    pub picto_ids: Vec<i32>,
+    #[serde(default)]
+    pub shape_ids: Vec<i32>,
+}
+
+/// The options of `POST /app/{id}/clone`. The clone is named after the
+/// original app unless `name` is given.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneAppJson {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub with_users: bool,
 }
 
 #[derive(Identifiable, Queryable, Insertable, Associations)]
@@ -155,17 +290,484 @@ pub struct AppFont {
     pub font_id: i32,
 }
 
+#[derive(Identifiable, Queryable, Insertable, Associations)]
+#[primary_key(app_id, carrier_id)]
+#[belongs_to(App)]
+#[belongs_to(Carrier)]
+pub struct AppCarrier {
+    pub app_id: i32,
+    pub carrier_id: i32,
+}
+
+/// Templates, pictos and shapes bound to apps are private to them, the
+/// other ones are shared by all apps.
+#[derive(Identifiable, Queryable, Insertable, Associations)]
+#[primary_key(app_id, template_id)]
+#[belongs_to(App)]
+#[belongs_to(Template)]
+pub struct AppTemplate {
+    pub app_id: i32,
+    pub template_id: i32,
+}
+
+#[derive(Identifiable, Queryable, Insertable, Associations)]
+#[primary_key(app_id, picto_id)]
+#[belongs_to(App)]
+#[belongs_to(Picto)]
+pub struct AppPicto {
+    pub app_id: i32,
+    pub picto_id: i32,
+}
+
+#[derive(Identifiable, Queryable, Insertable, Associations)]
+#[primary_key(app_id, shape_id)]
+#[belongs_to(App)]
+#[belongs_to(Shape)]
+pub struct AppShape {
+    pub app_id: i32,
+    pub shape_id: i32,
+}
+
+/// A domain allowed to call the private API, see `AllowedOrigins`.
+#[derive(Identifiable, Queryable, Insertable, Associations)]
+#[primary_key(app_id, origin)]
+#[belongs_to(App)]
+pub struct AppOrigin {
+    pub app_id: i32,
+    pub origin: String,
+}
+
+/// The app calling the public API, resolved from (by order of precedence):
+/// the API key, the `{app_key}` path segment, the `X-App-Key` header, or the
+/// `Host` header.
+pub struct PublicApp(pub App);
+
+impl FromRequest for PublicApp {
+    type Error = Error;
+    type Future = Ready<Result<Self>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(resolve(req).map(PublicApp))
+    }
+}
+
+// Helpers
+
+fn find_by_id(conn: &database::PooledConnection, id: i32) -> Result<App> {
+    use crate::schema::apps::dsl::apps;
+    apps.find(id)
+        .first::<App>(conn)
+        .chain_err(|| ErrorKind::FindAppErr(id))
+}
+
+fn find_by_public_key(conn: &database::PooledConnection, key: &str) -> Result<App> {
+    use crate::schema::apps::dsl;
+    dsl::apps
+        .filter(dsl::public_key.eq(key))
+        .first::<App>(conn)
+        .optional()
+        .chain_err(|| ErrorKind::SelectAppsErr)?
+        .ok_or_else(|| ErrorKind::UnknownAppErr(key.to_owned()).into())
+}
+
+fn find_by_host(conn: &database::PooledConnection, host: &str) -> Result<Option<App>> {
+    use crate::schema::apps::dsl;
+    let host = host.split(':').next().unwrap_or_default();
+    dsl::apps
+        .filter(dsl::host.eq(host))
+        .first::<App>(conn)
+        .optional()
+        .chain_err(|| ErrorKind::SelectAppsErr)
+}
+
+pub fn resolve(req: &HttpRequest) -> Result<App> {
+    let pool = req
+        .app_data::<web::Data<database::Pool>>()
+        .ok_or(ErrorKind::GetDbConnErr)?;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    if let Some(app_id) = req
+        .extensions()
+        .get::<Claims>()
+        .and_then(Claims::key_app_id)
+    {
+        return find_by_id(&conn, app_id);
+    }
+
+    if let Some(key) = req.match_info().get("app_key") {
+        return find_by_public_key(&conn, key);
+    }
+
+    if let Some(key) = req.headers().get("X-App-Key") {
+        let key = key.to_str().chain_err(|| ErrorKind::MissingAppErr)?;
+        return find_by_public_key(&conn, key);
+    }
+
+    match req.headers().get(header::HOST).map(|host| host.to_str()) {
+        Some(Ok(host)) => find_by_host(&conn, host)?.ok_or_else(|| ErrorKind::MissingAppErr.into()),
+        _ => Err(ErrorKind::MissingAppErr.into()),
+    }
+}
+
+/// Finds the assets private to apps other than `app_ids`, from
+/// `(asset_id, app_id)` links. Assets without link are shared by all apps.
+fn hidden_ids(links: &[(i32, i32)], app_ids: &[i32]) -> HashSet<i32> {
+    let visible: HashSet<i32> = links
+        .iter()
+        .filter(|(_, id)| app_ids.contains(id))
+        .map(|(asset_id, _)| *asset_id)
+        .collect();
+
+    links
+        .iter()
+        .map(|(asset_id, _)| *asset_id)
+        .filter(|asset_id| !visible.contains(asset_id))
+        .collect()
+}
+
+/// Finds the assets the user cannot see from `(asset_id, app_id)` links:
+/// none for admins, the ones private to apps they are not a member of
+/// otherwise.
+fn claims_hidden_ids(links: &[(i32, i32)], claims: &Claims) -> HashSet<i32> {
+    if claims.is_admin() {
+        HashSet::new()
+    } else {
+        hidden_ids(links, &claims.apps)
+    }
+}
+
+fn get_template_links(conn: &database::PooledConnection) -> Result<Vec<(i32, i32)>> {
+    app_templates::table
+        .select((app_templates::template_id, app_templates::app_id))
+        .load::<(i32, i32)>(conn)
+        .chain_err(|| ErrorKind::SelectAppTemplatesErr)
+}
+
+fn get_picto_links(conn: &database::PooledConnection) -> Result<Vec<(i32, i32)>> {
+    app_pictos::table
+        .select((app_pictos::picto_id, app_pictos::app_id))
+        .load::<(i32, i32)>(conn)
+        .chain_err(|| ErrorKind::SelectAppPictosErr)
+}
+
+fn get_shape_links(conn: &database::PooledConnection) -> Result<Vec<(i32, i32)>> {
+    app_shapes::table
+        .select((app_shapes::shape_id, app_shapes::app_id))
+        .load::<(i32, i32)>(conn)
+        .chain_err(|| ErrorKind::SelectAppShapesErr)
+}
+
+pub fn get_hidden_template_ids(
+    conn: &database::PooledConnection,
+    app_id: i32,
+) -> Result<HashSet<i32>> {
+    Ok(hidden_ids(&get_template_links(conn)?, &[app_id]))
+}
+
+pub fn get_hidden_picto_ids(
+    conn: &database::PooledConnection,
+    app_id: i32,
+) -> Result<HashSet<i32>> {
+    Ok(hidden_ids(&get_picto_links(conn)?, &[app_id]))
+}
+
+pub fn get_hidden_shape_ids(
+    conn: &database::PooledConnection,
+    app_id: i32,
+) -> Result<HashSet<i32>> {
+    Ok(hidden_ids(&get_shape_links(conn)?, &[app_id]))
+}
+
+pub fn get_claims_hidden_template_ids(
+    conn: &database::PooledConnection,
+    claims: &Claims,
+) -> Result<HashSet<i32>> {
+    Ok(claims_hidden_ids(&get_template_links(conn)?, claims))
+}
+
+pub fn get_claims_hidden_picto_ids(
+    conn: &database::PooledConnection,
+    claims: &Claims,
+) -> Result<HashSet<i32>> {
+    Ok(claims_hidden_ids(&get_picto_links(conn)?, claims))
+}
+
+pub fn get_claims_hidden_shape_ids(
+    conn: &database::PooledConnection,
+    claims: &Claims,
+) -> Result<HashSet<i32>> {
+    Ok(claims_hidden_ids(&get_shape_links(conn)?, claims))
+}
+
+pub fn get_material_ids(conn: &database::PooledConnection, app_id: i32) -> Result<Vec<i32>> {
+    use crate::schema::app_materials::dsl;
+    dsl::app_materials
+        .select(dsl::material_id)
+        .filter(dsl::app_id.eq(app_id))
+        .load::<i32>(conn)
+        .chain_err(|| ErrorKind::SelectAppMaterialsErr)
+}
+
+/// Finds the materials the user can manage: all of them for admins (`None`),
+/// the ones of their apps otherwise.
+pub fn get_claims_material_ids(
+    conn: &database::PooledConnection,
+    claims: &Claims,
+) -> Result<Option<Vec<i32>>> {
+    if claims.is_admin() {
+        return Ok(None);
+    }
+
+    use crate::schema::app_materials::dsl;
+    dsl::app_materials
+        .select(dsl::material_id)
+        .filter(dsl::app_id.eq_any(&claims.apps))
+        .distinct()
+        .load::<i32>(conn)
+        .map(Some)
+        .chain_err(|| ErrorKind::SelectAppMaterialsErr)
+}
+
+pub fn has_material(
+    conn: &database::PooledConnection,
+    app_id: i32,
+    material_id: i32,
+) -> Result<bool> {
+    app_materials::table
+        .find((app_id, material_id))
+        .first::<AppMaterial>(conn)
+        .optional()
+        .map(|app_material| app_material.is_some())
+        .chain_err(|| ErrorKind::SelectAppMaterialsErr)
+}
+
+pub fn get_font_ids(conn: &database::PooledConnection, app_id: i32) -> Result<Vec<i32>> {
+    use crate::schema::app_fonts::dsl;
+    dsl::app_fonts
+        .select(dsl::font_id)
+        .filter(dsl::app_id.eq(app_id))
+        .load::<i32>(conn)
+        .chain_err(|| ErrorKind::SelectAppFontsErr)
+}
+
+/// The fixations available on at least one material of the app.
+pub fn get_fixation_ids(conn: &database::PooledConnection, app_id: i32) -> Result<Vec<i32>> {
+    use crate::schema::material_fixations::dsl;
+    dsl::material_fixations
+        .select(dsl::fixation_id)
+        .filter(dsl::material_id.eq_any(get_material_ids(conn, app_id)?))
+        .distinct()
+        .load::<i32>(conn)
+        .chain_err(|| ErrorKind::SelectAppMaterialsErr)
+}
+
+/// Duplicates an app with its catalog, private assets, settings, pricing and
+/// origins. The users are copied only if asked, the host and the API keys
+/// never are since they identify the app.
+pub fn clone_app(
+    conn: &database::PooledConnection,
+    app_id: i32,
+    opts: &CloneAppJson,
+) -> Result<App> {
+    use crate::app_settings::AppSettings;
+    use crate::markup::{AppFixationMarkup, AppMaterialMarkup};
+    use crate::schema::{app_fixation_markups, app_material_markups, app_settings, tax_rates};
+    use crate::tax_rate::TaxRate;
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        let app = apps::table.find(app_id).first::<App>(conn)?;
+        let name = match opts.name.as_ref().map(|name| name.trim()) {
+            Some(name) if !name.is_empty() => name.to_owned(),
+            _ => format!("{} (copie)", app.name),
+        };
+
+        let new_app = diesel::insert_into(apps::table)
+            .values((apps::name.eq(&name), apps::markup.eq(app.markup)))
+            .get_result::<App>(conn)?;
+
+        if opts.with_users {
+            let users: Vec<AppUser> = AppUser::belonging_to(&app)
+                .load::<AppUser>(conn)?
+                .into_iter()
+                .map(|user| AppUser {
+                    app_id: new_app.id,
+                    ..user
+                })
+                .collect();
+
+            diesel::insert_into(app_users::table)
+                .values(&users)
+                .execute(conn)?;
+        }
+
+        let materials: Vec<AppMaterial> = AppMaterial::belonging_to(&app)
+            .load::<AppMaterial>(conn)?
+            .into_iter()
+            .map(|material| AppMaterial {
+                app_id: new_app.id,
+                ..material
+            })
+            .collect();
+
+        diesel::insert_into(app_materials::table)
+            .values(&materials)
+            .execute(conn)?;
+
+        let fonts: Vec<AppFont> = AppFont::belonging_to(&app)
+            .load::<AppFont>(conn)?
+            .into_iter()
+            .map(|font| AppFont {
+                app_id: new_app.id,
+                ..font
+            })
+            .collect();
+
+        diesel::insert_into(app_fonts::table)
+            .values(&fonts)
+            .execute(conn)?;
+
+        let carriers: Vec<AppCarrier> = AppCarrier::belonging_to(&app)
+            .load::<AppCarrier>(conn)?
+            .into_iter()
+            .map(|carrier| AppCarrier {
+                app_id: new_app.id,
+                ..carrier
+            })
+            .collect();
+
+        diesel::insert_into(app_carriers::table)
+            .values(&carriers)
+            .execute(conn)?;
+
+        let templates: Vec<AppTemplate> = AppTemplate::belonging_to(&app)
+            .load::<AppTemplate>(conn)?
+            .into_iter()
+            .map(|template| AppTemplate {
+                app_id: new_app.id,
+                ..template
+            })
+            .collect();
+
+        diesel::insert_into(app_templates::table)
+            .values(&templates)
+            .execute(conn)?;
+
+        let pictos: Vec<AppPicto> = AppPicto::belonging_to(&app)
+            .load::<AppPicto>(conn)?
+            .into_iter()
+            .map(|picto| AppPicto {
+                app_id: new_app.id,
+                ..picto
+            })
+            .collect();
+
+        diesel::insert_into(app_pictos::table)
+            .values(&pictos)
+            .execute(conn)?;
+
+        let shapes: Vec<AppShape> = AppShape::belonging_to(&app)
+            .load::<AppShape>(conn)?
+            .into_iter()
+            .map(|shape| AppShape {
+                app_id: new_app.id,
+                ..shape
+            })
+            .collect();
+
+        diesel::insert_into(app_shapes::table)
+            .values(&shapes)
+            .execute(conn)?;
+
+        let origins: Vec<AppOrigin> = AppOrigin::belonging_to(&app)
+            .load::<AppOrigin>(conn)?
+            .into_iter()
+            .map(|origin| AppOrigin {
+                app_id: new_app.id,
+                ..origin
+            })
+            .collect();
+
+        diesel::insert_into(app_origins::table)
+            .values(&origins)
+            .execute(conn)?;
+
+        if let Some(settings) = app_settings::table
+            .find(app.id)
+            .first::<AppSettings>(conn)
+            .optional()?
+        {
+            diesel::insert_into(app_settings::table)
+                .values(&AppSettings {
+                    app_id: new_app.id,
+                    ..settings
+                })
+                .execute(conn)?;
+        }
+
+        let material_markups: Vec<AppMaterialMarkup> = AppMaterialMarkup::belonging_to(&app)
+            .load::<AppMaterialMarkup>(conn)?
+            .into_iter()
+            .map(|markup| AppMaterialMarkup {
+                app_id: new_app.id,
+                ..markup
+            })
+            .collect();
+
+        diesel::insert_into(app_material_markups::table)
+            .values(&material_markups)
+            .execute(conn)?;
+
+        let fixation_markups: Vec<AppFixationMarkup> = AppFixationMarkup::belonging_to(&app)
+            .load::<AppFixationMarkup>(conn)?
+            .into_iter()
+            .map(|markup| AppFixationMarkup {
+                app_id: new_app.id,
+                ..markup
+            })
+            .collect();
+
+        diesel::insert_into(app_fixation_markups::table)
+            .values(&fixation_markups)
+            .execute(conn)?;
+
+        let rates: Vec<_> = tax_rates::table
+            .filter(tax_rates::app_id.eq(app.id))
+            .load::<TaxRate>(conn)?
+            .into_iter()
+            .map(|rate| {
+                (
+                    tax_rates::app_id.eq(new_app.id),
+                    tax_rates::material_id.eq(rate.material_id),
+                    tax_rates::country.eq(rate.country),
+                    tax_rates::rate.eq(rate.rate),
+                )
+            })
+            .collect();
+
+        diesel::insert_into(tax_rates::table)
+            .values(&rates)
+            .execute(conn)?;
+
+        Ok(new_app)
+    })
+    .chain_err(|| ErrorKind::CloneAppErr(app_id))
+}
+
 // Services
 
 #[get("/app")]
-async fn get(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
+async fn get(pool: web::Data<database::Pool>, claims: Claims) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
 
-    let apps = {
+    let apps: Vec<App> = {
         use crate::schema::apps::dsl::apps;
         apps.load::<App>(&conn)
     }
-    .chain_err(|| ErrorKind::SelectAppsErr)?;
+    .chain_err(|| ErrorKind::SelectAppsErr)?
+    .into_iter()
+    .filter(|app| claims.can_access_app(app.id))
+    .collect();
 
     let all_app_users = AppUser::belonging_to(&apps)
         .load::<AppUser>(&conn)
@@ -182,17 +784,54 @@ async fn get(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
         .chain_err(|| ErrorKind::SelectAppFontsErr)?
         .grouped_by(&apps);
 
+    let all_app_carriers = AppCarrier::belonging_to(&apps)
+        .load::<AppCarrier>(&conn)
+        .chain_err(|| ErrorKind::SelectAppCarriersErr)?
+        .grouped_by(&apps);
+
+    let all_app_origins = AppOrigin::belonging_to(&apps)
+        .load::<AppOrigin>(&conn)
+        .chain_err(|| ErrorKind::SelectAppOriginsErr)?
+        .grouped_by(&apps);
+
+    let all_app_templates = AppTemplate::belonging_to(&apps)
+        .load::<AppTemplate>(&conn)
+        .chain_err(|| ErrorKind::SelectAppTemplatesErr)?
+        .grouped_by(&apps);
+
+    let all_app_pictos = AppPicto::belonging_to(&apps)
+        .load::<AppPicto>(&conn)
+        .chain_err(|| ErrorKind::SelectAppPictosErr)?
+        .grouped_by(&apps);
+
+    let all_app_shapes = AppShape::belonging_to(&apps)
+        .load::<AppShape>(&conn)
+        .chain_err(|| ErrorKind::SelectAppShapesErr)?
+        .grouped_by(&apps);
+
     let output: Vec<AppJson> = apps
         .into_iter()
         .zip(all_app_users)
         .zip(all_app_materials)
         .zip(all_app_fonts)
-        .map(|(((app, u), m), f)| AppJson {
+        .zip(all_app_carriers)
+        .zip(all_app_origins)
+        .zip(all_app_templates)
+        .zip(all_app_pictos)
+        .zip(all_app_shapes)
+        .map(|((((((((app, u), m), f), c), o), t), p), s)| AppJson {
             id: app.id,
             name: app.name,
+            public_key: app.public_key,
+            host: app.host,
             user_ids: u.iter().map(|u| u.user_id).collect(),
             material_ids: m.iter().map(|m| m.material_id).collect(),
             font_ids: f.iter().map(|f| f.font_id).collect(),
+            carrier_ids: c.iter().map(|c| c.carrier_id).collect(),
+            origins: o.into_iter().map(|o| o.origin).collect(),
+            template_ids: t.iter().map(|t| t.template_id).collect(),
+            picto_ids: p.iter().map(|p| p.picto_id).collect(),
+            shape_ids: s.iter().map(|s| s.shape_id).collect(),
         })
         .collect();
 
@@ -200,92 +839,232 @@ async fn get(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
 }
 
 #[put("/app")]
-async fn set(pool: web::Data<database::Pool>, app: web::Json<AppJson>) -> Result<HttpResponse> {
+async fn set(
+    pool: web::Data<database::Pool>,
+    allowed_origins: web::Data<AllowedOrigins>,
+    _: Admin,
+    app: web::Json<AppJson>,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let app_id = app.id;
     let app = app.into_inner();
 
-    let app_id = if app.id == 0 {
-        let new_app = InsertableApp { name: &app.name };
-
-        diesel::insert_into(apps::table)
-            .values(&new_app)
-            .get_result::<App>(&conn)
-            .chain_err(|| ErrorKind::InsertAppErr(app_id))?
-            .id
-    } else {
-        {
+    // The links are replaced as a whole, and the origins reloaded only once
+    // committed.
+    conn.transaction::<_, Error, _>(|| {
+        let host = app.host.as_ref().filter(|host| !host.trim().is_empty());
+        let prev_user_ids = {
             use crate::schema::app_users::dsl;
-            diesel::delete(app_users::table)
+            dsl::app_users
+                .select(dsl::user_id)
                 .filter(dsl::app_id.eq(app.id))
-                .execute(&conn)
+                .load::<i32>(&conn)
         }
-        .chain_err(|| ErrorKind::DeleteAppUsersErr(app_id))?;
+        .chain_err(|| ErrorKind::SelectAppUsersErr)?;
 
-        {
-            use crate::schema::app_materials::dsl;
-            diesel::delete(app_materials::table)
-                .filter(dsl::app_id.eq(app.id))
-                .execute(&conn)
-        }
-        .chain_err(|| ErrorKind::DeleteAppMaterialsErr(app_id))?;
+        let app_id = if app.id == 0 {
+            let new_app = InsertableApp {
+                name: &app.name,
+                host,
+            };
 
-        {
-            use crate::schema::app_fonts::dsl;
-            diesel::delete(app_fonts::table)
-                .filter(dsl::app_id.eq(app.id))
+            diesel::insert_into(apps::table)
+                .values(&new_app)
+                .get_result::<App>(&conn)
+                .chain_err(|| ErrorKind::InsertAppErr(app_id))?
+                .id
+        } else {
+            {
+                use crate::schema::app_users::dsl;
+                diesel::delete(app_users::table)
+                    .filter(dsl::app_id.eq(app.id))
+                    .execute(&conn)
+            }
+            .chain_err(|| ErrorKind::DeleteAppUsersErr(app_id))?;
+
+            {
+                use crate::schema::app_materials::dsl;
+                diesel::delete(app_materials::table)
+                    .filter(dsl::app_id.eq(app.id))
+                    .execute(&conn)
+            }
+            .chain_err(|| ErrorKind::DeleteAppMaterialsErr(app_id))?;
+
+            {
+                use crate::schema::app_fonts::dsl;
+                diesel::delete(app_fonts::table)
+                    .filter(dsl::app_id.eq(app.id))
+                    .execute(&conn)
+            }
+            .chain_err(|| ErrorKind::DeleteAppFontsErr(app_id))?;
+
+            {
+                use crate::schema::app_carriers::dsl;
+                diesel::delete(app_carriers::table)
+                    .filter(dsl::app_id.eq(app.id))
+                    .execute(&conn)
+            }
+            .chain_err(|| ErrorKind::DeleteAppCarriersErr(app_id))?;
+
+            {
+                use crate::schema::app_origins::dsl;
+                diesel::delete(app_origins::table)
+                    .filter(dsl::app_id.eq(app.id))
+                    .execute(&conn)
+            }
+            .chain_err(|| ErrorKind::DeleteAppOriginsErr(app_id))?;
+
+            {
+                use crate::schema::app_templates::dsl;
+                diesel::delete(app_templates::table)
+                    .filter(dsl::app_id.eq(app.id))
+                    .execute(&conn)
+            }
+            .chain_err(|| ErrorKind::DeleteAppTemplatesErr(app_id))?;
+
+            {
+                use crate::schema::app_pictos::dsl;
+                diesel::delete(app_pictos::table)
+                    .filter(dsl::app_id.eq(app.id))
+                    .execute(&conn)
+            }
+            .chain_err(|| ErrorKind::DeleteAppPictosErr(app_id))?;
+
+            {
+                use crate::schema::app_shapes::dsl;
+                diesel::delete(app_shapes::table)
+                    .filter(dsl::app_id.eq(app.id))
+                    .execute(&conn)
+            }
+            .chain_err(|| ErrorKind::DeleteAppShapesErr(app_id))?;
+
+            let next_app = UpdatableApp {
+                id: &app.id,
+                name: &app.name,
+                host,
+            };
+
+            diesel::update(&next_app)
+                .set(&next_app)
                 .execute(&conn)
-        }
-        .chain_err(|| ErrorKind::DeleteAppFontsErr(app_id))?;
+                .chain_err(|| ErrorKind::UpdateAppErr(app_id))?;
 
-        let next_app = UpdatableApp {
-            id: &app.id,
-            name: &app.name,
+            app.id
         };
 
-        diesel::update(&next_app)
-            .set(&next_app)
+        let new_app_users: &[AppUser] = &app
+            .user_ids
+            .iter()
+            .map(|&user_id| AppUser { app_id, user_id })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(app_users::table)
+            .values(new_app_users)
             .execute(&conn)
-            .chain_err(|| ErrorKind::UpdateAppErr(app_id))?;
+            .chain_err(|| ErrorKind::InsertAppUsersErr(app_id))?;
 
-        app.id
-    };
+        // The tokens carry the apps of the user, those who joined or left the
+        // app are signed out everywhere.
+        let prev_user_ids: HashSet<i32> = prev_user_ids.into_iter().collect();
+        let user_ids: HashSet<i32> = app.user_ids.iter().copied().collect();
+        for &user_id in prev_user_ids.symmetric_difference(&user_ids) {
+            user::revoke_sessions(&conn, user_id)?;
+        }
 
-    let new_app_users: &[AppUser] = &app
-        .user_ids
-        .into_iter()
-        .map(|user_id| AppUser { app_id, user_id })
-        .collect::<Vec<_>>();
+        let new_app_materials: &[AppMaterial] = &app
+            .material_ids
+            .into_iter()
+            .map(|material_id| AppMaterial {
+                app_id,
+                material_id,
+            })
+            .collect::<Vec<_>>();
 
-    diesel::insert_into(app_users::table)
-        .values(new_app_users)
-        .execute(&conn)
-        .chain_err(|| ErrorKind::InsertAppUsersErr(app_id))?;
+        diesel::insert_into(app_materials::table)
+            .values(new_app_materials)
+            .execute(&conn)
+            .chain_err(|| ErrorKind::InsertAppMaterialsErr(app_id))?;
 
-    let new_app_materials: &[AppMaterial] = &app
-        .material_ids
-        .into_iter()
-        .map(|material_id| AppMaterial {
-            app_id,
-            material_id,
-        })
-        .collect::<Vec<_>>();
+        let new_app_fonts: &[AppFont] = &app
+            .font_ids
+            .into_iter()
+            .map(|font_id| AppFont { app_id, font_id })
+            .collect::<Vec<_>>();
 
-    diesel::insert_into(app_materials::table)
-        .values(new_app_materials)
-        .execute(&conn)
-        .chain_err(|| ErrorKind::InsertAppMaterialsErr(app_id))?;
+        diesel::insert_into(app_fonts::table)
+            .values(new_app_fonts)
+            .execute(&conn)
+            .chain_err(|| ErrorKind::InsertAppFontsErr(app_id))?;
 
-    let new_app_fonts: &[AppFont] = &app
-        .font_ids
-        .into_iter()
-        .map(|font_id| AppFont { app_id, font_id })
-        .collect::<Vec<_>>();
+        let new_app_carriers: &[AppCarrier] = &app
+            .carrier_ids
+            .into_iter()
+            .map(|carrier_id| AppCarrier { app_id, carrier_id })
+            .collect::<Vec<_>>();
 
-    diesel::insert_into(app_fonts::table)
-        .values(new_app_fonts)
-        .execute(&conn)
-        .chain_err(|| ErrorKind::InsertAppFontsErr(app_id))?;
+        diesel::insert_into(app_carriers::table)
+            .values(new_app_carriers)
+            .execute(&conn)
+            .chain_err(|| ErrorKind::InsertAppCarriersErr(app_id))?;
+
+        let new_app_templates: &[AppTemplate] = &app
+            .template_ids
+            .into_iter()
+            .map(|template_id| AppTemplate {
+                app_id,
+                template_id,
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(app_templates::table)
+            .values(new_app_templates)
+            .execute(&conn)
+            .chain_err(|| ErrorKind::InsertAppTemplatesErr(app_id))?;
+
+        let new_app_pictos: &[AppPicto] = &app
+            .picto_ids
+            .into_iter()
+            .map(|picto_id| AppPicto { app_id, picto_id })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(app_pictos::table)
+            .values(new_app_pictos)
+            .execute(&conn)
+            .chain_err(|| ErrorKind::InsertAppPictosErr(app_id))?;
+
+        let new_app_shapes: &[AppShape] = &app
+            .shape_ids
+            .into_iter()
+            .map(|shape_id| AppShape { app_id, shape_id })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(app_shapes::table)
+            .values(new_app_shapes)
+            .execute(&conn)
+            .chain_err(|| ErrorKind::InsertAppShapesErr(app_id))?;
+
+        let new_app_origins: &[AppOrigin] = &app
+            .origins
+            .iter()
+            .map(|origin| origin.trim())
+            .filter(|origin| !origin.is_empty())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(|origin| AppOrigin {
+                app_id,
+                origin: origin.to_owned(),
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(app_origins::table)
+            .values(new_app_origins)
+            .execute(&conn)
+            .chain_err(|| ErrorKind::InsertAppOriginsErr(app_id))?;
+
+        Ok(())
+    })?;
+
+    allowed_origins.refresh(&conn)?;
 
     Ok(HttpResponse::NoContent().finish())
 }
@@ -293,6 +1072,8 @@ async fn set(pool: web::Data<database::Pool>, app: web::Json<AppJson>) -> Result
 #[delete("/app/{id}")]
 async fn del(
     pool: web::Data<database::Pool>,
+    allowed_origins: web::Data<AllowedOrigins>,
+    _: Admin,
     web::Path(id): web::Path<i32>,
 ) -> Result<HttpResponse> {
     use crate::schema::apps::dsl::apps;
@@ -302,9 +1083,60 @@ async fn del(
         .execute(&conn)
         .chain_err(|| ErrorKind::DeleteAppErr(id))?;
 
+    allowed_origins.refresh(&conn)?;
+
     Ok(HttpResponse::NoContent().finish())
 }
 
+#[post("/app/{id}/clone")]
+async fn clone(
+    pool: web::Data<database::Pool>,
+    allowed_origins: web::Data<AllowedOrigins>,
+    _: Admin,
+    web::Path(id): web::Path<i32>,
+    opts: Option<web::Json<CloneAppJson>>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let opts = opts.map(|opts| opts.into_inner()).unwrap_or_default();
+    let new_app = clone_app(&conn, id, &opts)?;
+    allowed_origins.refresh(&conn)?;
+    Ok(HttpResponse::Created().json(new_app))
+}
+
 pub fn services(cfg: &mut web::ServiceConfig) {
-    cfg.service(get).service(set).service(del);
+    cfg.service(get).service(set).service(clone).service(del);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::auth::Role;
+
+    #[test]
+    fn hides_assets_private_to_other_apps() {
+        // Asset 1 is private to app 1, asset 2 is shared by apps 1 and 2,
+        // asset 3 is private to app 2.
+        let links = [(1, 1), (2, 1), (2, 2), (3, 2)];
+        assert_eq!(hidden_ids(&links, &[1]), [3].iter().copied().collect());
+        assert_eq!(hidden_ids(&links, &[2]), [1].iter().copied().collect());
+        assert_eq!(
+            hidden_ids(&links, &[3]),
+            [1, 2, 3].iter().copied().collect()
+        );
+        assert!(hidden_ids(&links, &[1, 2]).is_empty());
+        assert!(hidden_ids(&[], &[1]).is_empty());
+    }
+
+    #[test]
+    fn hides_assets_of_other_apps_from_members_only() {
+        let links = [(1, 1), (2, 1), (2, 2), (3, 2)];
+        let member = Claims::new(1, Role::Member, vec![2]);
+        assert_eq!(
+            claims_hidden_ids(&links, &member),
+            [1].iter().copied().collect()
+        );
+
+        let admin = Claims::new(1, Role::Admin, Vec::new());
+        assert!(claims_hidden_ids(&links, &admin).is_empty());
+    }
 }
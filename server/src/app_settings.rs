@@ -0,0 +1,280 @@
+use actix_web::{
+    self,
+    dev::HttpResponseBuilder,
+    get,
+    http::{header, StatusCode},
+    put, web, HttpResponse,
+};
+use diesel::prelude::*;
+use error_chain::error_chain;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::app::{self, PublicApp};
+use crate::database;
+use crate::font::Font;
+use crate::schema::app_settings;
+use crate::shared::auth::{self, Claims};
+
+/// The editor steps, in display order.
+pub const STEPS: [&str; 3] = ["product", "dimension", "fixation"];
+
+// Error management
+
+error_chain! {
+    links {
+        App(app::Error, app::ErrorKind);
+        Auth(auth::Error, auth::ErrorKind);
+    }
+    errors {
+        GetDbConnErr {
+            description("Impossible de se connecter à la base de données")
+            display("Could not get db conn from pool")
+        }
+        FindAppSettingsErr(id: i32) {
+            description("Impossible de récupérer les paramètres de l'application")
+            display("Could not find app_settings `{}`", id)
+        }
+        UpsertAppSettingsErr(id: i32) {
+            description("Impossible d'enregistrer les paramètres de l'application")
+            display("Could not upsert app_settings `{}`", id)
+        }
+        SelectFontsErr(id: i32) {
+            description("Impossible de récupérer les polices de l'application")
+            display("Could not select fonts of app `{}`", id)
+        }
+        InvalidStepErr(step: String) {
+            description("Étape de l'éditeur inconnue")
+            display("Unknown editor step `{}`", step)
+        }
+        InvalidColorErr(color: String) {
+            description("Couleur invalide, format attendu : #rrggbb")
+            display("Invalid palette color `{}`", color)
+        }
+        ProductNotInAppErr(id: i32) {
+            description("Le produit par défaut doit être rattaché à l'application")
+            display("Default material `{}` is not enabled for app", id)
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        HttpResponseBuilder::new(self.status_code())
+            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.description().to_owned())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match *self.kind() {
+            ErrorKind::InvalidStepErr(_)
+            | ErrorKind::InvalidColorErr(_)
+            | ErrorKind::ProductNotInAppErr(_) => StatusCode::BAD_REQUEST,
+            ErrorKind::Auth(ref kind) => auth::status_code(kind),
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+// Models
+
+/// The branding of an app and the defaults of its embedded editor. The
+/// `template_id`, `product_id` and `steps` replace the editor props of the
+/// same name when the host page does not set them.
+#[derive(Debug, Clone, PartialEq, Queryable, Insertable, AsChangeset, Serialize, Deserialize)]
+#[table_name = "app_settings"]
+#[changeset_options(treat_none_as_null = "true")]
+#[serde(rename_all = "camelCase")]
+pub struct AppSettings {
+    #[serde(default)]
+    pub app_id: i32,
+    #[serde(default)]
+    pub logo_url: Option<String>,
+    #[serde(default)]
+    pub palette: Vec<String>,
+    #[serde(default)]
+    pub template_id: Option<i32>,
+    #[serde(default)]
+    pub product_id: Option<i32>,
+    #[serde(default)]
+    pub steps: Vec<String>,
+}
+
+impl AppSettings {
+    pub fn new(app_id: i32) -> Self {
+        Self {
+            app_id,
+            logo_url: None,
+            palette: Vec::new(),
+            template_id: None,
+            product_id: None,
+            steps: STEPS.iter().map(|step| step.to_string()).collect(),
+        }
+    }
+}
+
+/// The configuration sent to the embedded editor.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EditorSettings {
+    name: String,
+    #[serde(flatten)]
+    settings: AppSettings,
+    fonts: Vec<Font>,
+}
+
+// Helpers
+
+pub fn check(settings: &AppSettings) -> Result<()> {
+    if let Some(step) = settings
+        .steps
+        .iter()
+        .find(|step| !STEPS.contains(&step.as_str()))
+    {
+        return Err(ErrorKind::InvalidStepErr(step.to_owned()).into());
+    }
+
+    if let Some(color) = settings.palette.iter().find(|color| !is_hex_color(color)) {
+        return Err(ErrorKind::InvalidColorErr(color.to_owned()).into());
+    }
+
+    Ok(())
+}
+
+fn is_hex_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => false,
+    }
+}
+
+/// Finds the settings of the app, or the default ones if never saved.
+pub fn find(conn: &database::PooledConnection, app_id: i32) -> Result<AppSettings> {
+    let settings = app_settings::table
+        .find(app_id)
+        .first::<AppSettings>(conn)
+        .optional()
+        .chain_err(|| ErrorKind::FindAppSettingsErr(app_id))?;
+
+    Ok(settings.unwrap_or_else(|| AppSettings::new(app_id)))
+}
+
+pub fn upsert(conn: &database::PooledConnection, settings: &AppSettings) -> Result<()> {
+    check(settings)?;
+
+    if let Some(product_id) = settings.product_id {
+        if !app::has_material(conn, settings.app_id, product_id)? {
+            return Err(ErrorKind::ProductNotInAppErr(product_id).into());
+        }
+    }
+
+    diesel::insert_into(app_settings::table)
+        .values(settings)
+        .on_conflict(app_settings::app_id)
+        .do_update()
+        .set(settings)
+        .execute(conn)
+        .chain_err(|| ErrorKind::UpsertAppSettingsErr(settings.app_id))?;
+
+    Ok(())
+}
+
+// Services
+
+#[get("/settings")]
+async fn get_for_app(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let settings = find(&conn, app.id)?;
+    let font_ids = app::get_font_ids(&conn, app.id)?;
+
+    let fonts = {
+        use crate::schema::fonts::dsl;
+        dsl::fonts
+            .filter(dsl::id.eq_any(font_ids))
+            .order(dsl::name)
+            .load::<Font>(&conn)
+    }
+    .chain_err(|| ErrorKind::SelectFontsErr(app.id))?;
+
+    Ok(HttpResponse::Ok().json(EditorSettings {
+        name: app.name,
+        settings,
+        fonts,
+    }))
+}
+
+#[get("/app/{id}/settings")]
+async fn get(
+    pool: web::Data<database::Pool>,
+    claims: Claims,
+    web::Path(app_id): web::Path<i32>,
+) -> Result<HttpResponse> {
+    claims.check_app(app_id)?;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    Ok(HttpResponse::Ok().json(find(&conn, app_id)?))
+}
+
+#[put("/app/{id}/settings")]
+async fn set(
+    pool: web::Data<database::Pool>,
+    claims: Claims,
+    web::Path(app_id): web::Path<i32>,
+    settings: web::Json<AppSettings>,
+) -> Result<HttpResponse> {
+    claims.check_app(app_id)?;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let settings = AppSettings {
+        app_id,
+        ..settings.into_inner()
+    };
+
+    upsert(&conn, &settings)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn pub_services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_for_app);
+}
+
+pub fn priv_services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get).service(set);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enables_all_steps_by_default() {
+        assert_eq!(AppSettings::new(1).steps, STEPS);
+        assert!(check(&AppSettings::new(1)).is_ok());
+    }
+
+    #[test]
+    fn refuses_unknown_steps() {
+        let settings = AppSettings {
+            steps: vec![String::from("product"), String::from("payment")],
+            ..AppSettings::new(1)
+        };
+        assert!(matches!(
+            check(&settings).unwrap_err().kind(),
+            ErrorKind::InvalidStepErr(step) if step == "payment"
+        ));
+    }
+
+    #[test]
+    fn refuses_invalid_colors() {
+        let palette = |color: &str| AppSettings {
+            palette: vec![String::from("#3240ff"), String::from(color)],
+            ..AppSettings::new(1)
+        };
+        assert!(check(&palette("#F7C629")).is_ok());
+        assert!(check(&palette("f7c629")).is_err());
+        assert!(check(&palette("#fff")).is_err());
+        assert!(check(&palette("#f7c62z")).is_err());
+    }
+}
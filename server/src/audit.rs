@@ -0,0 +1,817 @@
+use actix_web::{
+    self,
+    dev::{HttpResponseBuilder, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    error::{JsonPayloadError, PayloadError},
+    get,
+    http::{header, StatusCode},
+    web::{self, Bytes, BytesMut},
+    HttpMessage, HttpResponse,
+};
+use chrono::NaiveDateTime;
+use diesel::{prelude::*, sql_query, sql_types::Integer};
+use error_chain::error_chain;
+use futures::{
+    future::{ok, LocalBoxFuture, Ready},
+    stream, StreamExt,
+};
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use crate::database;
+use crate::schema::audit_logs;
+use crate::shared::auth::{Admin, Claims};
+use crate::shared::json;
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+
+// Error management
+
+error_chain! {
+    errors {
+        GetDbConnErr {
+            description("Impossible de se connecter à la base de données")
+            display("Could not get db conn from pool")
+        }
+        SelectAuditLogsErr {
+            description("Impossible de récupérer le journal des modifications")
+            display("Could not select audit logs")
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        HttpResponseBuilder::new(self.status_code())
+            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.description().to_owned())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+// Models
+
+/// What a route changes: a table whose `id` is the audited id, and the
+/// tables of its associations, with the column referring to it.
+#[derive(Debug)]
+pub struct Entity {
+    pub name: &'static str,
+    table: Option<&'static str>,
+    related: &'static [(&'static str, &'static str)],
+    /// Columns never written to the log.
+    hidden: &'static [&'static str],
+}
+
+/// Where the audited id is read: a path segment, or the `id` field of the
+/// JSON body, 0 meaning a creation.
+#[derive(Debug, Clone, Copy)]
+enum Id {
+    Path(&'static str),
+    Body,
+}
+
+#[derive(Debug)]
+struct Route {
+    method: &'static str,
+    pattern: &'static str,
+    entity: &'static Entity,
+    id: Id,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Create,
+    Update,
+    Delete,
+}
+
+impl Action {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Delete => "delete",
+        }
+    }
+}
+
+#[derive(Debug, Queryable, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLog {
+    pub id: i32,
+    /// Not a foreign key, so that the log outlives the user.
+    pub user_id: Option<i32>,
+    /// Subject of the claims: the user id, or the API key prefix.
+    pub subject: String,
+    pub entity: String,
+    pub entity_id: Option<i32>,
+    pub action: String,
+    pub method: String,
+    pub path: String,
+    /// Changed fields, as `{"field": {"before": …, "after": …}}`.
+    pub changes: Value,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "audit_logs"]
+struct InsertableAuditLog<'a> {
+    pub user_id: Option<i32>,
+    pub subject: &'a str,
+    pub entity: &'a str,
+    pub entity_id: Option<i32>,
+    pub action: &'a str,
+    pub method: &'a str,
+    pub path: &'a str,
+    pub changes: &'a Value,
+}
+
+#[derive(QueryableByName)]
+struct Snapshot {
+    #[sql_type = "diesel::sql_types::Jsonb"]
+    json: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogQuery {
+    pub entity: Option<String>,
+    pub entity_id: Option<i32>,
+    pub user_id: Option<i32>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// Entities
+
+static APP: Entity = Entity {
+    name: "app",
+    table: Some("apps"),
+    related: &[
+        ("app_carriers", "app_id"),
+        ("app_fonts", "app_id"),
+        ("app_materials", "app_id"),
+        ("app_origins", "app_id"),
+        ("app_pictos", "app_id"),
+        ("app_shapes", "app_id"),
+        ("app_templates", "app_id"),
+        ("app_users", "app_id"),
+    ],
+    hidden: &[],
+};
+
+static APP_SETTINGS: Entity = Entity {
+    name: "app_settings",
+    table: None,
+    related: &[("app_settings", "app_id")],
+    hidden: &[],
+};
+
+static APP_MARKUPS: Entity = Entity {
+    name: "app_markups",
+    table: Some("apps"),
+    related: &[
+        ("app_fixation_markups", "app_id"),
+        ("app_material_markups", "app_id"),
+    ],
+    hidden: &[],
+};
+
+static API_KEY: Entity = Entity {
+    name: "api_key",
+    table: Some("app_api_keys"),
+    related: &[],
+    hidden: &["secret_hash"],
+};
+
+static BADGE: Entity = Entity {
+    name: "badge",
+    table: Some("badges"),
+    related: &[],
+    hidden: &[],
+};
+
+static CARRIER: Entity = Entity {
+    name: "carrier",
+    table: Some("carriers"),
+    related: &[("shipping_brackets", "carrier_id")],
+    hidden: &[],
+};
+
+static DIMENSION: Entity = Entity {
+    name: "dimension",
+    table: Some("dimensions"),
+    related: &[],
+    hidden: &[],
+};
+
+static DISCOUNT: Entity = Entity {
+    name: "discount",
+    table: Some("discounts"),
+    related: &[],
+    hidden: &[],
+};
+
+static FIXATION: Entity = Entity {
+    name: "fixation",
+    table: Some("fixations"),
+    related: &[("fixation_conditions", "fixation_id")],
+    hidden: &[],
+};
+
+static FOLDER: Entity = Entity {
+    name: "folder",
+    table: Some("folders"),
+    related: &[],
+    hidden: &[],
+};
+
+static FONT: Entity = Entity {
+    name: "font",
+    table: Some("fonts"),
+    related: &[],
+    hidden: &[],
+};
+
+static HOLIDAY: Entity = Entity {
+    name: "holiday",
+    table: Some("holidays"),
+    related: &[],
+    hidden: &[],
+};
+
+static MATERIAL: Entity = Entity {
+    name: "material",
+    table: Some("materials"),
+    related: &[
+        ("material_badges", "material_id"),
+        ("material_dimensions", "material_id"),
+        ("material_discounts", "material_id"),
+        ("material_fixations", "material_id"),
+        ("material_shapes", "material_id"),
+    ],
+    hidden: &[],
+};
+
+static ORDER: Entity = Entity {
+    name: "order",
+    table: Some("orders"),
+    related: &[],
+    hidden: &[],
+};
+
+static PICTO: Entity = Entity {
+    name: "picto",
+    table: Some("pictos"),
+    related: &[],
+    hidden: &[],
+};
+
+static SHAPE: Entity = Entity {
+    name: "shape",
+    table: Some("shapes"),
+    related: &[],
+    hidden: &[],
+};
+
+static TAX_RATE: Entity = Entity {
+    name: "tax_rate",
+    table: Some("tax_rates"),
+    related: &[],
+    hidden: &[],
+};
+
+static TEMPLATE: Entity = Entity {
+    name: "template",
+    table: Some("templates"),
+    related: &[],
+    hidden: &[],
+};
+
+static UPLOAD: Entity = Entity {
+    name: "upload",
+    table: None,
+    related: &[],
+    hidden: &[],
+};
+
+static USER: Entity = Entity {
+    name: "user",
+    table: Some("users"),
+    related: &[],
+    hidden: &["password", "token"],
+};
+
+static USER_LOCK: Entity = Entity {
+    name: "user_lock",
+    table: None,
+    related: &[],
+    hidden: &[],
+};
+
+static USER_SESSIONS: Entity = Entity {
+    name: "user_sessions",
+    table: None,
+    related: &[("refresh_tokens", "user_id")],
+    hidden: &["token_hash"],
+};
+
+static WEBHOOK: Entity = Entity {
+    name: "webhook",
+    table: Some("webhooks"),
+    related: &[],
+    hidden: &["secret"],
+};
+
+const fn put(pattern: &'static str, entity: &'static Entity, id: Id) -> Route {
+    Route {
+        method: "PUT",
+        pattern,
+        entity,
+        id,
+    }
+}
+
+const fn delete(pattern: &'static str, entity: &'static Entity) -> Route {
+    Route {
+        method: "DELETE",
+        pattern,
+        entity,
+        id: Id::Path("id"),
+    }
+}
+
+/// The private routes changing data. A route missing here is still logged,
+/// under its path and without changes.
+static ROUTES: &[Route] = &[
+    put("/app", &APP, Id::Body),
+    delete("/app/{id}", &APP),
+    put("/app/{id}/settings", &APP_SETTINGS, Id::Path("id")),
+    put("/app/{id}/markups", &APP_MARKUPS, Id::Path("id")),
+    delete("/app/{app_id}/api-key/{id}", &API_KEY),
+    put("/app/{id}/webhook", &WEBHOOK, Id::Body),
+    delete("/app/{app_id}/webhook/{id}", &WEBHOOK),
+    put("/badge", &BADGE, Id::Body),
+    delete("/badge/{id}", &BADGE),
+    put("/carrier", &CARRIER, Id::Body),
+    delete("/carrier/{id}", &CARRIER),
+    put("/dimension", &DIMENSION, Id::Body),
+    delete("/dimension/{id}", &DIMENSION),
+    put("/discount", &DISCOUNT, Id::Body),
+    delete("/discount/{id}", &DISCOUNT),
+    put("/fixation", &FIXATION, Id::Body),
+    delete("/fixation/{id}", &FIXATION),
+    put("/folder", &FOLDER, Id::Body),
+    delete("/folder/{id}", &FOLDER),
+    put("/font", &FONT, Id::Body),
+    delete("/font/{id}", &FONT),
+    put("/holiday", &HOLIDAY, Id::Body),
+    delete("/holiday/{id}", &HOLIDAY),
+    put("/material", &MATERIAL, Id::Body),
+    delete("/material/{id}", &MATERIAL),
+    put("/order/{id}/status", &ORDER, Id::Path("id")),
+    put("/picto", &PICTO, Id::Body),
+    delete("/picto/{id}", &PICTO),
+    put("/shape", &SHAPE, Id::Body),
+    delete("/shape/{id}", &SHAPE),
+    put("/tax-rate", &TAX_RATE, Id::Body),
+    delete("/tax-rate/{id}", &TAX_RATE),
+    put("/template", &TEMPLATE, Id::Body),
+    delete("/template/{id}", &TEMPLATE),
+    put("/upload", &UPLOAD, Id::Body),
+    put("/user", &USER, Id::Body),
+    delete("/user/{id}", &USER),
+    delete("/user/{id}/lock", &USER_LOCK),
+    delete("/user/{id}/session", &USER_SESSIONS),
+];
+
+// Helpers
+
+/// Finds the route of a request, with the id read from its path if any.
+fn find_route(method: &str, path: &str) -> Option<(&'static Route, Option<i32>)> {
+    ROUTES.iter().find_map(|route| {
+        let pattern = route.pattern.split('/').collect::<Vec<_>>();
+        let segments = path.split('/').collect::<Vec<_>>();
+        if route.method != method || pattern.len() != segments.len() {
+            return None;
+        }
+
+        let mut id = None;
+        for (part, segment) in pattern.iter().zip(segments) {
+            if part.starts_with('{') && part.ends_with('}') {
+                if let Id::Path(name) = route.id {
+                    if part[1..part.len() - 1] == *name {
+                        id = Some(segment.parse::<i32>().ok()?);
+                    }
+                }
+            } else if *part != segment {
+                return None;
+            }
+        }
+
+        Some((route, id))
+    })
+}
+
+/// Removes the hidden columns, from the entity and its associations.
+fn redact(value: &mut Value, hidden: &[&str]) {
+    match value {
+        Value::Object(fields) => {
+            for column in hidden {
+                fields.remove(*column);
+            }
+            fields.values_mut().for_each(|value| redact(value, hidden));
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact(item, hidden)),
+        _ => (),
+    }
+}
+
+/// Keeps the fields that changed, with their value before and after.
+fn diff(before: Option<&Value>, after: Option<&Value>) -> Value {
+    let empty = Map::new();
+    let before = before.and_then(Value::as_object).unwrap_or(&empty);
+    let after = after.and_then(Value::as_object).unwrap_or(&empty);
+
+    let changes = before
+        .keys()
+        .chain(after.keys().filter(|key| !before.contains_key(*key)))
+        .filter_map(|key| {
+            let (prev, next) = (before.get(key), after.get(key));
+            if prev == next {
+                None
+            } else {
+                Some((key.to_owned(), json!({"before": prev, "after": next})))
+            }
+        })
+        .collect::<Map<_, _>>();
+
+    Value::Object(changes)
+}
+
+/// Reads the entity and its associations as JSON, `None` if the entity
+/// does not exist.
+fn snapshot(
+    conn: &database::PooledConnection,
+    entity: &Entity,
+    id: i32,
+) -> QueryResult<Option<Value>> {
+    let mut value = match entity.table {
+        Some(table) => {
+            let row = sql_query(format!(
+                "SELECT to_jsonb(t) AS json FROM {} t WHERE t.id = $1",
+                table
+            ))
+            .bind::<Integer, _>(id)
+            .get_result::<Snapshot>(conn)
+            .optional()?;
+
+            match row {
+                Some(row) => row.json,
+                None => return Ok(None),
+            }
+        }
+        None => json!({}),
+    };
+
+    for (table, column) in entity.related {
+        let rows = sql_query(format!(
+            "SELECT COALESCE(jsonb_agg(to_jsonb(t) ORDER BY to_jsonb(t)::TEXT), '[]') AS json \
+             FROM {} t WHERE t.{} = $1",
+            table, column
+        ))
+        .bind::<Integer, _>(id)
+        .get_result::<Snapshot>(conn)?;
+        value[*table] = rows.json;
+    }
+
+    redact(&mut value, entity.hidden);
+    Ok(Some(value))
+}
+
+async fn take_snapshot(
+    pool: &web::Data<database::Pool>,
+    entity: Option<&'static Entity>,
+    id: Option<i32>,
+) -> Option<Value> {
+    let (entity, id) = match (entity, id) {
+        (Some(entity), Some(id)) => (entity, id),
+        _ => return None,
+    };
+
+    let pool = pool.clone();
+    web::block(move || {
+        let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+        snapshot(&conn, entity, id)
+            .chain_err(|| format!("Could not snapshot {} `{}`", entity.name, id))
+    })
+    .await
+    .unwrap_or_else(|err| {
+        error!("{}", err);
+        None
+    })
+}
+
+/// Reads the JSON body, then puts it back for the service. Bodies over the
+/// limit of the JSON extractor are refused the same way.
+async fn read_json_body(req: &mut ServiceRequest) -> actix_web::Result<Option<Value>> {
+    if req.content_type() != "application/json" {
+        return Ok(None);
+    }
+
+    let mut payload = req.take_payload();
+    let mut body = BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk?;
+        if body.len() + chunk.len() > json::PAYLOAD_LIMIT {
+            return Err(json::payload_error(JsonPayloadError::Overflow));
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    let body = body.freeze();
+    let value = serde_json::from_slice::<Value>(&body).ok();
+    req.set_payload(Payload::Stream(Box::pin(stream::once(async move {
+        Ok::<Bytes, PayloadError>(body)
+    }))));
+
+    Ok(value)
+}
+
+// Middleware
+
+/// Logs the private PUT and DELETE requests: who made them, and the fields
+/// of the entity they changed. Goes inside the authentication, which gives
+/// the claims. Logging is best effort: the change is already made, so a
+/// failure is only reported.
+pub struct Audit;
+
+pub struct AuditMiddleware<S> {
+    service: Rc<RefCell<S>>,
+}
+
+impl<S, B> Transform<S> for Audit
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>
+        + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type InitError = ();
+    type Transform = AuditMiddleware<S>;
+    type Future = Ready<std::result::Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(AuditMiddleware {
+            service: Rc::new(RefCell::new(service)),
+        })
+    }
+}
+
+impl<S, B> Service for AuditMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>
+        + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, std::result::Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<std::result::Result<(), Self::Error>> {
+        self.service.borrow_mut().poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let method = req.method().as_str().to_owned();
+            let claims = req.extensions().get::<Claims>().cloned();
+            let pool = req.app_data::<web::Data<database::Pool>>().cloned();
+
+            let (claims, pool) = match (claims, pool) {
+                (Some(claims), Some(pool)) if method == "PUT" || method == "DELETE" => {
+                    (claims, pool)
+                }
+                _ => {
+                    // The service must not stay borrowed while the request
+                    // is handled.
+                    let res = service.borrow_mut().call(req);
+                    return res.await;
+                }
+            };
+
+            let path = req.path().to_owned();
+            let route = find_route(&method, &path);
+            let entity = route.map(|(route, _)| route.entity);
+            let body = match route {
+                Some((Route { id: Id::Body, .. }, _)) => read_json_body(&mut req).await?,
+                _ => None,
+            };
+            let id = match route {
+                Some((Route { id: Id::Body, .. }, _)) => body
+                    .as_ref()
+                    .and_then(|body| body["id"].as_i64())
+                    .map(|id| id as i32)
+                    .filter(|id| *id != 0),
+                Some((_, id)) => id,
+                None => None,
+            };
+
+            let before = take_snapshot(&pool, entity, id).await;
+            let has_table = entity.is_some_and(|entity| entity.table.is_some());
+            let action = match method.as_str() {
+                "DELETE" => Action::Delete,
+                _ if id.is_none() || (before.is_none() && has_table) => Action::Create,
+                _ => Action::Update,
+            };
+
+            let res = service.borrow_mut().call(req);
+            let res = res.await?;
+            if !res.status().is_success() {
+                return Ok(res);
+            }
+
+            let after = match action {
+                Action::Create if id.is_none() => body.map(|mut body| {
+                    redact(&mut body, entity.map_or(&[], |entity| entity.hidden));
+                    body
+                }),
+                _ => take_snapshot(&pool, entity, id).await,
+            };
+            // Some deletions only mark the row, like revoking an API key.
+            let action = match action {
+                Action::Delete if has_table && after.is_some() => Action::Update,
+                action => action,
+            };
+
+            let changes = diff(before.as_ref(), after.as_ref());
+            let entity = entity.map_or(path.as_str(), |entity| entity.name);
+            let log = InsertableAuditLog {
+                user_id: claims.sub.parse::<i32>().ok(),
+                subject: &claims.sub,
+                entity,
+                entity_id: id,
+                action: action.as_str(),
+                method: &method,
+                path: &path,
+                changes: &changes,
+            };
+
+            let inserted = pool
+                .get()
+                .chain_err(|| ErrorKind::GetDbConnErr)
+                .and_then(|conn| {
+                    diesel::insert_into(audit_logs::table)
+                        .values(&log)
+                        .execute(&conn)
+                        .chain_err(|| {
+                            format!("Could not insert audit log of `{} {}`", method, path)
+                        })
+                });
+
+            if let Err(err) = inserted {
+                error!("{}", err);
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+// Private services
+
+#[get("/audit-log")]
+async fn get(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    query: web::Query<AuditLogQuery>,
+) -> Result<HttpResponse> {
+    use crate::schema::audit_logs::dsl;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    let mut logs = dsl::audit_logs.into_boxed();
+    if let Some(ref entity) = query.entity {
+        logs = logs.filter(dsl::entity.eq(entity));
+    }
+    if let Some(entity_id) = query.entity_id {
+        logs = logs.filter(dsl::entity_id.eq(entity_id));
+    }
+    if let Some(user_id) = query.user_id {
+        logs = logs.filter(dsl::user_id.eq(user_id));
+    }
+
+    let logs = logs
+        .order(dsl::id.desc())
+        .limit(query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT))
+        .offset(query.offset.unwrap_or(0).max(0))
+        .load::<AuditLog>(&conn)
+        .chain_err(|| ErrorKind::SelectAuditLogsErr)?;
+
+    Ok(HttpResponse::Ok().json(logs))
+}
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{rt::System, test::TestRequest};
+
+    #[test]
+    fn finds_routes_with_their_id() {
+        let (route, id) = find_route("DELETE", "/material/42").unwrap();
+        assert_eq!(route.entity.name, "material");
+        assert_eq!(id, Some(42));
+
+        let (route, id) = find_route("DELETE", "/app/3/webhook/7").unwrap();
+        assert_eq!(route.entity.name, "webhook");
+        assert_eq!(id, Some(7));
+
+        let (route, id) = find_route("PUT", "/app/3/settings").unwrap();
+        assert_eq!(route.entity.name, "app_settings");
+        assert_eq!(id, Some(3));
+
+        let (route, id) = find_route("PUT", "/fixation").unwrap();
+        assert_eq!(route.entity.name, "fixation");
+        assert_eq!(id, None);
+
+        assert!(find_route("GET", "/material/42").is_none());
+        assert!(find_route("DELETE", "/material/abc").is_none());
+        assert!(find_route("DELETE", "/unknown/42").is_none());
+    }
+
+    #[test]
+    fn redacts_hidden_columns() {
+        let mut value = json!({
+            "id": 1,
+            "password": "hash",
+            "refresh_tokens": [{"id": 2, "token_hash": "hash"}],
+        });
+        redact(&mut value, &["password", "token_hash"]);
+        assert_eq!(value, json!({"id": 1, "refresh_tokens": [{"id": 2}]}));
+    }
+
+    #[test]
+    fn diffs_changed_fields_only() {
+        let before = json!({
+            "id": 1,
+            "price": 100,
+            "fixation_conditions": [{"id": 1, "shape_id": 2}],
+        });
+        let after = json!({
+            "id": 1,
+            "price": 120,
+            "fixation_conditions": [{"id": 3, "shape_id": 2}],
+        });
+
+        assert_eq!(
+            diff(Some(&before), Some(&after)),
+            json!({
+                "price": {"before": 100, "after": 120},
+                "fixation_conditions": {
+                    "before": [{"id": 1, "shape_id": 2}],
+                    "after": [{"id": 3, "shape_id": 2}],
+                },
+            })
+        );
+
+        assert_eq!(
+            diff(Some(&json!({"id": 1})), None),
+            json!({"id": {"before": 1, "after": null}})
+        );
+        assert_eq!(diff(Some(&before), Some(&before)), json!({}));
+    }
+
+    #[test]
+    fn refuses_bodies_over_the_json_limit() {
+        let read = |len: usize| {
+            let mut req = TestRequest::default()
+                .header(header::CONTENT_TYPE, "application/json")
+                .set_payload(format!("\"{}\"", "a".repeat(len)))
+                .to_srv_request();
+            System::new("test").block_on(async move { read_json_body(&mut req).await })
+        };
+
+        assert!(read(16).unwrap().is_some());
+        assert!(read(json::PAYLOAD_LIMIT).is_err());
+    }
+}
@@ -13,6 +13,7 @@ use std::ops::Deref;
 
 use crate::database;
 use crate::schema::badges;
+use crate::shared::auth::Admin;
 
 // Error management
 
@@ -88,7 +89,11 @@ async fn get(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
 }
 
 #[put("/badge")]
-async fn set(pool: web::Data<database::Pool>, badge: web::Json<Badge>) -> Result<HttpResponse> {
+async fn set(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    badge: web::Json<Badge>,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let badge_id = badge.id;
 
@@ -121,6 +126,7 @@ async fn set(pool: web::Data<database::Pool>, badge: web::Json<Badge>) -> Result
 #[delete("/badge/{id}")]
 async fn del(
     pool: web::Data<database::Pool>,
+    _: Admin,
     web::Path(id): web::Path<i32>,
 ) -> Result<HttpResponse> {
     use crate::schema::badges::dsl::badges;
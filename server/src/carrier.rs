@@ -0,0 +1,368 @@
+use actix_web::{
+    self, delete,
+    dev::HttpResponseBuilder,
+    get,
+    http::{header, StatusCode},
+    put, web, HttpResponse,
+};
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use error_chain::error_chain;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database,
+    delivery::Calendar,
+    schema::carriers,
+    shared::{auth::Admin, money::Money},
+    shipping_bracket::{self, ShippingBracket},
+};
+
+// Error management
+
+error_chain! {
+    links {
+        ShippingBracket(shipping_bracket::Error, shipping_bracket::ErrorKind);
+    }
+    errors {
+        GetDbConnErr {
+            description("Impossible de se connecter à la base de données")
+            display("Could not get db conn from pool")
+        }
+        SelectCarriersErr {
+            description("Impossible de récupérer la liste des transporteurs")
+            display("Could not select carriers")
+        }
+        InsertCarrierErr {
+            description("Impossible de créer le transporteur")
+            display("Could not insert carrier")
+        }
+        UpdateCarrierErr(id: i32) {
+            description("Impossible de modifier le transporteur")
+            display("Could not update carrier `{}`", id)
+        }
+        DeleteCarrierErr(id: i32) {
+            description("Impossible de supprimer le transporteur")
+            display("Could not delete carrier `{}`", id)
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        HttpResponseBuilder::new(self.status_code())
+            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.description().to_owned())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+// Models
+
+/// A carrier accepts parcels up to `max_length` by `max_width` mm, a bound
+/// set to 0 meaning no limit, and delivers them in `transit_days` business
+/// days. Its prices depend on the parcel weight, see `ShippingBracket`.
+#[derive(Debug, Default, Clone, Identifiable, Queryable, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Carrier {
+    pub id: i32,
+    pub name: String,
+    pub max_length: f32,
+    pub max_width: f32,
+    pub transit_days: i16,
+}
+
+#[derive(Insertable)]
+#[table_name = "carriers"]
+struct InsertableCarrier<'a> {
+    pub name: &'a str,
+    pub max_length: &'a f32,
+    pub max_width: &'a f32,
+    pub transit_days: &'a i16,
+}
+
+#[derive(Identifiable, AsChangeset)]
+#[table_name = "carriers"]
+struct UpdatableCarrier<'a> {
+    pub id: &'a i32,
+    pub name: &'a str,
+    pub max_length: &'a f32,
+    pub max_width: &'a f32,
+    pub transit_days: &'a i16,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CarrierJson {
+    #[serde(flatten)]
+    pub inner: Carrier,
+    #[serde(default)]
+    pub brackets: Vec<ShippingBracket>,
+}
+
+/// The parcel to ship: its total weight in kg, and the length and the width
+/// in mm of the largest sign it contains.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Parcel {
+    pub weight: f32,
+    pub length: f32,
+    pub width: f32,
+}
+
+impl Parcel {
+    /// Adds a sign of `width` by `height` mm weighing `weight` kg.
+    pub fn add(&mut self, weight: f32, width: f32, height: f32) {
+        self.weight += weight;
+        self.length = self.length.max(width.max(height));
+        self.width = self.width.max(width.min(height));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShippingOption {
+    pub carrier_id: i32,
+    pub carrier_name: String,
+    pub price: Money,
+    pub delivery_date: NaiveDate,
+}
+
+// Helpers
+
+pub fn fits(carrier: &Carrier, parcel: &Parcel) -> bool {
+    let fits_bound = |value: f32, max: f32| max <= 0.0 || value <= max;
+    fits_bound(parcel.length, carrier.max_length) && fits_bound(parcel.width, carrier.max_width)
+}
+
+/// Finds the lightest bracket the weight fits in.
+pub fn find_bracket(brackets: &[ShippingBracket], weight: f32) -> Option<&ShippingBracket> {
+    brackets
+        .iter()
+        .filter(|bracket| weight <= bracket.max_weight)
+        .min_by(|a, b| a.max_weight.total_cmp(&b.max_weight))
+}
+
+/// Lists the carriers able to ship the parcel, cheapest first. A carrier is
+/// left out when the parcel is too large for it or heavier than its last
+/// bracket.
+pub fn shipping_options(
+    carriers: &[CarrierJson],
+    parcel: &Parcel,
+    calendar: &Calendar,
+    ship_date: NaiveDate,
+) -> Vec<ShippingOption> {
+    let mut options: Vec<ShippingOption> = carriers
+        .iter()
+        .filter(|carrier| fits(&carrier.inner, parcel))
+        .filter_map(|carrier| {
+            find_bracket(&carrier.brackets, parcel.weight).map(|bracket| ShippingOption {
+                carrier_id: carrier.inner.id,
+                carrier_name: carrier.inner.name.clone(),
+                price: bracket.price,
+                delivery_date: calendar.delivery_date(ship_date, carrier.inner.transit_days),
+            })
+        })
+        .collect();
+
+    options.sort_by_key(|option| option.price);
+    options
+}
+
+/// Loads the carriers with their brackets. When `app_id` is given, only the
+/// carriers enabled for the app are loaded.
+pub fn get_all(conn: &database::PooledConnection, app_id: Option<i32>) -> Result<Vec<CarrierJson>> {
+    let carriers = match app_id {
+        Some(app_id) => {
+            use crate::schema::app_carriers::dsl;
+            carriers::table
+                .inner_join(dsl::app_carriers)
+                .filter(dsl::app_id.eq(app_id))
+                .select(carriers::all_columns)
+                .order(carriers::id)
+                .load::<Carrier>(conn)
+        }
+        None => carriers::table.order(carriers::id).load::<Carrier>(conn),
+    }
+    .chain_err(|| ErrorKind::SelectCarriersErr)?;
+
+    let brackets = ShippingBracket::belonging_to(&carriers)
+        .order(crate::schema::shipping_brackets::max_weight)
+        .load::<ShippingBracket>(conn)
+        .chain_err(|| ErrorKind::SelectCarriersErr)?
+        .grouped_by(&carriers);
+
+    Ok(carriers
+        .into_iter()
+        .zip(brackets)
+        .map(|(inner, brackets)| CarrierJson { inner, brackets })
+        .collect())
+}
+
+// Services
+
+#[get("/carrier")]
+async fn get(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let all_carriers = get_all(&conn, None)?;
+    Ok(HttpResponse::Ok().json(all_carriers))
+}
+
+#[put("/carrier")]
+async fn set(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    carrier: web::Json<CarrierJson>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let carrier = carrier.into_inner();
+    let mut carrier_id = carrier.inner.id;
+
+    if carrier_id == 0 {
+        let new_carrier = InsertableCarrier {
+            name: &carrier.inner.name,
+            max_length: &carrier.inner.max_length,
+            max_width: &carrier.inner.max_width,
+            transit_days: &carrier.inner.transit_days,
+        };
+
+        carrier_id = diesel::insert_into(carriers::table)
+            .values(&new_carrier)
+            .get_result::<Carrier>(&conn)
+            .chain_err(|| ErrorKind::InsertCarrierErr)?
+            .id;
+    } else {
+        shipping_bracket::del(&conn, carrier_id)?;
+
+        let next_carrier = UpdatableCarrier {
+            id: &carrier.inner.id,
+            name: &carrier.inner.name,
+            max_length: &carrier.inner.max_length,
+            max_width: &carrier.inner.max_width,
+            transit_days: &carrier.inner.transit_days,
+        };
+
+        diesel::update(&next_carrier)
+            .set(&next_carrier)
+            .execute(&conn)
+            .chain_err(|| ErrorKind::UpdateCarrierErr(carrier_id))?;
+    }
+
+    shipping_bracket::set(&conn, carrier_id, &carrier.brackets)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[delete("/carrier/{id}")]
+async fn del(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    web::Path(id): web::Path<i32>,
+) -> Result<HttpResponse> {
+    use crate::schema::carriers::dsl::carriers;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    web::block(move || diesel::delete(carriers.find(id)).execute(&conn))
+        .await
+        .chain_err(|| ErrorKind::DeleteCarrierErr(id))?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get).service(set).service(del);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn carrier(id: i32, max_length: f32, max_width: f32, brackets: &[(f32, i64)]) -> CarrierJson {
+        CarrierJson {
+            inner: Carrier {
+                id,
+                name: format!("Carrier {}", id),
+                max_length,
+                max_width,
+                transit_days: id as i16,
+            },
+            brackets: brackets
+                .iter()
+                .map(|&(max_weight, cents)| ShippingBracket {
+                    id: 0,
+                    carrier_id: id,
+                    max_weight,
+                    price: Money::from_cents(cents),
+                })
+                .collect(),
+        }
+    }
+
+    fn parcel(weight: f32, length: f32, width: f32) -> Parcel {
+        Parcel {
+            weight,
+            length,
+            width,
+        }
+    }
+
+    #[test]
+    fn parcel_keeps_the_largest_sign() {
+        let mut parcel = Parcel::default();
+        parcel.add(1.0, 300.0, 600.0);
+        parcel.add(0.5, 500.0, 100.0);
+        assert_eq!(parcel.length, 600.0);
+        assert_eq!(parcel.width, 300.0);
+        assert_eq!(parcel.weight, 1.5);
+    }
+
+    #[test]
+    fn picks_the_lightest_matching_bracket() {
+        let carrier = carrier(1, 0.0, 0.0, &[(5.0, 900), (1.0, 500), (30.0, 2000)]);
+        let price = |weight| find_bracket(&carrier.brackets, weight).map(|b| b.price.cents());
+        assert_eq!(price(0.2), Some(500));
+        assert_eq!(price(1.0), Some(500));
+        assert_eq!(price(1.1), Some(900));
+        assert_eq!(price(30.0), Some(2000));
+        assert_eq!(price(30.1), None);
+    }
+
+    #[test]
+    fn checks_the_parcel_size() {
+        let bounded = carrier(1, 1000.0, 600.0, &[]);
+        assert!(fits(&bounded.inner, &parcel(1.0, 1000.0, 600.0)));
+        assert!(!fits(&bounded.inner, &parcel(1.0, 1200.0, 300.0)));
+        assert!(!fits(&bounded.inner, &parcel(1.0, 800.0, 700.0)));
+
+        let unbounded = carrier(2, 0.0, 0.0, &[]);
+        assert!(fits(&unbounded.inner, &parcel(1.0, 5000.0, 5000.0)));
+    }
+
+    #[test]
+    fn lists_the_cheapest_options_first() {
+        let carriers = [
+            carrier(1, 0.0, 0.0, &[(10.0, 1500)]),
+            carrier(2, 0.0, 0.0, &[(10.0, 900)]),
+            carrier(3, 500.0, 500.0, &[(10.0, 500)]),
+            carrier(4, 0.0, 0.0, &[(1.0, 300)]),
+        ];
+        let calendar = Calendar {
+            holidays: Default::default(),
+            cutoff_hour: 12,
+        };
+        // A Friday
+        let ship_date = NaiveDate::from_ymd_opt(2021, 10, 15).unwrap();
+        let options = shipping_options(&carriers, &parcel(2.0, 800.0, 400.0), &calendar, ship_date);
+        let ids: Vec<_> = options.iter().map(|o| o.carrier_id).collect();
+        assert_eq!(ids, vec![2, 1]);
+        assert_eq!(options[0].price, Money::from_cents(900));
+        assert_eq!(
+            options[0].delivery_date,
+            NaiveDate::from_ymd_opt(2021, 10, 19).unwrap()
+        );
+    }
+}
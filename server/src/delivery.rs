@@ -0,0 +1,155 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Weekday};
+use std::{collections::HashSet, env};
+
+// Models
+
+/// The working days of the workshop and of the carriers: every weekday but
+/// the holidays. Orders placed after `cutoff_hour` start the next business
+/// day.
+#[derive(Debug, Clone)]
+pub struct Calendar {
+    pub holidays: HashSet<NaiveDate>,
+    pub cutoff_hour: u32,
+}
+
+// Helpers
+
+pub fn default_cutoff_hour() -> u32 {
+    env::var("CUTOFF_HOUR")
+        .ok()
+        .and_then(|hour| hour.parse().ok())
+        .unwrap_or(12)
+}
+
+impl Calendar {
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(&date)
+    }
+
+    pub fn next_business_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut date = date + Duration::days(1);
+        while !self.is_business_day(date) {
+            date += Duration::days(1);
+        }
+        date
+    }
+
+    pub fn add_business_days(&self, date: NaiveDate, days: i64) -> NaiveDate {
+        (0..days).fold(date, |date, _| self.next_business_day(date))
+    }
+
+    /// The first business day the workshop works on an order placed at
+    /// `ordered_at`.
+    pub fn start_date(&self, ordered_at: NaiveDateTime) -> NaiveDate {
+        let date = ordered_at.date();
+        if ordered_at.hour() < self.cutoff_hour && self.is_business_day(date) {
+            date
+        } else {
+            self.next_business_day(date)
+        }
+    }
+
+    /// The day the order leaves the workshop, `manufacturing_time` business
+    /// days after it started.
+    pub fn ship_date(&self, ordered_at: NaiveDateTime, manufacturing_time: i16) -> NaiveDate {
+        self.add_business_days(
+            self.start_date(ordered_at),
+            manufacturing_time.max(0) as i64,
+        )
+    }
+
+    pub fn delivery_date(&self, ship_date: NaiveDate, transit_days: i16) -> NaiveDate {
+        self.add_business_days(ship_date, transit_days.max(0) as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn at(date: NaiveDate, hour: u32) -> NaiveDateTime {
+        date.and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn calendar(holidays: &[NaiveDate]) -> Calendar {
+        Calendar {
+            holidays: holidays.iter().copied().collect(),
+            cutoff_hour: 12,
+        }
+    }
+
+    #[test]
+    fn skips_weekends_and_holidays() {
+        let calendar = calendar(&[date(2021, 11, 11)]);
+        assert!(calendar.is_business_day(date(2021, 11, 10)));
+        assert!(!calendar.is_business_day(date(2021, 11, 11)));
+        assert!(!calendar.is_business_day(date(2021, 11, 13)));
+        assert!(!calendar.is_business_day(date(2021, 11, 14)));
+        assert_eq!(
+            calendar.next_business_day(date(2021, 11, 10)),
+            date(2021, 11, 12)
+        );
+        assert_eq!(
+            calendar.next_business_day(date(2021, 11, 12)),
+            date(2021, 11, 15)
+        );
+    }
+
+    #[test]
+    fn starts_the_same_day_before_the_cutoff() {
+        let calendar = calendar(&[]);
+        assert_eq!(
+            calendar.start_date(at(date(2021, 10, 13), 11)),
+            date(2021, 10, 13)
+        );
+        assert_eq!(
+            calendar.start_date(at(date(2021, 10, 13), 12)),
+            date(2021, 10, 14)
+        );
+    }
+
+    #[test]
+    fn starts_the_next_business_day_on_days_off() {
+        let calendar = calendar(&[date(2021, 11, 1)]);
+        assert_eq!(
+            calendar.start_date(at(date(2021, 10, 30), 9)),
+            date(2021, 11, 2)
+        );
+        assert_eq!(
+            calendar.start_date(at(date(2021, 10, 29), 15)),
+            date(2021, 11, 2)
+        );
+    }
+
+    #[test]
+    fn counts_manufacturing_time_in_business_days() {
+        let calendar = calendar(&[date(2021, 11, 11)]);
+        // Wednesday morning, 3 business days skipping Thursday 11th and the
+        // weekend.
+        assert_eq!(
+            calendar.ship_date(at(date(2021, 11, 10), 9), 3),
+            date(2021, 11, 16)
+        );
+        assert_eq!(
+            calendar.ship_date(at(date(2021, 11, 10), 9), 0),
+            date(2021, 11, 10)
+        );
+    }
+
+    #[test]
+    fn adds_transit_days() {
+        let calendar = calendar(&[date(2021, 12, 27)]);
+        assert_eq!(
+            calendar.delivery_date(date(2021, 12, 23), 2),
+            date(2021, 12, 28)
+        );
+        assert_eq!(
+            calendar.delivery_date(date(2021, 12, 23), 0),
+            date(2021, 12, 23)
+        );
+    }
+}
@@ -13,6 +13,7 @@ use std::ops::Deref;
 
 use crate::database;
 use crate::schema::dimensions;
+use crate::shared::auth::Admin;
 
 // Error management
 
@@ -95,6 +96,7 @@ async fn get(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
 #[put("/dimension")]
 async fn set(
     pool: web::Data<database::Pool>,
+    _: Admin,
     dimension: web::Json<Dimension>,
 ) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
@@ -131,6 +133,7 @@ async fn set(
 #[delete("/dimension/{id}")]
 async fn del(
     pool: web::Data<database::Pool>,
+    _: Admin,
     web::Path(id): web::Path<i32>,
 ) -> Result<HttpResponse> {
     use crate::schema::dimensions::dsl::dimensions;
@@ -13,6 +13,7 @@ use std::ops::Deref;
 
 use crate::database;
 use crate::schema::discounts;
+use crate::shared::auth::Admin;
 
 // Error management
 
@@ -26,6 +27,10 @@ error_chain! {
             description("Impossible de récupérer la liste des remises")
             display("Could not select discounts")
         }
+        SelectMaterialDiscountsErr(id: i32) {
+            description("Impossible de récupérer la liste des remises rattachées au matériau")
+            display("Could not select discounts of material `{}`", id)
+        }
         InsertDiscountErr(id: i32) {
             description("Impossible de créer la remise")
             display("Could not insert discount `{}`", id)
@@ -77,21 +82,58 @@ struct InsertableDiscount<'a> {
 
 // Helpers
 
-pub fn find_by_quantity(
-    conn: &database::PooledConnection,
-    quantity: i16,
-) -> Result<Option<Discount>> {
-    Ok(get_all(&conn)?
-        .iter()
-        .fold(None as Option<&Discount>, |matching_discount, curr_discount| {
+/// Picks the tier with the highest quantity threshold reached by `quantity`.
+pub fn find_tier(discounts: &[Discount], quantity: i16) -> Option<&Discount> {
+    discounts.iter().fold(
+        None as Option<&Discount>,
+        |matching_discount, curr_discount| {
             let matching_quantity = matching_discount.map(|d| d.quantity).unwrap_or_default();
             if curr_discount.quantity > matching_quantity && curr_discount.quantity <= quantity {
                 Some(curr_discount)
             } else {
                 matching_discount
             }
-        })
-        .cloned())
+        },
+    )
+}
+
+/// Returns the tiers attached to the material, or the global tiers (the ones
+/// attached to no material at all) when the material has none.
+pub fn get_by_material(
+    conn: &database::PooledConnection,
+    material_id: i32,
+) -> Result<Vec<Discount>> {
+    let material_discounts = {
+        use crate::schema::material_discounts::dsl;
+        discounts::table
+            .inner_join(dsl::material_discounts)
+            .filter(dsl::material_id.eq(material_id))
+            .select(discounts::all_columns)
+            .load::<Discount>(conn)
+    }
+    .chain_err(|| ErrorKind::SelectMaterialDiscountsErr(material_id))?;
+
+    if material_discounts.is_empty() {
+        get_global(conn)
+    } else {
+        Ok(material_discounts)
+    }
+}
+
+pub fn get_global(conn: &database::PooledConnection) -> Result<Vec<Discount>> {
+    let attached_ids = {
+        use crate::schema::material_discounts::dsl::*;
+        material_discounts
+            .select(discount_id)
+            .distinct()
+            .load::<i32>(conn)
+    }
+    .chain_err(|| ErrorKind::SelectDiscountsErr)?;
+
+    Ok(get_all(conn)?
+        .into_iter()
+        .filter(|d| !attached_ids.contains(&d.id))
+        .collect())
 }
 
 pub fn get_all(conn: &database::PooledConnection) -> Result<Vec<Discount>> {
@@ -104,16 +146,29 @@ pub fn get_all(conn: &database::PooledConnection) -> Result<Vec<Discount>> {
 
 // Services
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetDiscountQuery {
+    material_id: Option<i32>,
+}
+
 #[get("/discount")]
-async fn get(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
+async fn get(
+    pool: web::Data<database::Pool>,
+    query: web::Query<GetDiscountQuery>,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
-    let all_discounts = get_all(&conn)?;
-    Ok(HttpResponse::Ok().json(all_discounts))
+    let discounts = match query.material_id {
+        Some(material_id) => get_by_material(&conn, material_id)?,
+        None => get_all(&conn)?,
+    };
+    Ok(HttpResponse::Ok().json(discounts))
 }
 
 #[put("/discount")]
 async fn set(
     pool: web::Data<database::Pool>,
+    _: Admin,
     discount: web::Json<Discount>,
 ) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
@@ -148,6 +203,7 @@ async fn set(
 #[delete("/discount/{id}")]
 async fn del(
     pool: web::Data<database::Pool>,
+    _: Admin,
     web::Path(id): web::Path<i32>,
 ) -> Result<HttpResponse> {
     use crate::schema::discounts::dsl::discounts;
@@ -11,11 +11,13 @@ use log::error;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    app::{self, PublicApp},
     database,
     fixation_condition::{self, FixationCondition},
     order::Order,
     schema::fixations,
     shape::Shape,
+    shared::{auth::Admin, money::Money},
 };
 
 // Error management
@@ -50,8 +52,13 @@ error_chain! {
             description("Impossible de supprimer la fixation")
             display("Could not delete fixation `{}`", id)
         }
+        FixationNotInAppErr(id: i32) {
+            description("La fixation n'est pas disponible pour cette application")
+            display("Fixation `{}` is not enabled for app", id)
+        }
     }
     links {
+        App(app::Error, app::ErrorKind);
         FixationCondition(fixation_condition::Error, fixation_condition::ErrorKind);
     }
 }
@@ -65,7 +72,12 @@ impl actix_web::error::ResponseError for Error {
     }
 
     fn status_code(&self) -> StatusCode {
+        use fixation_condition::ErrorKind::*;
         match *self.kind() {
+            ErrorKind::FixationCondition(EmptyAreaRangeErr(_))
+            | ErrorKind::FixationCondition(OverlappingAreaRangesErr(_))
+            | ErrorKind::FixationCondition(GappedAreaRangesErr(..)) => StatusCode::BAD_REQUEST,
+            ErrorKind::FixationNotInAppErr(_) => StatusCode::NOT_FOUND,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -81,11 +93,36 @@ pub struct Fixation {
     pub preview_url: String,
     pub icon_url: String,
     pub video_url: Option<String>,
-    pub price: f32,
+    pub price: Money,
     pub diameter: f32,
     pub drill_diameter: f32,
 }
 
+/// A position on the edges of a design, e.g. `Tl` for top left or `Cr` for
+/// center right.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HolePosition {
+    Tl,
+    Tc,
+    Tr,
+    Cl,
+    Cr,
+    Bl,
+    Bc,
+    Br,
+}
+
+/// A hole to drill, in mm from the top left corner of the design.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrillHole {
+    pub position: HolePosition,
+    pub x: f32,
+    pub y: f32,
+    pub diameter: f32,
+}
+
 #[derive(Insertable)]
 #[table_name = "fixations"]
 struct InsertableFixation<'f> {
@@ -93,7 +130,7 @@ struct InsertableFixation<'f> {
     pub preview_url: &'f str,
     pub icon_url: &'f str,
     pub video_url: Option<&'f String>,
-    pub price: &'f f32,
+    pub price: &'f Money,
     pub diameter: &'f f32,
     pub drill_diameter: &'f f32,
 }
@@ -106,7 +143,7 @@ struct UpdatableFixation<'f> {
     pub preview_url: &'f str,
     pub icon_url: &'f str,
     pub video_url: Option<&'f String>,
-    pub price: &'f f32,
+    pub price: &'f Money,
     pub diameter: &'f f32,
     pub drill_diameter: &'f f32,
 }
@@ -127,7 +164,7 @@ pub struct SetFixationRequest {
     pub preview_url: String,
     pub icon_url: String,
     pub video_url: Option<String>,
-    pub price: f32,
+    pub price: Money,
     pub diameter: f32,
     pub drill_diameter: f32,
     #[serde(default)]
@@ -137,21 +174,34 @@ pub struct SetFixationRequest {
 // Services
 
 #[get("/fixation")]
-async fn get_all(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
+async fn get_all(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+) -> Result<HttpResponse> {
     use crate::schema::fixations::dsl::*;
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let fixation_ids = app::get_fixation_ids(&conn, app.id)?;
 
-    let all_fixations = web::block(move || fixations.load::<Fixation>(&conn))
-        .await
-        .chain_err(|| ErrorKind::SelectFixationsErr)?;
+    let all_fixations = web::block(move || {
+        fixations
+            .filter(id.eq_any(fixation_ids))
+            .load::<Fixation>(&conn)
+    })
+    .await
+    .chain_err(|| ErrorKind::SelectFixationsErr)?;
 
     Ok(HttpResponse::Ok().json(all_fixations))
 }
 
 #[get("/fixation/{id}")]
-async fn get(pool: web::Data<database::Pool>, fixation_id: web::Path<i32>) -> Result<HttpResponse> {
+async fn get(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+    fixation_id: web::Path<i32>,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let fixation_id = fixation_id.into_inner();
+    check_app_fixation(&conn, app.id, fixation_id)?;
 
     let fixation = if fixation_id == 0 {
         Fixation::default()
@@ -169,57 +219,47 @@ async fn get(pool: web::Data<database::Pool>, fixation_id: web::Path<i32>) -> Re
 #[get("/fixation/{id}/conditions")]
 async fn get_conditions(
     pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
     fixation_id: web::Path<i32>,
 ) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let fixation_id = fixation_id.into_inner();
+    check_app_fixation(&conn, app.id, fixation_id)?;
+    Ok(HttpResponse::Ok().json(get_conditions_json(&conn, fixation_id)?))
+}
 
-    let fixation = if fixation_id == 0 {
-        Fixation {
-            id: 0,
-            name: String::new(),
-            preview_url: String::new(),
-            icon_url: String::new(),
-            video_url: None,
-            price: 0.0,
-            diameter: 0.0,
-            drill_diameter: 0.0,
-        }
-    } else {
-        use crate::schema::fixations::dsl;
-        dsl::fixations
-            .filter(dsl::id.eq(fixation_id))
-            .first::<Fixation>(&conn)
-            .chain_err(|| ErrorKind::FindFixationErr(fixation_id))?
-    };
+#[get("/fixation")]
+async fn get_all_admin(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
+    use crate::schema::fixations::dsl::*;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
 
-    let conditions = if fixation_id == 0 {
-        Vec::new()
-    } else {
-        fixation_condition::get(&conn, fixation_id)?
-    };
+    let all_fixations = web::block(move || fixations.load::<Fixation>(&conn))
+        .await
+        .chain_err(|| ErrorKind::SelectFixationsErr)?;
 
-    let shapes = {
-        use crate::schema::shapes::dsl;
-        dsl::shapes.load::<Shape>(&conn)
-    }
-    .chain_err(|| ErrorKind::SelectShapeIdsErr)?;
+    Ok(HttpResponse::Ok().json(all_fixations))
+}
 
-    Ok(HttpResponse::Ok().json(GetFixationResponse {
-        fixation,
-        conditions,
-        shapes,
-    }))
+#[get("/fixation/{id}/conditions")]
+async fn get_conditions_admin(
+    pool: web::Data<database::Pool>,
+    fixation_id: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    Ok(HttpResponse::Ok().json(get_conditions_json(&conn, fixation_id.into_inner())?))
 }
 
 #[put("/fixation")]
 async fn set(
     pool: web::Data<database::Pool>,
+    _: Admin,
     fixation: web::Json<SetFixationRequest>,
 ) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let mut fixation_id = fixation.id;
 
+    fixation_condition::check_area_ranges(&fixation.conditions)?;
+
     if fixation_id == 0 {
         let new_fixation = InsertableFixation {
             name: &fixation.name,
@@ -264,6 +304,7 @@ async fn set(
 #[delete("/fixation/{id}")]
 async fn del(
     pool: web::Data<database::Pool>,
+    _: Admin,
     web::Path(id): web::Path<i32>,
 ) -> Result<HttpResponse> {
     use crate::schema::fixations::dsl::fixations;
@@ -281,11 +322,64 @@ pub fn pub_services(cfg: &mut web::ServiceConfig) {
 }
 
 pub fn priv_services(cfg: &mut web::ServiceConfig) {
-    cfg.service(set).service(del);
+    cfg.service(get_all_admin)
+        .service(get_conditions_admin)
+        .service(set)
+        .service(del);
 }
 
 // Helpers
 
+fn check_app_fixation(conn: &database::PooledConnection, app_id: i32, id: i32) -> Result<()> {
+    if id != 0 && !app::get_fixation_ids(conn, app_id)?.contains(&id) {
+        return Err(ErrorKind::FixationNotInAppErr(id).into());
+    }
+
+    Ok(())
+}
+
+fn get_conditions_json(
+    conn: &database::PooledConnection,
+    fixation_id: i32,
+) -> Result<GetFixationResponse> {
+    let fixation = if fixation_id == 0 {
+        Fixation {
+            id: 0,
+            name: String::new(),
+            preview_url: String::new(),
+            icon_url: String::new(),
+            video_url: None,
+            price: Money::default(),
+            diameter: 0.0,
+            drill_diameter: 0.0,
+        }
+    } else {
+        use crate::schema::fixations::dsl;
+        dsl::fixations
+            .filter(dsl::id.eq(fixation_id))
+            .first::<Fixation>(conn)
+            .chain_err(|| ErrorKind::FindFixationErr(fixation_id))?
+    };
+
+    let conditions = if fixation_id == 0 {
+        Vec::new()
+    } else {
+        fixation_condition::get(conn, fixation_id)?
+    };
+
+    let shapes = {
+        use crate::schema::shapes::dsl;
+        dsl::shapes.load::<Shape>(conn)
+    }
+    .chain_err(|| ErrorKind::SelectShapeIdsErr)?;
+
+    Ok(GetFixationResponse {
+        fixation,
+        conditions,
+        shapes,
+    })
+}
+
 pub fn find_by_id(conn: &database::PooledConnection, id: i32) -> Result<Option<Fixation>> {
     if id == 0 {
         return Ok(None);
@@ -300,98 +394,173 @@ pub fn find_by_id(conn: &database::PooledConnection, id: i32) -> Result<Option<F
     Ok(Some(fixation))
 }
 
-pub fn count_pos(cond: &FixationCondition) -> i32 {
-    let mut count = 0;
+/// Lists the positions enabled by the condition.
+pub fn positions(cond: &FixationCondition) -> Vec<HolePosition> {
+    vec![
+        (HolePosition::Tl, cond.pos_tl),
+        (HolePosition::Tc, cond.pos_tc),
+        (HolePosition::Tr, cond.pos_tr),
+        (HolePosition::Cl, cond.pos_cl),
+        (HolePosition::Cr, cond.pos_cr),
+        (HolePosition::Bl, cond.pos_bl),
+        (HolePosition::Bc, cond.pos_bc),
+        (HolePosition::Br, cond.pos_br),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| enabled.unwrap_or_default())
+    .map(|(pos, _)| pos)
+    .collect()
+}
 
-    if let Some(true) = cond.pos_tl {
-        count += 1
-    };
-    if let Some(true) = cond.pos_tc {
-        count += 1
-    };
-    if let Some(true) = cond.pos_tr {
-        count += 1
-    };
-    if let Some(true) = cond.pos_cl {
-        count += 1
-    };
-    if let Some(true) = cond.pos_cr {
-        count += 1
-    };
-    if let Some(true) = cond.pos_bl {
-        count += 1
-    };
-    if let Some(true) = cond.pos_bc {
-        count += 1
-    };
-    if let Some(true) = cond.pos_bc {
-        count += 1
-    };
+pub fn count_pos(cond: &FixationCondition) -> i32 {
+    positions(cond).len() as i32
+}
 
-    count
+/// Computes the center of the holes to drill for a design of `width` by
+/// `height` mm. Holes on an edge are inset by half the fixation diameter
+/// plus the condition padding, so that the fixation fits in the design.
+pub fn drill_holes(
+    fixation: &Fixation,
+    cond: &FixationCondition,
+    width: f32,
+    height: f32,
+) -> Vec<DrillHole> {
+    let inset_h = fixation.diameter / 2.0 + cond.padding_h.unwrap_or_default();
+    let inset_v = fixation.diameter / 2.0 + cond.padding_v.unwrap_or_default();
+    let (left, center, right) = (inset_h, width / 2.0, width - inset_h);
+    let (top, middle, bottom) = (inset_v, height / 2.0, height - inset_v);
+
+    positions(cond)
+        .into_iter()
+        .map(|position| {
+            let (x, y) = match position {
+                HolePosition::Tl => (left, top),
+                HolePosition::Tc => (center, top),
+                HolePosition::Tr => (right, top),
+                HolePosition::Cl => (left, middle),
+                HolePosition::Cr => (right, middle),
+                HolePosition::Bl => (left, bottom),
+                HolePosition::Bc => (center, bottom),
+                HolePosition::Br => (right, bottom),
+            };
+
+            DrillHole {
+                position,
+                x,
+                y,
+                diameter: fixation.drill_diameter,
+            }
+        })
+        .collect()
 }
 
-pub fn first_min_condition(
+/// Finds the condition matching the shape and the size of the order. See
+/// `fixation_condition::find_by_size` for the matching rule.
+pub fn find_condition(
     conn: &database::PooledConnection,
     order: &Order,
 ) -> Result<Option<FixationCondition>> {
-    if order.fixation_id == 0 {
+    if order.fixation_id == 0 || order.shape_id == 0 {
         return Ok(None);
     }
 
-    if order.shape_id == 0 {
-        return Ok(None);
+    let conditions = fixation_condition::get(conn, order.fixation_id)?;
+
+    Ok(
+        fixation_condition::find_by_size(&conditions, order.shape_id, order.width, order.height)
+            .cloned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixation() -> Fixation {
+        Fixation {
+            id: 1,
+            diameter: 20.0,
+            drill_diameter: 6.0,
+            ..Fixation::default()
+        }
     }
 
-    let condition = {
-        use crate::schema::fixation_conditions::dsl::{
-            area_min, fixation_conditions, fixation_id, shape_id,
-        };
-        fixation_conditions
-            .filter(fixation_id.eq(order.fixation_id))
-            .filter(shape_id.eq(order.shape_id))
-            .order(area_min)
-            .first::<FixationCondition>(conn)
-            .optional()
+    fn condition(positions: &[&str]) -> FixationCondition {
+        FixationCondition {
+            padding_h: Some(5.0),
+            padding_v: Some(2.0),
+            ..fixation_condition::with_positions(positions)
+        }
     }
-    .chain_err(|| ErrorKind::FindFixationErr(order.fixation_id))?;
 
-    Ok(condition)
-}
+    #[test]
+    fn counts_each_position_once() {
+        let all = ["tl", "tc", "tr", "cl", "cr", "bl", "bc", "br"];
+        assert_eq!(count_pos(&condition(&all)), 8);
+        assert_eq!(count_pos(&condition(&["bc"])), 1);
+        assert_eq!(count_pos(&condition(&["br"])), 1);
+        assert_eq!(count_pos(&condition(&[])), 0);
+    }
 
-pub(crate) fn find_condition_by_area(
-    conn: &database::PooledConnection,
-    order: &Order,
-    area: i32,
-) -> Result<Option<FixationCondition>> {
-    if order.fixation_id == 0 {
-        return Ok(None);
+    #[test]
+    fn ignores_unset_positions() {
+        let cond = FixationCondition {
+            pos_tl: None,
+            ..condition(&["tl", "tr"])
+        };
+        assert_eq!(positions(&cond), vec![HolePosition::Tr]);
     }
 
-    if order.shape_id == 0 {
-        return Ok(None);
+    #[test]
+    fn insets_corner_holes() {
+        let holes = drill_holes(&fixation(), &condition(&["tl", "br"]), 300.0, 200.0);
+        assert_eq!(
+            holes,
+            vec![
+                DrillHole {
+                    position: HolePosition::Tl,
+                    x: 15.0,
+                    y: 12.0,
+                    diameter: 6.0,
+                },
+                DrillHole {
+                    position: HolePosition::Br,
+                    x: 285.0,
+                    y: 188.0,
+                    diameter: 6.0,
+                },
+            ]
+        );
     }
 
-    let condition = {
-        use crate::schema::fixation_conditions::dsl::{
-            area_max, area_min, fixation_conditions, fixation_id, shape_id,
-        };
-        fixation_conditions
-            .filter(fixation_id.eq(order.fixation_id))
-            .filter(shape_id.eq(order.shape_id))
-            .filter(
-                area_min
-                    .eq(0)
-                    .and(area_max.eq(0))
-                    .or(area_min.eq(0).and(area_max.ge(area)))
-                    .or(area_min.gt(area).and(area_max.le(area)))
-                    .or(area_min.lt(area).and(area_max.eq(0))),
-            )
-            .order(area_min)
-            .first::<FixationCondition>(conn)
-            .optional()
+    #[test]
+    fn centers_middle_holes() {
+        let holes = drill_holes(
+            &fixation(),
+            &condition(&["tc", "cl", "cr", "bc"]),
+            300.0,
+            200.0,
+        );
+        let coords: Vec<_> = holes.iter().map(|h| (h.position, h.x, h.y)).collect();
+        assert_eq!(
+            coords,
+            vec![
+                (HolePosition::Tc, 150.0, 12.0),
+                (HolePosition::Cl, 15.0, 100.0),
+                (HolePosition::Cr, 285.0, 100.0),
+                (HolePosition::Bc, 150.0, 188.0),
+            ]
+        );
     }
-    .chain_err(|| ErrorKind::FindFixationErr(order.fixation_id))?;
 
-    Ok(condition)
+    #[test]
+    fn defaults_missing_padding_to_zero() {
+        let cond = FixationCondition {
+            padding_h: None,
+            padding_v: None,
+            ..condition(&["tl"])
+        };
+        let holes = drill_holes(&fixation(), &cond, 300.0, 200.0);
+        assert_eq!((holes[0].x, holes[0].y), (10.0, 10.0));
+    }
 }
@@ -5,6 +5,9 @@ use serde::{Deserialize, Serialize};
 use crate::database;
 use crate::schema::fixation_conditions;
 
+/// Width or height, in mm, up to which a design is matched by its shape only.
+const NARROW_DIMENSION: f32 = 10.0;
+
 // Error management
 
 error_chain! {
@@ -21,6 +24,18 @@ error_chain! {
             description("Impossible de supprimer les conditions associées à la fixation")
             display("Could not select fixation_conditions `{}`", id)
         }
+        EmptyAreaRangeErr(shape_id: i32) {
+            description("La superficie max. d'une condition doit être supérieure à sa superficie min.")
+            display("Empty area range for shape `{}`", shape_id)
+        }
+        OverlappingAreaRangesErr(shape_id: i32) {
+            description("Les superficies de plusieurs conditions d'une même forme se chevauchent")
+            display("Overlapping area ranges for shape `{}`", shape_id)
+        }
+        GappedAreaRangesErr(shape_id: i32, from: i32, to: i32) {
+            description("Les superficies des conditions d'une même forme doivent se suivre sans trou")
+            display("Gap between areas `{}` and `{}` for shape `{}`", from, to, shape_id)
+        }
     }
 }
 
@@ -48,6 +63,23 @@ pub struct FixationCondition {
     pub pos_br: Option<bool>,
 }
 
+impl FixationCondition {
+    /// The area range of the condition in mm², `area_min` included and
+    /// `area_max` excluded. A missing `area_min` starts at 0, a missing (or
+    /// zero) `area_max` has no upper bound.
+    pub fn area_range(&self) -> (i32, Option<i32>) {
+        (
+            self.area_min.unwrap_or_default(),
+            self.area_max.filter(|&max| max > 0),
+        )
+    }
+
+    pub fn matches_area(&self, area: f32) -> bool {
+        let (min, max) = self.area_range();
+        area >= min as f32 && max.is_none_or(|max| area < max as f32)
+    }
+}
+
 #[derive(Insertable)]
 #[table_name = "fixation_conditions"]
 struct InsertableFixationCondition<'a> {
@@ -67,6 +99,79 @@ struct InsertableFixationCondition<'a> {
     pub pos_br: Option<&'a bool>,
 }
 
+// Helpers
+
+/// Checks that, for each shape, the area ranges of the conditions follow each
+/// other without overlap nor gap, so that any area resolves to at most one
+/// condition. Areas below the first range or above the last one resolve to
+/// none.
+pub fn check_area_ranges(conditions: &[FixationCondition]) -> Result<()> {
+    let mut ranges: Vec<_> = conditions
+        .iter()
+        .map(|condition| (condition.shape_id, condition.area_range()))
+        .collect();
+    ranges.sort_by_key(|&(shape_id, (min, _))| (shape_id, min));
+
+    for &(shape_id, (min, max)) in ranges.iter() {
+        if max.is_some_and(|max| max <= min) {
+            return Err(ErrorKind::EmptyAreaRangeErr(shape_id).into());
+        }
+    }
+
+    for pair in ranges.windows(2) {
+        let (shape_id, (_, prev_max)) = pair[0];
+        let (next_shape_id, (next_min, _)) = pair[1];
+
+        if shape_id != next_shape_id {
+            continue;
+        }
+
+        match prev_max {
+            None => return Err(ErrorKind::OverlappingAreaRangesErr(shape_id).into()),
+            Some(prev_max) if prev_max > next_min => {
+                return Err(ErrorKind::OverlappingAreaRangesErr(shape_id).into())
+            }
+            Some(prev_max) if prev_max < next_min => {
+                return Err(ErrorKind::GappedAreaRangesErr(shape_id, prev_max, next_min).into())
+            }
+            Some(_) => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the condition of the shape whose area range contains `area` (in
+/// mm²). Ranges being checked on save, there is at most one.
+pub fn find_by_area(
+    conditions: &[FixationCondition],
+    shape_id: i32,
+    area: f32,
+) -> Option<&FixationCondition> {
+    conditions
+        .iter()
+        .find(|condition| condition.shape_id == shape_id && condition.matches_area(area))
+}
+
+/// Finds the condition of the shape for a design of `width` × `height` mm.
+/// Designs of `NARROW_DIMENSION` mm or less on a side take the condition of
+/// the smallest area of the shape, whatever their area.
+pub fn find_by_size(
+    conditions: &[FixationCondition],
+    shape_id: i32,
+    width: f32,
+    height: f32,
+) -> Option<&FixationCondition> {
+    if width <= NARROW_DIMENSION || height <= NARROW_DIMENSION {
+        conditions
+            .iter()
+            .filter(|condition| condition.shape_id == shape_id)
+            .min_by_key(|condition| condition.area_range().0)
+    } else {
+        find_by_area(conditions, shape_id, width * height)
+    }
+}
+
 // Services
 
 pub fn get(conn: &database::PooledConnection, fixation_id: i32) -> Result<Vec<FixationCondition>> {
@@ -123,3 +228,175 @@ pub fn del(conn: &database::PooledConnection, fixation_id: i32) -> Result<()> {
 
     Ok(())
 }
+
+/// Builds a condition of fixation 1 and shape 1 for the tests, with holes at
+/// the given positions (`"tl"`, `"tc"`, …) and no area range nor padding.
+#[cfg(test)]
+pub(crate) fn with_positions(positions: &[&str]) -> FixationCondition {
+    let has = |pos: &str| Some(positions.contains(&pos));
+    FixationCondition {
+        id: 1,
+        fixation_id: 1,
+        shape_id: 1,
+        area_min: None,
+        area_max: None,
+        padding_h: None,
+        padding_v: None,
+        pos_tl: has("tl"),
+        pos_tc: has("tc"),
+        pos_tr: has("tr"),
+        pos_cl: has("cl"),
+        pos_cr: has("cr"),
+        pos_bl: has("bl"),
+        pos_bc: has("bc"),
+        pos_br: has("br"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(shape_id: i32, area_min: Option<i32>, area_max: Option<i32>) -> FixationCondition {
+        FixationCondition {
+            shape_id,
+            area_min,
+            area_max,
+            ..with_positions(&[])
+        }
+    }
+
+    fn check_err(conditions: &[FixationCondition]) -> ErrorKind {
+        check_area_ranges(conditions).unwrap_err().0
+    }
+
+    #[test]
+    fn accepts_contiguous_ranges() {
+        let conditions = [
+            condition(1, Some(400), None),
+            condition(1, None, Some(100)),
+            condition(1, Some(100), Some(400)),
+            condition(2, Some(0), Some(0)),
+        ];
+        assert!(check_area_ranges(&conditions).is_ok());
+        assert!(check_area_ranges(&[]).is_ok());
+    }
+
+    #[test]
+    fn accepts_ranges_not_starting_at_zero() {
+        let conditions = [
+            condition(1, Some(100), Some(400)),
+            condition(1, Some(400), Some(900)),
+        ];
+        assert!(check_area_ranges(&conditions).is_ok());
+    }
+
+    #[test]
+    fn rejects_empty_ranges() {
+        let conditions = [condition(1, Some(400), Some(400))];
+        assert!(matches!(
+            check_err(&conditions),
+            ErrorKind::EmptyAreaRangeErr(1)
+        ));
+
+        let conditions = [condition(1, Some(400), Some(100))];
+        assert!(matches!(
+            check_err(&conditions),
+            ErrorKind::EmptyAreaRangeErr(1)
+        ));
+    }
+
+    #[test]
+    fn rejects_overlapping_ranges() {
+        let conditions = [
+            condition(1, None, Some(400)),
+            condition(1, Some(100), Some(900)),
+        ];
+        assert!(matches!(
+            check_err(&conditions),
+            ErrorKind::OverlappingAreaRangesErr(1)
+        ));
+    }
+
+    #[test]
+    fn rejects_ranges_following_an_unbounded_one() {
+        let conditions = [condition(2, None, None), condition(2, Some(100), Some(400))];
+        assert!(matches!(
+            check_err(&conditions),
+            ErrorKind::OverlappingAreaRangesErr(2)
+        ));
+    }
+
+    #[test]
+    fn rejects_duplicated_ranges() {
+        let conditions = [condition(1, None, None), condition(1, Some(0), Some(0))];
+        assert!(matches!(
+            check_err(&conditions),
+            ErrorKind::OverlappingAreaRangesErr(1)
+        ));
+    }
+
+    #[test]
+    fn rejects_gapped_ranges() {
+        let conditions = [condition(1, None, Some(100)), condition(1, Some(200), None)];
+        assert!(matches!(
+            check_err(&conditions),
+            ErrorKind::GappedAreaRangesErr(1, 100, 200)
+        ));
+    }
+
+    #[test]
+    fn includes_min_and_excludes_max() {
+        let conditions = [condition(1, None, Some(100)), condition(1, Some(100), None)];
+        let find = |area| find_by_area(&conditions, 1, area).and_then(|c| c.area_min);
+        assert_eq!(find(0.0), None);
+        assert_eq!(find(99.9), None);
+        assert_eq!(find(100.0), Some(100));
+        assert_eq!(find(1_000_000.0), Some(100));
+    }
+
+    #[test]
+    fn matches_sizes_in_mm_against_ranges_in_mm2() {
+        // As saved by the admin, from ranges of 0-1000 and 1000+ cm².
+        let conditions = [
+            condition(1, Some(0), Some(100_000)),
+            condition(1, Some(100_000), None),
+        ];
+        let find = |width, height| find_by_size(&conditions, 1, width, height).map(|c| c.area_min);
+        assert_eq!(find(300.0, 200.0), Some(Some(0)));
+        assert_eq!(find(500.0, 200.0), Some(Some(100_000)));
+        assert_eq!(find(1000.0, 800.0), Some(Some(100_000)));
+    }
+
+    #[test]
+    fn matches_narrow_sizes_with_the_smallest_range() {
+        let conditions = [
+            condition(1, Some(100_000), None),
+            condition(1, Some(20_000), Some(100_000)),
+            condition(2, Some(0), None),
+        ];
+        let find = |width, height| find_by_size(&conditions, 1, width, height).map(|c| c.area_min);
+        assert_eq!(find(10.0, 3000.0), Some(Some(20_000)));
+        assert_eq!(find(3000.0, 5.0), Some(Some(20_000)));
+        assert_eq!(find(100.0, 100.0), None);
+        assert!(find_by_size(&conditions, 3, 5.0, 5.0).is_none());
+    }
+
+    #[test]
+    fn matches_the_ordered_shape_only() {
+        let conditions = [condition(1, None, None), condition(2, None, None)];
+        assert_eq!(
+            find_by_area(&conditions, 2, 50.0).map(|c| c.shape_id),
+            Some(2)
+        );
+        assert!(find_by_area(&conditions, 3, 50.0).is_none());
+    }
+
+    #[test]
+    fn matches_nothing_outside_the_ranges() {
+        let conditions = [condition(1, Some(100), Some(400))];
+        assert!(find_by_area(&conditions, 1, 50.0).is_none());
+        assert!(find_by_area(&conditions, 1, 400.0).is_none());
+        assert!(find_by_area(&conditions, 1, 200.0).is_some());
+    }
+}
@@ -11,12 +11,17 @@ use log::error;
 use serde::{Deserialize, Serialize};
 use std::ops::Deref;
 
+use crate::app::{self, PublicApp};
 use crate::database;
 use crate::schema::fonts;
+use crate::shared::auth::Admin;
 
 // Error management
 
 error_chain! {
+    links {
+        App(app::Error, app::ErrorKind);
+    }
     errors {
         GetDbConnErr {
             description("Impossible de se connecter à la base de données")
@@ -87,8 +92,28 @@ async fn get(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
     Ok(HttpResponse::Ok().json(all_fonts))
 }
 
+#[get("/font")]
+async fn get_for_app(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+) -> Result<HttpResponse> {
+    use crate::schema::fonts::dsl::*;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let font_ids = app::get_font_ids(&conn, app.id)?;
+
+    let app_fonts = web::block(move || fonts.filter(id.eq_any(font_ids)).load::<Font>(&conn))
+        .await
+        .chain_err(|| ErrorKind::SelectFontsErr)?;
+
+    Ok(HttpResponse::Ok().json(app_fonts))
+}
+
 #[put("/font")]
-async fn set(pool: web::Data<database::Pool>, font: web::Json<Font>) -> Result<HttpResponse> {
+async fn set(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    font: web::Json<Font>,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let font_id = font.id;
 
@@ -121,6 +146,7 @@ async fn set(pool: web::Data<database::Pool>, font: web::Json<Font>) -> Result<H
 #[delete("/font/{id}")]
 async fn del(
     pool: web::Data<database::Pool>,
+    _: Admin,
     web::Path(id): web::Path<i32>,
 ) -> Result<HttpResponse> {
     use crate::schema::fonts::dsl::fonts;
@@ -133,6 +159,10 @@ async fn del(
     Ok(HttpResponse::NoContent().finish())
 }
 
+pub fn pub_services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_for_app);
+}
+
 pub fn services(cfg: &mut web::ServiceConfig) {
     cfg.service(get).service(set).service(del);
 }
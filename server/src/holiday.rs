@@ -0,0 +1,160 @@
+use actix_web::{
+    self, delete,
+    dev::HttpResponseBuilder,
+    get,
+    http::{header, StatusCode},
+    put, web, HttpResponse,
+};
+use chrono::{Local, NaiveDate};
+use diesel::prelude::*;
+use error_chain::error_chain;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::database;
+use crate::delivery::{self, Calendar};
+use crate::schema::holidays;
+use crate::shared::auth::Admin;
+
+// Error management
+
+error_chain! {
+    errors {
+        GetDbConnErr {
+            description("Impossible de se connecter à la base de données")
+            display("Could not get db conn from pool")
+        }
+        SelectHolidaysErr {
+            description("Impossible de récupérer la liste des jours fériés")
+            display("Could not select holidays")
+        }
+        InsertHolidayErr(date: NaiveDate) {
+            description("Impossible de créer le jour férié")
+            display("Could not insert holiday `{}`", date)
+        }
+        UpdateHolidayErr(id: i32) {
+            description("Impossible de modifier le jour férié")
+            display("Could not update holiday `{}`", id)
+        }
+        DeleteHolidayErr(id: i32) {
+            description("Impossible de supprimer le jour férié")
+            display("Could not delete holiday `{}`", id)
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        HttpResponseBuilder::new(self.status_code())
+            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.description().to_owned())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+// Models
+
+/// A day neither the workshop nor the carriers work.
+#[derive(Debug, Clone, Identifiable, Queryable, AsChangeset, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Holiday {
+    pub id: i32,
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "holidays"]
+struct InsertableHoliday<'a> {
+    pub date: &'a NaiveDate,
+    pub name: &'a str,
+}
+
+// Helpers
+
+/// Builds the calendar from the holidays to come.
+pub fn get_calendar(conn: &database::PooledConnection) -> Result<Calendar> {
+    use crate::schema::holidays::dsl;
+
+    let holidays = dsl::holidays
+        .select(dsl::date)
+        .filter(dsl::date.ge(Local::now().naive_local().date()))
+        .load::<NaiveDate>(conn)
+        .chain_err(|| ErrorKind::SelectHolidaysErr)?;
+
+    Ok(Calendar {
+        holidays: holidays.into_iter().collect(),
+        cutoff_hour: delivery::default_cutoff_hour(),
+    })
+}
+
+// Services
+
+#[get("/holiday")]
+async fn get(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
+    use crate::schema::holidays::dsl;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    let all_holidays = web::block(move || dsl::holidays.order(dsl::date).load::<Holiday>(&conn))
+        .await
+        .chain_err(|| ErrorKind::SelectHolidaysErr)?;
+
+    Ok(HttpResponse::Ok().json(all_holidays))
+}
+
+#[put("/holiday")]
+async fn set(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    holiday: web::Json<Holiday>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let holiday = holiday.into_inner();
+    let holiday_id = holiday.id;
+    let holiday_date = holiday.date;
+
+    if holiday_id == 0 {
+        web::block(move || {
+            let new_holiday = InsertableHoliday {
+                date: &holiday.date,
+                name: &holiday.name,
+            };
+
+            diesel::insert_into(holidays::table)
+                .values(&new_holiday)
+                .execute(&conn)
+        })
+        .await
+        .chain_err(|| ErrorKind::InsertHolidayErr(holiday_date))
+    } else {
+        web::block(move || diesel::update(&holiday).set(&holiday).execute(&conn))
+            .await
+            .chain_err(|| ErrorKind::UpdateHolidayErr(holiday_id))
+    }?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[delete("/holiday/{id}")]
+async fn del(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    web::Path(id): web::Path<i32>,
+) -> Result<HttpResponse> {
+    use crate::schema::holidays::dsl::holidays;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    web::block(move || diesel::delete(holidays.find(id)).execute(&conn))
+        .await
+        .chain_err(|| ErrorKind::DeleteHolidayErr(id))?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get).service(set).service(del);
+}
@@ -1,3 +1,5 @@
+#![recursion_limit = "256"]
+
 #[macro_use]
 extern crate diesel;
 
@@ -10,22 +12,39 @@ use actix_web_httpauth::middleware::HttpAuthentication;
 use env_logger;
 use std::{env, io};
 
+mod api_key;
 mod app;
+mod app_settings;
+mod audit;
 mod badge;
+mod carrier;
 mod database;
+mod delivery;
 mod dimension;
 mod discount;
 mod fixation;
 mod fixation_condition;
 mod font;
+mod holiday;
+mod markup;
 mod material;
 mod order;
+mod order_item;
+mod password_reset;
 mod picto;
+mod pricing;
+mod quote;
+mod quote_event;
+mod refresh_token;
 mod schema;
 mod shape;
 mod shared;
+mod shipping_bracket;
+mod sign_in_throttle;
+mod tax_rate;
 mod template;
 mod user;
+mod webhook;
 
 #[actix_web::main]
 async fn main() -> io::Result<()> {
@@ -33,48 +52,58 @@ async fn main() -> io::Result<()> {
     env_logger::init();
     shared::upload::init()?;
 
+    let pool = database::get_conn_pool().expect("Could not initialize database pool");
+    let allowed_origins =
+        shared::cors::AllowedOrigins::load(&pool.get().expect("Could not get db conn from pool"))
+            .expect("Could not load allowed origins");
+    let mailer = web::Data::from(shared::mailer::from_env().expect("Could not initialize mailer"));
+    actix_web::rt::spawn(webhook::run_worker(pool.clone()));
+
     let mut server = HttpServer::new(move || {
         let cors = if cfg!(debug_assertions) {
             Cors::permissive()
         } else {
+            let allowed_origins = allowed_origins.clone();
             Cors::default()
                 .allow_any_header()
                 .allow_any_method()
-                .allowed_origin_fn(|orig, _| {
-                    orig.as_bytes().ends_with(b"admin.pictosigns.io")
-                        || orig.as_bytes().ends_with(b"picto-test.com")
-                        || orig.as_bytes().ends_with(b"pictosigns.com")
-                })
+                .allowed_origin_fn(move |orig, _| allowed_origins.is_allowed(orig.as_bytes()))
                 .supports_credentials()
         };
 
         App::new()
             .wrap(NormalizePath::new(TrailingSlash::Trim))
             .wrap(Logger::default())
-            .data(database::get_conn_pool().expect("Could not initialize database pool"))
+            .data(pool.clone())
+            .data(allowed_origins.clone())
+            .app_data(mailer.clone())
             .configure(shared::json::payload_error_management)
             .service(
                 web::scope("/public")
                     .wrap(Cors::permissive())
+                    .service(web::scope("/app/{app_key}").configure(catalog_services))
                     .configure(user::sign_in_service)
-                    .configure(picto::pub_services)
-                    .configure(material::pub_services)
-                    .configure(badge::pub_services)
-                    .configure(fixation::pub_services)
-                    .configure(shape::pub_services)
-                    .configure(discount::pub_services)
-                    .configure(template::pub_services)
-                    .configure(dimension::pub_services)
-                    .configure(order::services)
+                    .configure(password_reset::pub_services)
+                    .configure(catalog_services)
                     .configure(shared::upload::pub_services),
             )
+            .service(
+                web::scope("/api")
+                    .wrap(HttpAuthentication::bearer(shared::auth::bearer_validator))
+                    .configure(catalog_services),
+            )
             .service(
                 web::scope("/")
+                    .wrap(audit::Audit)
                     .wrap(HttpAuthentication::bearer(shared::auth::bearer_validator))
                     .wrap(cors)
                     .configure(shared::auth::services)
                     .configure(user::services)
                     .configure(app::services)
+                    .configure(api_key::services)
+                    .configure(webhook::services)
+                    .configure(app_settings::priv_services)
+                    .configure(markup::services)
                     .configure(discount::priv_services)
                     .configure(font::services)
                     .configure(material::priv_services)
@@ -84,6 +113,12 @@ async fn main() -> io::Result<()> {
                     .configure(shape::priv_services)
                     .configure(template::priv_services)
                     .configure(fixation::priv_services)
+                    .configure(order::priv_services)
+                    .configure(quote_event::services)
+                    .configure(audit::services)
+                    .configure(tax_rate::services)
+                    .configure(carrier::services)
+                    .configure(holiday::services)
                     .configure(shared::folder::services)
                     .configure(shared::upload::priv_services),
             )
@@ -104,3 +139,20 @@ async fn main() -> io::Result<()> {
         .run()
         .await
 }
+
+/// Services scoped to the calling app, see `app::resolve`. They are public,
+/// and also exposed under `/api` to the API keys.
+fn catalog_services(cfg: &mut web::ServiceConfig) {
+    app_settings::pub_services(cfg);
+    picto::pub_services(cfg);
+    material::pub_services(cfg);
+    badge::pub_services(cfg);
+    fixation::pub_services(cfg);
+    shape::pub_services(cfg);
+    discount::pub_services(cfg);
+    template::pub_services(cfg);
+    dimension::pub_services(cfg);
+    font::pub_services(cfg);
+    order::services(cfg);
+    quote::services(cfg);
+}
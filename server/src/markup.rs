@@ -0,0 +1,274 @@
+use actix_web::{
+    self,
+    dev::HttpResponseBuilder,
+    get,
+    http::{header, StatusCode},
+    put, web, HttpResponse,
+};
+use diesel::prelude::*;
+use error_chain::error_chain;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::app::App;
+use crate::database;
+use crate::fixation::Fixation;
+use crate::material::Material;
+use crate::pricing::Markup;
+use crate::schema::{app_fixation_markups, app_material_markups, apps};
+use crate::shared::auth::{self, Claims};
+
+// Error management
+
+error_chain! {
+    links {
+        Auth(auth::Error, auth::ErrorKind);
+    }
+    errors {
+        GetDbConnErr {
+            description("Impossible de se connecter à la base de données")
+            display("Could not get db conn from pool")
+        }
+        FindAppErr(id: i32) {
+            description("Impossible de récupérer l'application")
+            display("Could not find app `{}`", id)
+        }
+        SelectMarkupsErr(id: i32) {
+            description("Impossible de récupérer les marges de l'application")
+            display("Could not select markups of app `{}`", id)
+        }
+        UpdateMarkupsErr(id: i32) {
+            description("Impossible de modifier les marges de l'application")
+            display("Could not update markups of app `{}`", id)
+        }
+        InvalidMarkupErr(markup: String) {
+            description("Une marge doit être un pourcentage positif")
+            display("Invalid markup `{}`", markup)
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        HttpResponseBuilder::new(self.status_code())
+            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.description().to_owned())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match *self.kind() {
+            ErrorKind::InvalidMarkupErr(_) => StatusCode::BAD_REQUEST,
+            ErrorKind::Auth(ref kind) => auth::status_code(kind),
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+// Models
+
+/// Overrides the app markup for a material, in percent.
+#[derive(
+    Debug, Clone, Identifiable, Queryable, Insertable, Associations, Serialize, Deserialize,
+)]
+#[primary_key(app_id, material_id)]
+#[belongs_to(App)]
+#[belongs_to(Material)]
+#[serde(rename_all = "camelCase")]
+pub struct AppMaterialMarkup {
+    #[serde(default)]
+    pub app_id: i32,
+    pub material_id: i32,
+    pub markup: f32,
+}
+
+/// Overrides the app markup for a fixation, in percent.
+#[derive(
+    Debug, Clone, Identifiable, Queryable, Insertable, Associations, Serialize, Deserialize,
+)]
+#[primary_key(app_id, fixation_id)]
+#[belongs_to(App)]
+#[belongs_to(Fixation)]
+#[serde(rename_all = "camelCase")]
+pub struct AppFixationMarkup {
+    #[serde(default)]
+    pub app_id: i32,
+    pub fixation_id: i32,
+    pub markup: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkupsJson {
+    pub markup: f32,
+    #[serde(default)]
+    pub materials: Vec<AppMaterialMarkup>,
+    #[serde(default)]
+    pub fixations: Vec<AppFixationMarkup>,
+}
+
+// Helpers
+
+pub fn check(markups: &MarkupsJson) -> Result<()> {
+    let invalid = std::iter::once(markups.markup)
+        .chain(markups.materials.iter().map(|m| m.markup))
+        .chain(markups.fixations.iter().map(|f| f.markup))
+        .find(|markup| !markup.is_finite() || *markup < 0.0);
+
+    match invalid {
+        Some(markup) => Err(ErrorKind::InvalidMarkupErr(markup.to_string()).into()),
+        None => Ok(()),
+    }
+}
+
+/// Finds the markups the app applies on a material and a fixation, falling
+/// back on the app markup when not overridden.
+pub fn find(
+    conn: &database::PooledConnection,
+    app: &App,
+    material_id: i32,
+    fixation_id: i32,
+) -> Result<Markup> {
+    let material = app_material_markups::table
+        .find((app.id, material_id))
+        .select(app_material_markups::markup)
+        .first::<f32>(conn)
+        .optional()
+        .chain_err(|| ErrorKind::SelectMarkupsErr(app.id))?;
+
+    let fixation = app_fixation_markups::table
+        .find((app.id, fixation_id))
+        .select(app_fixation_markups::markup)
+        .first::<f32>(conn)
+        .optional()
+        .chain_err(|| ErrorKind::SelectMarkupsErr(app.id))?;
+
+    Ok(Markup {
+        material: material.unwrap_or(app.markup),
+        fixation: fixation.unwrap_or(app.markup),
+    })
+}
+
+// Services
+
+#[get("/app/{id}/markups")]
+async fn get(
+    pool: web::Data<database::Pool>,
+    claims: Claims,
+    web::Path(app_id): web::Path<i32>,
+) -> Result<HttpResponse> {
+    claims.check_app(app_id)?;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    let markup = apps::table
+        .find(app_id)
+        .select(apps::markup)
+        .first::<f32>(&conn)
+        .chain_err(|| ErrorKind::FindAppErr(app_id))?;
+
+    let materials = app_material_markups::table
+        .filter(app_material_markups::app_id.eq(app_id))
+        .load::<AppMaterialMarkup>(&conn)
+        .chain_err(|| ErrorKind::SelectMarkupsErr(app_id))?;
+
+    let fixations = app_fixation_markups::table
+        .filter(app_fixation_markups::app_id.eq(app_id))
+        .load::<AppFixationMarkup>(&conn)
+        .chain_err(|| ErrorKind::SelectMarkupsErr(app_id))?;
+
+    Ok(HttpResponse::Ok().json(MarkupsJson {
+        markup,
+        materials,
+        fixations,
+    }))
+}
+
+#[put("/app/{id}/markups")]
+async fn set(
+    pool: web::Data<database::Pool>,
+    claims: Claims,
+    web::Path(app_id): web::Path<i32>,
+    markups: web::Json<MarkupsJson>,
+) -> Result<HttpResponse> {
+    claims.check_app(app_id)?;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let markups = markups.into_inner();
+    check(&markups)?;
+    let markup = markups.markup;
+
+    let materials: Vec<AppMaterialMarkup> = markups
+        .materials
+        .into_iter()
+        .map(|markup| AppMaterialMarkup { app_id, ..markup })
+        .collect();
+
+    let fixations: Vec<AppFixationMarkup> = markups
+        .fixations
+        .into_iter()
+        .map(|markup| AppFixationMarkup { app_id, ..markup })
+        .collect();
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::update(apps::table.find(app_id))
+            .set(apps::markup.eq(markup))
+            .execute(&conn)?;
+
+        diesel::delete(app_material_markups::table)
+            .filter(app_material_markups::app_id.eq(app_id))
+            .execute(&conn)?;
+
+        diesel::insert_into(app_material_markups::table)
+            .values(&materials)
+            .execute(&conn)?;
+
+        diesel::delete(app_fixation_markups::table)
+            .filter(app_fixation_markups::app_id.eq(app_id))
+            .execute(&conn)?;
+
+        diesel::insert_into(app_fixation_markups::table)
+            .values(&fixations)
+            .execute(&conn)?;
+
+        Ok(())
+    })
+    .chain_err(|| ErrorKind::UpdateMarkupsErr(app_id))?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get).service(set);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markups(markup: f32, material: f32, fixation: f32) -> MarkupsJson {
+        MarkupsJson {
+            markup,
+            materials: vec![AppMaterialMarkup {
+                app_id: 1,
+                material_id: 1,
+                markup: material,
+            }],
+            fixations: vec![AppFixationMarkup {
+                app_id: 1,
+                fixation_id: 1,
+                markup: fixation,
+            }],
+        }
+    }
+
+    #[test]
+    fn accepts_positive_markups() {
+        assert!(check(&markups(0.0, 12.5, 100.0)).is_ok());
+    }
+
+    #[test]
+    fn refuses_negative_markups() {
+        assert!(check(&markups(-1.0, 0.0, 0.0)).is_err());
+        assert!(check(&markups(10.0, -5.0, 0.0)).is_err());
+        assert!(check(&markups(10.0, 0.0, f32::NAN)).is_err());
+    }
+}
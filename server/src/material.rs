@@ -9,7 +9,9 @@ use diesel::prelude::*;
 use error_chain::error_chain;
 use log::error;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
+use crate::app::{self, PublicApp};
 use crate::badge::Badge;
 use crate::database;
 use crate::dimension::Dimension;
@@ -20,10 +22,17 @@ use crate::schema::{
     materials,
 };
 use crate::shape::Shape;
+use crate::shared::auth::{self, Admin, Claims};
+use crate::shared::money::Money;
+use crate::webhook::{self, Event};
 
 // Error management
 
 error_chain! {
+    links {
+        App(app::Error, app::ErrorKind);
+        Auth(auth::Error, auth::ErrorKind);
+    }
     errors {
         GetDbConnErr {
             description("Impossible de se connecter à la base de données")
@@ -33,6 +42,10 @@ error_chain! {
             description("Impossible de récupérer le produit")
             display("Could not find product `{}`", id)
         }
+        MaterialNotInAppErr(id: i32) {
+            description("Le produit n'est pas disponible pour cette application")
+            display("Material `{}` is not enabled for app", id)
+        }
         SelectMaterialsErr {
             description("Impossible de récupérer la liste des matériaux")
             display("Could not select materials")
@@ -45,6 +58,10 @@ error_chain! {
             description("Impossible de modifier le matériau")
             display("Could not update material `{}`", id)
         }
+        SelectMaterialAppsErr(id: i32) {
+            description("Impossible de récupérer les applications du produit")
+            display("Could not select apps of material `{}`", id)
+        }
         DeleteMaterialErr(id: i32) {
             description("Impossible de supprimer le matériau")
             display("Could not delete material `{}`", id)
@@ -53,62 +70,22 @@ error_chain! {
             description("Impossible de récupérer la liste des dimensions rattachées aux matériaux")
             display("Could not select material_dimensions")
         }
-        InsertMaterialDimensionsErr(id: i32) {
-            description("Impossible de rattacher les dimensions au matériau")
-            display("Could not insert material_dimensions `{}`", id)
-        }
-        DeleteMaterialDimensionsErr(id: i32) {
-            description("Impossible de détacher les dimensions du matériau")
-            display("Could not delete material_dimensions `{}`", id)
-        }
         SelectMaterialBadgesErr {
             description("Impossible de récupérer la liste des badges rattachés aux matériaux")
             display("Could not select material_badges")
         }
-        InsertMaterialBadgesErr(id: i32) {
-            description("Impossible de rattacher les badges au matériau")
-            display("Could not insert material_badges `{}`", id)
-        }
-        DeleteMaterialBadgesErr(id: i32) {
-            description("Impossible de détacher les badges du matériau")
-            display("Could not delete material_badges `{}`", id)
-        }
         SelectMaterialDiscountsErr {
             description("Impossible de récupérer la liste des remises rattachées aux matériaux")
             display("Could not select material_discounts")
         }
-        InsertMaterialDiscountsErr(id: i32) {
-            description("Impossible de rattacher les remises au matériau")
-            display("Could not insert material_discounts `{}`", id)
-        }
-        DeleteMaterialDiscountsErr(id: i32) {
-            description("Impossible de détacher les remises du matériau")
-            display("Could not delete material_discounts `{}`", id)
-        }
         SelectMaterialFixationsErr {
             description("Impossible de récupérer la liste des fixations rattachées aux matériaux")
             display("Could not select material_fixations")
         }
-        InsertMaterialFixationsErr(id: i32) {
-            description("Impossible de rattacher les fixations au matériau")
-            display("Could not insert material_fixations `{}`", id)
-        }
-        DeleteMaterialFixationsErr(id: i32) {
-            description("Impossible de détacher les fixations du matériau")
-            display("Could not delete material_fixations `{}`", id)
-        }
         SelectMaterialShapesErr {
             description("Impossible de récupérer la liste des formes rattachées aux matériaux")
             display("Could not select material_shapes")
         }
-        InsertMaterialShapesErr(id: i32) {
-            description("Impossible de rattacher les formes au matériau")
-            display("Could not insert material_shapes `{}`", id)
-        }
-        DeleteMaterialShapesErr(id: i32) {
-            description("Impossible de détacher les formes du matériau")
-            display("Could not delete material_shapes `{}`", id)
-        }
     }
 }
 
@@ -122,6 +99,8 @@ impl actix_web::error::ResponseError for Error {
 
     fn status_code(&self) -> StatusCode {
         match *self.kind() {
+            ErrorKind::MaterialNotInAppErr(_) => StatusCode::NOT_FOUND,
+            ErrorKind::Auth(ref kind) => auth::status_code(kind),
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -144,8 +123,8 @@ pub struct Material {
     pub max_width: f32,
     pub max_height: f32,
     pub weight: f32,
-    pub fixed_price: f32,
-    pub surface_price: f32,
+    pub fixed_price: Money,
+    pub surface_price: Money,
     pub manufacturing_time: i16,
     pub more: Option<String>,
     pub transparency: i32,
@@ -163,8 +142,8 @@ struct InsertableMaterial<'a> {
     pub max_width: &'a f32,
     pub max_height: &'a f32,
     pub weight: &'a f32,
-    pub fixed_price: &'a f32,
-    pub surface_price: &'a f32,
+    pub fixed_price: &'a Money,
+    pub surface_price: &'a Money,
     pub manufacturing_time: &'a i16,
     pub more: Option<&'a String>,
     pub transparency: &'a i32,
@@ -183,8 +162,8 @@ struct UpdatableMaterial<'a> {
     pub max_width: &'a f32,
     pub max_height: &'a f32,
     pub weight: &'a f32,
-    pub fixed_price: &'a f32,
-    pub surface_price: &'a f32,
+    pub fixed_price: &'a Money,
+    pub surface_price: &'a Money,
     pub manufacturing_time: &'a i16,
     pub more: Option<&'a String>,
     pub transparency: &'a i32,
@@ -252,41 +231,48 @@ pub struct MaterialBadge {
     pub badge_id: i32,
 }
 
-// Services
-
-#[get("/material")]
-async fn get_all(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
-    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+// Helpers
 
+/// Loads the materials with their associations, restricted to `ids` when
+/// given.
+fn get_all_json(
+    conn: &database::PooledConnection,
+    ids: Option<&[i32]>,
+) -> Result<Vec<MaterialJson>> {
     let materials = {
-        use crate::schema::materials::dsl::materials;
-        use crate::schema::materials::dsl::title;
-        materials.order(title).load::<Material>(&conn)
+        use crate::schema::materials::dsl;
+        match ids {
+            Some(ids) => dsl::materials
+                .filter(dsl::id.eq_any(ids))
+                .order(dsl::title)
+                .load::<Material>(conn),
+            None => dsl::materials.order(dsl::title).load::<Material>(conn),
+        }
     }
     .chain_err(|| ErrorKind::SelectMaterialsErr)?;
 
     let material_fixations = MaterialFixation::belonging_to(&materials)
-        .load::<MaterialFixation>(&conn)
+        .load::<MaterialFixation>(conn)
         .chain_err(|| ErrorKind::SelectMaterialFixationsErr)?
         .grouped_by(&materials);
 
     let material_shapes = MaterialShape::belonging_to(&materials)
-        .load::<MaterialShape>(&conn)
+        .load::<MaterialShape>(conn)
         .chain_err(|| ErrorKind::SelectMaterialShapesErr)?
         .grouped_by(&materials);
 
     let material_dimensions = MaterialDimension::belonging_to(&materials)
-        .load::<MaterialDimension>(&conn)
+        .load::<MaterialDimension>(conn)
         .chain_err(|| ErrorKind::SelectMaterialDimensionsErr)?
         .grouped_by(&materials);
 
     let material_discounts = MaterialDiscount::belonging_to(&materials)
-        .load::<MaterialDiscount>(&conn)
+        .load::<MaterialDiscount>(conn)
         .chain_err(|| ErrorKind::SelectMaterialDiscountsErr)?
         .grouped_by(&materials);
 
     let material_badges = MaterialBadge::belonging_to(&materials)
-        .load::<MaterialBadge>(&conn)
+        .load::<MaterialBadge>(conn)
         .chain_err(|| ErrorKind::SelectMaterialBadgesErr)?
         .grouped_by(&materials);
 
@@ -309,198 +295,253 @@ async fn get_all(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
         )
         .collect();
 
+    Ok(output)
+}
+
+fn get_json(conn: &database::PooledConnection, product_id: i32) -> Result<MaterialJson> {
+    if product_id == 0 {
+        return Ok(MaterialJson::default());
+    }
+
+    use crate::schema::materials::dsl;
+    let inner = dsl::materials
+        .filter(dsl::id.eq(product_id))
+        .first::<Material>(conn)
+        .chain_err(|| ErrorKind::FindMaterialErr(product_id))?;
+
+    Ok(MaterialJson {
+        inner,
+        ..MaterialJson::default()
+    })
+}
+
+fn check_claims(
+    conn: &database::PooledConnection,
+    claims: &Claims,
+    material_id: i32,
+) -> Result<()> {
+    let material_ids = app::get_claims_material_ids(conn, claims)?;
+    let allowed = material_ids.is_none_or(|ids| ids.contains(&material_id));
+    claims.check(allowed)?;
+    Ok(())
+}
+
+// Services
+
+#[get("/material")]
+async fn get_all(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let material_ids = app::get_material_ids(&conn, app.id)?;
+    let output = get_all_json(&conn, Some(&material_ids))?;
     Ok(HttpResponse::Ok().json(output))
 }
 
 #[get("/material/{id}")]
-async fn get(pool: web::Data<database::Pool>, product_id: web::Path<i32>) -> Result<HttpResponse> {
+async fn get(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+    product_id: web::Path<i32>,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let product_id = product_id.into_inner();
 
-    let product = if product_id == 0 {
-        MaterialJson::default()
-    } else {
-        use crate::schema::materials::dsl;
-        let inner = dsl::materials
-            .filter(dsl::id.eq(product_id))
-            .first::<Material>(&conn)
-            .chain_err(|| ErrorKind::FindMaterialErr(product_id))?;
-        MaterialJson {
-            inner,
-            ..MaterialJson::default()
-        }
-    };
+    if product_id != 0 && !app::has_material(&conn, app.id, product_id)? {
+        return Err(ErrorKind::MaterialNotInAppErr(product_id).into());
+    }
 
-    Ok(HttpResponse::Ok().json(product))
+    Ok(HttpResponse::Ok().json(get_json(&conn, product_id)?))
+}
+
+#[get("/material")]
+async fn get_all_admin(pool: web::Data<database::Pool>, claims: Claims) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let material_ids = app::get_claims_material_ids(&conn, &claims)?;
+    let output = get_all_json(&conn, material_ids.as_deref())?;
+    Ok(HttpResponse::Ok().json(output))
+}
+
+#[get("/material/{id}")]
+async fn get_admin(
+    pool: web::Data<database::Pool>,
+    claims: Claims,
+    product_id: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let product_id = product_id.into_inner();
+
+    if product_id != 0 {
+        check_claims(&conn, &claims, product_id)?;
+    }
+
+    Ok(HttpResponse::Ok().json(get_json(&conn, product_id)?))
 }
 
 #[put("/material")]
 async fn set(
     pool: web::Data<database::Pool>,
+    claims: Claims,
     material: web::Json<MaterialJson>,
 ) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let material = material.into_inner();
     let material_id = material.inner.id;
-    let material_id = if material_id == 0 {
-        let new_material = InsertableMaterial {
-            title: &material.inner.title,
-            description: &material.inner.description,
-            more: material.inner.more.as_ref(),
-            preview: &material.inner.preview,
-            background: &material.inner.background,
-            min_width: &material.inner.min_width,
-            max_width: &material.inner.max_width,
-            min_height: &material.inner.min_height,
-            max_height: &material.inner.max_height,
-            weight: &material.inner.weight,
-            fixed_price: &material.inner.fixed_price,
-            surface_price: &material.inner.surface_price,
-            manufacturing_time: &material.inner.manufacturing_time,
-            transparency: &material.inner.transparency,
-        };
 
-        diesel::insert_into(materials::table)
-            .values(&new_material)
-            .get_result::<Material>(&conn)
-            .chain_err(|| ErrorKind::InsertMaterialErr(material_id))?
-            .id
-    } else {
-        {
-            use crate::schema::material_dimensions::dsl;
-            diesel::delete(material_dimensions::table)
-                .filter(dsl::material_id.eq(material_id))
-                .execute(&conn)
-        }
-        .chain_err(|| ErrorKind::DeleteMaterialDimensionsErr(material_id))?;
+    // Materials are shared between apps: members can only edit the ones
+    // of their apps, and only admins can create new ones.
+    match material_id {
+        0 => claims.check_admin()?,
+        id => check_claims(&conn, &claims, id)?,
+    }
 
-        {
-            use crate::schema::material_discounts::dsl;
+    // The associations are replaced as a whole, and the event only queued
+    // with the change.
+    let app_ids = get_app_ids(&conn, material_id)?;
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        let material_id = if material_id == 0 {
+            let new_material = InsertableMaterial {
+                title: &material.inner.title,
+                description: &material.inner.description,
+                more: material.inner.more.as_ref(),
+                preview: &material.inner.preview,
+                background: &material.inner.background,
+                min_width: &material.inner.min_width,
+                max_width: &material.inner.max_width,
+                min_height: &material.inner.min_height,
+                max_height: &material.inner.max_height,
+                weight: &material.inner.weight,
+                fixed_price: &material.inner.fixed_price,
+                surface_price: &material.inner.surface_price,
+                manufacturing_time: &material.inner.manufacturing_time,
+                transparency: &material.inner.transparency,
+            };
+
+            diesel::insert_into(materials::table)
+                .values(&new_material)
+                .get_result::<Material>(&conn)?
+                .id
+        } else {
+            diesel::delete(material_dimensions::table)
+                .filter(material_dimensions::material_id.eq(material_id))
+                .execute(&conn)?;
             diesel::delete(material_discounts::table)
-                .filter(dsl::material_id.eq(material_id))
-                .execute(&conn)
-        }
-        .chain_err(|| ErrorKind::DeleteMaterialDiscountsErr(material_id))?;
-
-        {
-            use crate::schema::material_fixations::dsl;
+                .filter(material_discounts::material_id.eq(material_id))
+                .execute(&conn)?;
             diesel::delete(material_fixations::table)
-                .filter(dsl::material_id.eq(material_id))
-                .execute(&conn)
-        }
-        .chain_err(|| ErrorKind::DeleteMaterialFixationsErr(material_id))?;
-
-        {
-            use crate::schema::material_shapes::dsl;
+                .filter(material_fixations::material_id.eq(material_id))
+                .execute(&conn)?;
             diesel::delete(material_shapes::table)
-                .filter(dsl::material_id.eq(material_id))
-                .execute(&conn)
-        }
-        .chain_err(|| ErrorKind::DeleteMaterialShapesErr(material_id))?;
-
-        {
-            use crate::schema::material_badges::dsl;
+                .filter(material_shapes::material_id.eq(material_id))
+                .execute(&conn)?;
             diesel::delete(material_badges::table)
-                .filter(dsl::material_id.eq(material_id))
-                .execute(&conn)
-        }
-        .chain_err(|| ErrorKind::DeleteMaterialBadgesErr(material_id))?;
-
-        let next_material = UpdatableMaterial {
-            id: &material.inner.id,
-            title: &material.inner.title,
-            description: &material.inner.description,
-            more: material.inner.more.as_ref(),
-            preview: &material.inner.preview,
-            background: &material.inner.background,
-            min_width: &material.inner.min_width,
-            max_width: &material.inner.max_width,
-            min_height: &material.inner.min_height,
-            max_height: &material.inner.max_height,
-            weight: &material.inner.weight,
-            fixed_price: &material.inner.fixed_price,
-            surface_price: &material.inner.surface_price,
-            manufacturing_time: &material.inner.manufacturing_time,
-            transparency: &material.inner.transparency,
+                .filter(material_badges::material_id.eq(material_id))
+                .execute(&conn)?;
+
+            let next_material = UpdatableMaterial {
+                id: &material.inner.id,
+                title: &material.inner.title,
+                description: &material.inner.description,
+                more: material.inner.more.as_ref(),
+                preview: &material.inner.preview,
+                background: &material.inner.background,
+                min_width: &material.inner.min_width,
+                max_width: &material.inner.max_width,
+                min_height: &material.inner.min_height,
+                max_height: &material.inner.max_height,
+                weight: &material.inner.weight,
+                fixed_price: &material.inner.fixed_price,
+                surface_price: &material.inner.surface_price,
+                manufacturing_time: &material.inner.manufacturing_time,
+                transparency: &material.inner.transparency,
+            };
+
+            diesel::update(&next_material)
+                .set(&next_material)
+                .execute(&conn)?;
+
+            material_id
         };
 
-        diesel::update(&next_material)
-            .set(&next_material)
-            .execute(&conn)
-            .chain_err(|| ErrorKind::UpdateMaterialErr(material_id))?;
-
-        material_id
-    };
-
-    let new_material_dimensions = material
-        .dimension_ids
-        .into_iter()
-        .map(|dimension_id| MaterialDimension {
-            material_id,
-            dimension_id,
-        })
-        .collect::<Vec<_>>();
-
-    diesel::insert_into(material_dimensions::table)
-        .values(&new_material_dimensions)
-        .execute(&conn)
-        .chain_err(|| ErrorKind::InsertMaterialDimensionsErr(material_id))?;
-
-    let new_material_discounts = material
-        .discount_ids
-        .into_iter()
-        .map(|discount_id| MaterialDiscount {
-            material_id,
-            discount_id,
-        })
-        .collect::<Vec<_>>();
-
-    diesel::insert_into(material_discounts::table)
-        .values(&new_material_discounts)
-        .execute(&conn)
-        .chain_err(|| ErrorKind::InsertMaterialDiscountsErr(material_id))?;
-
-    let new_material_fixations = material
-        .fixation_ids
-        .into_iter()
-        .map(|fixation_id| MaterialFixation {
-            material_id,
-            fixation_id,
-        })
-        .collect::<Vec<_>>();
-
-    diesel::insert_into(material_fixations::table)
-        .values(&new_material_fixations)
-        .execute(&conn)
-        .chain_err(|| ErrorKind::InsertMaterialFixationsErr(material_id))?;
-
-    let new_material_shapes = material
-        .shape_ids
-        .into_iter()
-        .map(|shape_id| MaterialShape {
-            material_id,
-            shape_id,
-        })
-        .collect::<Vec<_>>();
-
-    diesel::insert_into(material_shapes::table)
-        .values(&new_material_shapes)
-        .execute(&conn)
-        .chain_err(|| ErrorKind::InsertMaterialShapesErr(material_id))?;
-
-    let new_material_badges = material
-        .badge_ids
-        .into_iter()
-        .map(|badge_id| MaterialBadge {
-            material_id,
-            badge_id,
-        })
-        .collect::<Vec<_>>();
-
-    diesel::insert_into(material_badges::table)
-        .values(&new_material_badges)
-        .execute(&conn)
-        .chain_err(|| ErrorKind::InsertMaterialBadgesErr(material_id))?;
+        let new_material_dimensions = material
+            .dimension_ids
+            .iter()
+            .map(|&dimension_id| MaterialDimension {
+                material_id,
+                dimension_id,
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(material_dimensions::table)
+            .values(&new_material_dimensions)
+            .execute(&conn)?;
+
+        let new_material_discounts = material
+            .discount_ids
+            .iter()
+            .map(|&discount_id| MaterialDiscount {
+                material_id,
+                discount_id,
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(material_discounts::table)
+            .values(&new_material_discounts)
+            .execute(&conn)?;
+
+        let new_material_fixations = material
+            .fixation_ids
+            .iter()
+            .map(|&fixation_id| MaterialFixation {
+                material_id,
+                fixation_id,
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(material_fixations::table)
+            .values(&new_material_fixations)
+            .execute(&conn)?;
+
+        let new_material_shapes = material
+            .shape_ids
+            .iter()
+            .map(|&shape_id| MaterialShape {
+                material_id,
+                shape_id,
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(material_shapes::table)
+            .values(&new_material_shapes)
+            .execute(&conn)?;
+
+        let new_material_badges = material
+            .badge_ids
+            .iter()
+            .map(|&badge_id| MaterialBadge {
+                material_id,
+                badge_id,
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(material_badges::table)
+            .values(&new_material_badges)
+            .execute(&conn)?;
+
+        // Only the id: prices differ by app, which get them marked up from
+        // the catalog.
+        webhook::enqueue(
+            &conn,
+            &app_ids,
+            Event::MaterialUpdated,
+            &json!({ "id": material_id }),
+        )
+    })
+    .chain_err(|| match material_id {
+        0 => ErrorKind::InsertMaterialErr(material_id),
+        id => ErrorKind::UpdateMaterialErr(id),
+    })?;
 
     Ok(HttpResponse::NoContent().finish())
 }
@@ -508,14 +549,23 @@ async fn set(
 #[delete("/material/{id}")]
 async fn del(
     pool: web::Data<database::Pool>,
+    _: Admin,
     web::Path(id): web::Path<i32>,
 ) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    // The app links are removed along with the material.
+    let app_ids = get_app_ids(&conn, id)?;
 
-    {
+    conn.transaction::<_, diesel::result::Error, _>(|| {
         use crate::schema::materials::dsl::materials;
-        diesel::delete(materials.find(id)).execute(&conn)
-    }
+        diesel::delete(materials.find(id)).execute(&conn)?;
+        webhook::enqueue(
+            &conn,
+            &app_ids,
+            Event::MaterialDeleted,
+            &json!({ "id": id }),
+        )
+    })
     .chain_err(|| ErrorKind::DeleteMaterialErr(id))?;
 
     Ok(HttpResponse::NoContent().finish())
@@ -526,7 +576,10 @@ pub fn pub_services(cfg: &mut web::ServiceConfig) {
 }
 
 pub fn priv_services(cfg: &mut web::ServiceConfig) {
-    cfg.service(set).service(del);
+    cfg.service(get_all_admin)
+        .service(get_admin)
+        .service(set)
+        .service(del);
 }
 
 // Helpers
@@ -544,3 +597,47 @@ pub fn find_by_id(conn: &database::PooledConnection, id: i32) -> Result<Option<M
 
     Ok(Some(material))
 }
+
+/// Gets the ids of the apps selling the material.
+pub fn get_app_ids(conn: &database::PooledConnection, material_id: i32) -> Result<Vec<i32>> {
+    use crate::schema::app_materials::dsl;
+    dsl::app_materials
+        .filter(dsl::material_id.eq(material_id))
+        .select(dsl::app_id)
+        .load::<i32>(conn)
+        .chain_err(|| ErrorKind::SelectMaterialAppsErr(material_id))
+}
+
+pub fn has_fixation(
+    conn: &database::PooledConnection,
+    material_id: i32,
+    fixation_id: i32,
+) -> Result<bool> {
+    let material_fixation = {
+        use crate::schema::material_fixations::dsl::material_fixations;
+        material_fixations
+            .find((material_id, fixation_id))
+            .first::<MaterialFixation>(conn)
+            .optional()
+    }
+    .chain_err(|| ErrorKind::SelectMaterialFixationsErr)?;
+
+    Ok(material_fixation.is_some())
+}
+
+pub fn has_shape(
+    conn: &database::PooledConnection,
+    material_id: i32,
+    shape_id: i32,
+) -> Result<bool> {
+    let material_shape = {
+        use crate::schema::material_shapes::dsl::material_shapes;
+        material_shapes
+            .find((material_id, shape_id))
+            .first::<MaterialShape>(conn)
+            .optional()
+    }
+    .chain_err(|| ErrorKind::SelectMaterialShapesErr)?;
+
+    Ok(material_shape.is_some())
+}
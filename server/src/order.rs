@@ -6,26 +6,47 @@ use actix_web::{
     dev::HttpResponseBuilder,
     get,
     http::{header, StatusCode},
-    web, HttpResponse,
+    put, web, HttpResponse,
 };
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
 use error_chain::error_chain;
 use log::error;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 use crate::{
+    app::{self, App, PublicApp},
     database, discount,
-    fixation::{self, Fixation},
+    fixation::{self, DrillHole, Fixation},
     fixation_condition::FixationCondition,
-    material,
+    holiday, markup, material,
+    order_item::{self, InsertableOrderItem, OrderItem},
+    pricing::{self, Catalog, Pricing},
+    quote_event,
+    schema::{order_items, orders},
+    shared::{
+        auth::{self, Claims},
+        json,
+        money::Money,
+    },
+    tax_rate,
+    webhook::{self, Event},
 };
 
 // Error management
 
 error_chain! {
     links {
+        App(app::Error, app::ErrorKind);
         Product(material::Error, material::ErrorKind);
         Fixation(fixation::Error, fixation::ErrorKind);
         Discount(discount::Error, discount::ErrorKind);
+        TaxRate(tax_rate::Error, tax_rate::ErrorKind);
+        Holiday(holiday::Error, holiday::ErrorKind);
+        Markup(markup::Error, markup::ErrorKind);
+        OrderItem(order_item::Error, order_item::ErrorKind);
+        Auth(auth::Error, auth::ErrorKind);
     }
     errors {
         GetDbConnErr {
@@ -33,27 +54,76 @@ error_chain! {
             display("Could not get db conn from pool")
         }
         SelectOrdersErr {
-            description("Impossible de récupérer la liste des matériaux")
+            description("Impossible de récupérer la liste des commandes")
             display("Could not select orders")
         }
+        FindOrderErr(id: i32) {
+            description("Impossible de récupérer la commande")
+            display("Could not find order `{}`", id)
+        }
+        EmptyOrderErr {
+            description("La commande ne contient aucun article")
+            display("Could not create order without items")
+        }
+        InvalidOrderErr(violations: Vec<Violation>) {
+            description("La commande est invalide")
+            display("Invalid order: {} violation(s)", violations.len())
+        }
+        MissingOrderMaterialErr(idx: usize) {
+            description("Un article de la commande n'a pas de matériau")
+            display("Could not find material of order item `{}`", idx)
+        }
+        SerializeOrderSnapshotErr {
+            description("Impossible d'enregistrer le détail de la commande")
+            display("Could not serialize order snapshot")
+        }
+        InsertOrderErr {
+            description("Impossible de créer la commande")
+            display("Could not insert order")
+        }
+        UpdateOrderStatusErr(id: i32) {
+            description("Impossible de modifier le statut de la commande")
+            display("Could not update status of order `{}`", id)
+        }
     }
 }
 
 impl actix_web::error::ResponseError for Error {
     fn error_response(&self) -> HttpResponse {
         error!("{}", self.to_string());
-        HttpResponseBuilder::new(self.status_code())
-            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
-            .body(self.description().to_owned())
+        match *self.kind() {
+            ErrorKind::InvalidOrderErr(ref violations) => violations_response(violations),
+            _ => HttpResponseBuilder::new(self.status_code())
+                .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(self.description().to_owned()),
+        }
     }
 
     fn status_code(&self) -> StatusCode {
-        match *self.kind() {
-            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        status_code(self.kind())
+    }
+}
+
+pub fn status_code(kind: &ErrorKind) -> StatusCode {
+    match *kind {
+        ErrorKind::EmptyOrderErr => StatusCode::BAD_REQUEST,
+        ErrorKind::InvalidOrderErr(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        ErrorKind::MissingOrderMaterialErr(_) => StatusCode::BAD_REQUEST,
+        ErrorKind::TaxRate(tax_rate::ErrorKind::FindTaxRateErr(_)) => {
+            StatusCode::UNPROCESSABLE_ENTITY
         }
+        ErrorKind::Auth(ref kind) => auth::status_code(kind),
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
     }
 }
 
+pub fn violations_response(violations: &[Violation]) -> HttpResponse {
+    HttpResponse::UnprocessableEntity().json(json!({
+        "message": ErrorKind::InvalidOrderErr(vec![]).description(),
+        "violations": violations,
+    }))
+}
+
 // Models
 
 #[derive(Debug, Deserialize)]
@@ -71,73 +141,571 @@ pub struct Order {
     pub width: f32,
     #[serde(default)]
     pub height: f32,
+    #[serde(default)]
+    pub country: Option<String>,
+}
+
+/// Describes why an order cannot be manufactured. `line` is set when the
+/// order is part of a cart.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Violation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(field: &'static str, message: String) -> Self {
+        Self {
+            line: None,
+            field,
+            message,
+        }
+    }
 }
 
 #[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderPrice {
-    pub weight: f32,
-    pub discount: i16,
-    pub total_tax_excl: f32,
-    pub total_tax_incl: f32,
-    pub unit_price_tax_excl_discounted: f32,
-    pub total_tax_excl_discounted: f32,
-    pub total_tax_incl_discounted: f32,
+    #[serde(flatten)]
+    pub pricing: Pricing,
     pub product: Material,
     pub fixation: Option<Fixation>,
     pub condition: Option<FixationCondition>,
+    pub holes: Vec<DrillHole>,
+    /// The day the order would leave the workshop if placed now.
+    pub ship_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Identifiable, Queryable, Serialize)]
+#[table_name = "orders"]
+#[serde(rename_all = "camelCase")]
+pub struct OrderRecord {
+    pub id: i32,
+    pub status: String,
+    pub total_tax_excl: Money,
+    pub total_tax_incl: Money,
+    pub created_at: NaiveDateTime,
+    pub app_id: Option<i32>,
 }
 
-// Services
+#[derive(Insertable)]
+#[table_name = "orders"]
+struct InsertableOrder<'a> {
+    pub app_id: &'a i32,
+    pub total_tax_excl: &'a Money,
+    pub total_tax_incl: &'a Money,
+}
 
-#[get("/order")]
-async fn get(pool: web::Data<database::Pool>, order: web::Query<Order>) -> Result<HttpResponse> {
-    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
-    let area = order.width * order.height;
-    let fixation = fixation::find_by_id(&conn, order.fixation_id)?;
-    let discount = discount::find_by_quantity(&conn, order.quantity)?;
-    let discount_percent = discount.map(|d| d.amount).unwrap_or_default();
-    let discount_factor = ((100 - discount_percent) as f32) / 100.0;
-    let condition = if order.fixation_id == 0 {
-        None
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    Pending,
+    Paid,
+    InProduction,
+    Shipped,
+    Delivered,
+    Cancelled,
+}
+
+impl OrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Pending => "pending",
+            OrderStatus::Paid => "paid",
+            OrderStatus::InProduction => "in_production",
+            OrderStatus::Shipped => "shipped",
+            OrderStatus::Delivered => "delivered",
+            OrderStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderStatusJson {
+    pub status: OrderStatus,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderJson {
+    #[serde(flatten)]
+    pub inner: OrderRecord,
+    pub items: Vec<OrderItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrderItemRequest {
+    #[serde(flatten)]
+    pub order: Order,
+    pub svg: String,
+    pub config: String,
+    pub preview: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrderRequest {
+    pub items: Vec<CreateOrderItemRequest>,
+}
+
+// Helpers
+
+fn check_range(
+    violations: &mut Vec<Violation>,
+    field: &'static str,
+    label: &str,
+    value: f32,
+    min: f32,
+    max: f32,
+) {
+    if !value.is_finite() || value <= 0.0 {
+        violations.push(Violation::new(
+            field,
+            format!("{} doit être un nombre positif", label),
+        ));
+    } else if value < min || value > max {
+        violations.push(Violation::new(
+            field,
+            format!("{} doit être comprise entre {} et {} mm", label, min, max),
+        ));
+    }
+}
+
+/// Checks that the order can be manufactured with the given material: the
+/// material must be sold by the app, the dimensions must fit the material
+/// bounds, and the shape and the fixation must be linked to the material.
+pub fn validate(
+    conn: &database::PooledConnection,
+    app: &App,
+    order: &Order,
+    product: &Material,
+) -> Result<Vec<Violation>> {
+    let mut violations = Vec::new();
+
+    if !app::has_material(conn, app.id, product.id)? {
+        violations.push(Violation::new(
+            "materialId",
+            String::from("Le matériau n'est pas disponible pour cette application"),
+        ));
+    }
+
+    if order.quantity < 1 {
+        violations.push(Violation::new(
+            "quantity",
+            String::from("La quantité doit être d'au moins 1"),
+        ));
+    }
+
+    check_range(
+        &mut violations,
+        "width",
+        "La longueur",
+        order.width,
+        product.min_width,
+        product.max_width,
+    );
+    check_range(
+        &mut violations,
+        "height",
+        "La hauteur",
+        order.height,
+        product.min_height,
+        product.max_height,
+    );
+
+    if order.shape_id != 0 && !material::has_shape(conn, product.id, order.shape_id)? {
+        violations.push(Violation::new(
+            "shapeId",
+            String::from("La forme n'est pas disponible pour ce matériau"),
+        ));
+    }
+
+    if order.fixation_id != 0 && !material::has_fixation(conn, product.id, order.fixation_id)? {
+        violations.push(Violation::new(
+            "fixationId",
+            String::from("La fixation n'est pas disponible pour ce matériau"),
+        ));
+    }
+
+    Ok(violations)
+}
+
+/// Prices several lines at once. The violations of all the lines are
+/// gathered instead of stopping at the first invalid line.
+pub fn compute_prices<'a, I>(
+    conn: &database::PooledConnection,
+    app: &App,
+    lines: I,
+) -> Result<Vec<OrderPrice>>
+where
+    I: IntoIterator<Item = (&'a Order, i16)>,
+{
+    let mut prices = Vec::new();
+    let mut violations = Vec::new();
+
+    for (idx, (order, discount_quantity)) in lines.into_iter().enumerate() {
+        match compute_price(conn, app, order, discount_quantity) {
+            Ok(price) => prices.push(price),
+            Err(Error(ErrorKind::InvalidOrderErr(line_violations), _)) => {
+                violations.extend(line_violations.into_iter().map(|violation| Violation {
+                    line: Some(idx),
+                    ..violation
+                }))
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(prices)
     } else {
-        if order.width <= 10.0 || order.height <= 10.0 {
-            fixation::first_min_condition(&conn, order.deref())?
-        } else {
-            fixation::find_condition_by_area(&conn, order.deref(), area as i32)?
+        Err(ErrorKind::InvalidOrderErr(violations).into())
+    }
+}
+
+/// Prices a single line. The discount tier is picked with `discount_quantity`,
+/// which is the line quantity unless discounts are computed across a cart.
+pub fn compute_price(
+    conn: &database::PooledConnection,
+    app: &App,
+    order: &Order,
+    discount_quantity: i16,
+) -> Result<OrderPrice> {
+    let product = match material::find_by_id(conn, order.material_id)? {
+        Some(product) => product,
+        None => {
+            return Err(ErrorKind::InvalidOrderErr(vec![Violation::new(
+                "materialId",
+                String::from("Le matériau n'existe pas"),
+            )])
+            .into())
         }
     };
-    let fixations_price = (condition
-        .as_ref()
-        .map(fixation::count_pos)
-        .unwrap_or_default() as f32)
-        * fixation.as_ref().map(|f| f.price).unwrap_or_default();
-    if let Some(product) = material::find_by_id(&conn, order.material_id)? {
-        let weight = product.weight * (order.width as f32) * (order.height as f32) * 0.00001;
-        let area_price = (area as f32) * 0.000001 * product.surface_price;
-        let unit_price_tax_excl = product.fixed_price + area_price + fixations_price;
-        let total_tax_excl = (order.quantity as f32) * unit_price_tax_excl;
-        let total_tax_incl = total_tax_excl * 1.2;
-        let unit_price_tax_excl_discounted = unit_price_tax_excl * discount_factor;
-        let total_tax_excl_discounted = total_tax_excl * discount_factor;
-        let total_tax_incl_discounted = total_tax_incl * discount_factor;
-        Ok(HttpResponse::Ok().json(OrderPrice {
-            weight,
-            discount: discount_percent,
-            total_tax_excl,
-            total_tax_incl,
-            unit_price_tax_excl_discounted,
-            total_tax_excl_discounted,
-            total_tax_incl_discounted,
-            product,
-            fixation,
-            condition,
-        }))
-    } else {
-        Ok(HttpResponse::Ok().json(OrderPrice::default()))
+
+    let violations = validate(conn, app, order, &product)?;
+    if !violations.is_empty() {
+        return Err(ErrorKind::InvalidOrderErr(violations).into());
+    }
+
+    let fixation = fixation::find_by_id(conn, order.fixation_id)?;
+    let condition = fixation::find_condition(conn, order)?;
+    if fixation.is_some() && order.shape_id != 0 && condition.is_none() {
+        return Err(ErrorKind::InvalidOrderErr(vec![Violation::new(
+            "fixationId",
+            String::from("La fixation n'est pas disponible pour cette forme à cette taille"),
+        )])
+        .into());
+    }
+    let discounts = discount::get_by_material(conn, product.id)?;
+    let country = order
+        .country
+        .clone()
+        .unwrap_or_else(tax_rate::default_country);
+    let tax_rate = tax_rate::find(conn, Some(app.id), product.id, &country)?.rate;
+    let markup = markup::find(conn, app, product.id, order.fixation_id)?;
+
+    let pricing = pricing::compute(
+        order,
+        &Catalog {
+            material: &product,
+            fixation: fixation.as_ref(),
+            condition: condition.as_ref(),
+            discounts: &discounts,
+            tax_rate,
+            markup,
+        },
+        discount_quantity,
+    )
+    .ok_or_else(|| {
+        ErrorKind::InvalidOrderErr(vec![Violation::new(
+            "quantity",
+            String::from("Le montant de la commande est trop élevé"),
+        )])
+    })?;
+
+    let holes = match (&fixation, &condition) {
+        (Some(fixation), Some(condition)) => {
+            fixation::drill_holes(fixation, condition, order.width, order.height)
+        }
+        _ => Vec::new(),
+    };
+
+    let ship_date = holiday::get_calendar(conn)?
+        .ship_date(Local::now().naive_local(), product.manufacturing_time);
+
+    Ok(OrderPrice {
+        pricing,
+        product,
+        fixation,
+        condition,
+        holes,
+        ship_date: Some(ship_date),
+    })
+}
+
+// Public services
+
+#[get("/order")]
+async fn get(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+    order: web::Query<Order>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let price = compute_price(&conn, &app, order.deref(), order.quantity)?;
+    quote_event::record(
+        &conn,
+        &app,
+        quote_event::Source::Order,
+        &[(order.deref(), &price)],
+    );
+
+    Ok(HttpResponse::Ok().json(price))
+}
+
+async fn create(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+    req: web::Json<CreateOrderRequest>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let req = req.into_inner();
+
+    if req.items.is_empty() {
+        return Err(ErrorKind::EmptyOrderErr.into());
+    }
+
+    for (idx, item) in req.items.iter().enumerate() {
+        if item.order.material_id == 0 {
+            return Err(ErrorKind::MissingOrderMaterialErr(idx).into());
+        }
     }
+
+    let prices = compute_prices(
+        &conn,
+        &app,
+        req.items
+            .iter()
+            .map(|item| (&item.order, item.order.quantity)),
+    )?;
+
+    let total_tax_excl: Money = prices
+        .iter()
+        .map(|p| p.pricing.total_tax_excl_discounted)
+        .sum();
+    let total_tax_incl: Money = prices
+        .iter()
+        .map(|p| p.pricing.total_tax_incl_discounted)
+        .sum();
+
+    let snapshots = prices
+        .iter()
+        .map(|p| {
+            Ok((
+                serde_json::to_value(&p.product)?,
+                p.fixation.as_ref().map(serde_json::to_value).transpose()?,
+                p.condition.as_ref().map(serde_json::to_value).transpose()?,
+                serde_json::to_value(&p.holes)?,
+            ))
+        })
+        .collect::<serde_json::Result<Vec<_>>>()
+        .chain_err(|| ErrorKind::SerializeOrderSnapshotErr)?;
+
+    let order = conn
+        .transaction::<_, diesel::result::Error, _>(|| {
+            let new_order = InsertableOrder {
+                app_id: &app.id,
+                total_tax_excl: &total_tax_excl,
+                total_tax_incl: &total_tax_incl,
+            };
+
+            let order = diesel::insert_into(orders::table)
+                .values(&new_order)
+                .get_result::<OrderRecord>(&conn)?;
+
+            let new_items = req
+                .items
+                .iter()
+                .zip(prices.iter())
+                .zip(snapshots)
+                .map(|((item, price), (material, fixation, condition, holes))| {
+                    InsertableOrderItem {
+                        order_id: order.id,
+                        material_id: Some(&price.product.id),
+                        fixation_id: price.fixation.as_ref().map(|f| &f.id),
+                        shape_id: Some(&item.order.shape_id).filter(|&&id| id != 0),
+                        quantity: &item.order.quantity,
+                        width: &item.order.width,
+                        height: &item.order.height,
+                        weight: &price.pricing.weight,
+                        discount: &price.pricing.discount,
+                        tax_rate: &price.pricing.tax_rate,
+                        unit_price_tax_excl: &price.pricing.unit_price_tax_excl_discounted,
+                        total_tax_excl: &price.pricing.total_tax_excl_discounted,
+                        total_tax_incl: &price.pricing.total_tax_incl_discounted,
+                        material,
+                        fixation,
+                        condition,
+                        svg: &item.svg,
+                        config: &item.config,
+                        preview: &item.preview,
+                        holes,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            diesel::insert_into(order_items::table)
+                .values(&new_items)
+                .execute(&conn)?;
+
+            webhook::enqueue(&conn, &[app.id], Event::OrderCreated, &json!(order))?;
+
+            Ok(order)
+        })
+        .chain_err(|| ErrorKind::InsertOrderErr)?;
+
+    Ok(HttpResponse::Created().json(order))
 }
 
 pub fn services(cfg: &mut web::ServiceConfig) {
-    cfg.service(get);
+    cfg.service(get).service(
+        web::resource("/order")
+            .app_data(json::order_payload_config())
+            .route(web::post().to(create)),
+    );
+}
+
+// Private services
+
+#[get("/order")]
+async fn get_all(pool: web::Data<database::Pool>, claims: Claims) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    let all_orders = {
+        use crate::schema::orders::dsl::*;
+        let all_orders = orders.order(created_at.desc());
+        match claims.is_admin() {
+            true => all_orders.load::<OrderRecord>(&conn),
+            false => all_orders
+                .filter(app_id.eq_any(&claims.apps))
+                .load::<OrderRecord>(&conn),
+        }
+    }
+    .chain_err(|| ErrorKind::SelectOrdersErr)?;
+
+    Ok(HttpResponse::Ok().json(all_orders))
+}
+
+#[get("/order/{id}")]
+async fn get_one(
+    pool: web::Data<database::Pool>,
+    claims: Claims,
+    web::Path(id): web::Path<i32>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    let inner = {
+        use crate::schema::orders::dsl::orders;
+        orders.find(id).first::<OrderRecord>(&conn)
+    }
+    .chain_err(|| ErrorKind::FindOrderErr(id))?;
+
+    // Orders placed without app are only visible to admins.
+    match inner.app_id {
+        Some(app_id) => claims.check_app(app_id)?,
+        None => claims.check_admin()?,
+    }
+
+    let items = order_item::get(&conn, &inner)?;
+
+    Ok(HttpResponse::Ok().json(OrderJson { inner, items }))
+}
+
+#[put("/order/{id}/status")]
+async fn set_status(
+    pool: web::Data<database::Pool>,
+    claims: Claims,
+    web::Path(id): web::Path<i32>,
+    next: web::Json<OrderStatusJson>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    let prev = {
+        use crate::schema::orders::dsl::orders;
+        orders.find(id).first::<OrderRecord>(&conn)
+    }
+    .chain_err(|| ErrorKind::FindOrderErr(id))?;
+
+    match prev.app_id {
+        Some(app_id) => claims.check_app(app_id)?,
+        None => claims.check_admin()?,
+    }
+
+    let status = next.status.as_str();
+    if prev.status == status {
+        return Ok(HttpResponse::Ok().json(prev));
+    }
+
+    let order = conn
+        .transaction::<_, diesel::result::Error, _>(|| {
+            use crate::schema::orders::dsl;
+            let order = diesel::update(dsl::orders.find(id))
+                .set(dsl::status.eq(status))
+                .get_result::<OrderRecord>(&conn)?;
+
+            if let Some(app_id) = order.app_id {
+                let data = json!({ "order": order, "previousStatus": prev.status });
+                webhook::enqueue(&conn, &[app_id], Event::OrderStatusChanged, &data)?;
+            }
+
+            Ok(order)
+        })
+        .chain_err(|| ErrorKind::UpdateOrderStatusErr(id))?;
+
+    Ok(HttpResponse::Ok().json(order))
+}
+
+pub fn priv_services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_all).service(get_one).service(set_status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(value: f32, min: f32, max: f32) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        check_range(&mut violations, "width", "La longueur", value, min, max);
+        violations
+    }
+
+    #[test]
+    fn accepts_dimensions_within_the_material_bounds() {
+        assert!(check(100.0, 10.0, 1000.0).is_empty());
+        assert!(check(10.0, 10.0, 1000.0).is_empty());
+        assert!(check(1000.0, 10.0, 1000.0).is_empty());
+    }
+
+    #[test]
+    fn rejects_dimensions_out_of_the_material_bounds() {
+        assert_eq!(check(5.0, 10.0, 1000.0).len(), 1);
+        assert_eq!(check(1001.0, 10.0, 1000.0).len(), 1);
+        assert_eq!(check(100.0, 0.0, 0.0).len(), 1);
+    }
+
+    #[test]
+    fn rejects_non_positive_and_non_finite_dimensions() {
+        for value in [0.0, -10.0, f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            let violations = check(value, 0.0, 1000.0);
+            assert_eq!(violations.len(), 1, "{}", value);
+            assert_eq!(
+                violations[0].message,
+                "La longueur doit être un nombre positif"
+            );
+        }
+    }
 }
@@ -0,0 +1,85 @@
+use diesel::prelude::*;
+use error_chain::error_chain;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::database;
+use crate::order::OrderRecord;
+use crate::schema::order_items;
+use crate::shared::money::Money;
+
+// Error management
+
+error_chain! {
+    errors {
+        SelectOrderItemsErr(id: i32) {
+            description("Impossible de récupérer la liste des articles de la commande")
+            display("Could not select order_items `{}`", id)
+        }
+    }
+}
+
+// Models
+
+#[derive(Debug, Identifiable, Queryable, Associations, Serialize)]
+#[belongs_to(OrderRecord, foreign_key = "order_id")]
+#[serde(rename_all = "camelCase")]
+pub struct OrderItem {
+    pub id: i32,
+    pub order_id: i32,
+    pub material_id: Option<i32>,
+    pub fixation_id: Option<i32>,
+    pub shape_id: Option<i32>,
+    pub quantity: i16,
+    pub width: f32,
+    pub height: f32,
+    pub weight: f32,
+    pub discount: i16,
+    pub unit_price_tax_excl: Money,
+    pub total_tax_excl: Money,
+    pub total_tax_incl: Money,
+    pub material: Value,
+    pub fixation: Option<Value>,
+    pub condition: Option<Value>,
+    pub svg: String,
+    pub config: String,
+    pub preview: String,
+    pub tax_rate: f32,
+    pub holes: Value,
+}
+
+#[derive(Insertable)]
+#[table_name = "order_items"]
+pub struct InsertableOrderItem<'a> {
+    pub order_id: i32,
+    pub material_id: Option<&'a i32>,
+    pub fixation_id: Option<&'a i32>,
+    pub shape_id: Option<&'a i32>,
+    pub quantity: &'a i16,
+    pub width: &'a f32,
+    pub height: &'a f32,
+    pub weight: &'a f32,
+    pub discount: &'a i16,
+    pub tax_rate: &'a f32,
+    pub unit_price_tax_excl: &'a Money,
+    pub total_tax_excl: &'a Money,
+    pub total_tax_incl: &'a Money,
+    pub material: Value,
+    pub fixation: Option<Value>,
+    pub condition: Option<Value>,
+    pub svg: &'a str,
+    pub config: &'a str,
+    pub preview: &'a str,
+    pub holes: Value,
+}
+
+// Services
+
+pub fn get(conn: &database::PooledConnection, order: &OrderRecord) -> Result<Vec<OrderItem>> {
+    let items = OrderItem::belonging_to(order)
+        .order(order_items::id)
+        .load::<OrderItem>(conn)
+        .chain_err(|| ErrorKind::SelectOrderItemsErr(order.id))?;
+
+    Ok(items)
+}
@@ -0,0 +1,324 @@
+use actix_web::{
+    self,
+    dev::HttpResponseBuilder,
+    http::{header, StatusCode},
+    post, put, rt, web, HttpRequest, HttpResponse,
+};
+use chrono::{Duration, Local, NaiveDateTime};
+use diesel::prelude::*;
+use error_chain::error_chain;
+use log::{error, info, warn};
+use serde::Deserialize;
+use std::env;
+use uuid::Uuid;
+
+use crate::database;
+use crate::schema::password_reset_tokens;
+use crate::shared::mailer::{Mail, Mailer};
+use crate::shared::token::hash;
+use crate::sign_in_throttle;
+use crate::user::{self, User};
+
+/// Lifetime of a password reset link, in minutes.
+const PASSWORD_RESET_TTL_MINUTES: i64 = 60;
+
+// Error management
+
+error_chain! {
+    errors {
+        GetDbConnErr {
+            description("Impossible de se connecter à la base de données")
+            display("Could not get db conn from pool")
+        }
+        FindUserErr(login: String) {
+            description("Impossible de récupérer l'utilisateur")
+            display("Could not find user by username or email `{}`", login)
+        }
+        InsertPasswordResetTokenErr(user_id: i32) {
+            description("Impossible de générer le lien de réinitialisation")
+            display("Could not insert password reset token for user `{}`", user_id)
+        }
+        ConsumePasswordResetTokenErr {
+            description("Impossible de vérifier le lien de réinitialisation")
+            display("Could not consume password reset token")
+        }
+        InvalidPasswordResetTokenErr {
+            description("Lien de réinitialisation invalide ou expiré")
+            display("Unknown, used or expired password reset token")
+        }
+        EmptyPasswdErr {
+            description("Mot de passe requis")
+            display("Could not reset password: empty password")
+        }
+        EncryptPasswdErr {
+            description("Impossible de chiffrer le mot de passe de l'utilisateur")
+            display("Could not encrypt new password")
+        }
+        UpdateUserPasswdErr(user_id: i32) {
+            description("Impossible de modifier le mot de passe de l'utilisateur")
+            display("Could not update password of user `{}`", user_id)
+        }
+    }
+    links {
+        User(user::Error, user::ErrorKind);
+        SignInThrottle(sign_in_throttle::Error, sign_in_throttle::ErrorKind);
+    }
+    foreign_links {
+        Transaction(diesel::result::Error);
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        HttpResponseBuilder::new(self.status_code())
+            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.description().to_owned())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match *self.kind() {
+            ErrorKind::InvalidPasswordResetTokenErr | ErrorKind::EmptyPasswdErr => {
+                StatusCode::BAD_REQUEST
+            }
+            ErrorKind::SignInThrottle(ref kind) => sign_in_throttle::status_code(kind),
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+// Models
+
+#[derive(Deserialize)]
+struct PasswordResetRequest {
+    /// Username or email of the user.
+    pub login: String,
+}
+
+#[derive(Deserialize)]
+struct PasswordReset {
+    pub token: String,
+    pub password: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "password_reset_tokens"]
+struct InsertablePasswordResetToken<'a> {
+    pub user_id: &'a i32,
+    pub token_hash: &'a str,
+    pub expires_at: &'a NaiveDateTime,
+}
+
+// Helpers
+
+fn generate() -> String {
+    format!(
+        "{}{}",
+        Uuid::new_v4().to_simple(),
+        Uuid::new_v4().to_simple()
+    )
+}
+
+/// Gets the admin page choosing the new password, to which the token is
+/// appended.
+fn reset_url() -> String {
+    env::var("PASSWORD_RESET_URL")
+        .unwrap_or_else(|_| String::from("http://localhost:3000/reset-password"))
+}
+
+/// Writes the mail giving the reset link, if the user has an email.
+fn reset_mail(user: &User, url: &str, token: &str) -> Option<Mail> {
+    let to = user.email.as_ref()?;
+
+    Some(Mail {
+        to: to.to_owned(),
+        subject: String::from("Réinitialisation de votre mot de passe"),
+        body: format!(
+            "Bonjour {},\n\n\
+             Une réinitialisation de votre mot de passe a été demandée. \
+             Ouvrez ce lien dans l'heure pour en choisir un nouveau :\n\n\
+             {}?token={}\n\n\
+             Si vous n'êtes pas à l'origine de cette demande, ignorez cet email.\n",
+            user.username, url, token
+        ),
+    })
+}
+
+/// Forgets the links not used yet, so that only the last one works.
+fn discard_tokens(conn: &database::PooledConnection, user_id: i32) -> QueryResult<usize> {
+    use crate::schema::password_reset_tokens::dsl;
+
+    diesel::update(
+        dsl::password_reset_tokens
+            .filter(dsl::user_id.eq(user_id))
+            .filter(dsl::used_at.is_null()),
+    )
+    .set(dsl::used_at.eq(Local::now().naive_local()))
+    .execute(conn)
+}
+
+// Public services
+
+/// Sends a reset link to the user. The response is the same whether the
+/// user exists or not, so that it does not tell which accounts exist.
+/// Requests are throttled by login and by address like sign ins.
+#[post("/password-reset")]
+async fn request_reset(
+    pool: web::Data<database::Pool>,
+    mailer: web::Data<dyn Mailer>,
+    http_req: HttpRequest,
+    req: web::Json<PasswordResetRequest>,
+) -> Result<HttpResponse> {
+    use crate::schema::users::dsl;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let login = req.login.trim();
+    let ip = sign_in_throttle::client_ip(&http_req);
+
+    sign_in_throttle::check_reset(&conn, login, &ip)?;
+    sign_in_throttle::record_reset(&conn, login, &ip)?;
+
+    let matching_user = dsl::users
+        .filter(dsl::username.eq(login).or(dsl::email.eq(login)))
+        .first::<User>(&conn)
+        .optional()
+        .chain_err(|| ErrorKind::FindUserErr(login.to_owned()))?;
+
+    let matching_user = match matching_user {
+        Some(matching_user) => matching_user,
+        None => {
+            info!("Password reset requested for unknown user `{}`", login);
+            return Ok(HttpResponse::NoContent().finish());
+        }
+    };
+
+    let token = generate();
+    let mail = match reset_mail(&matching_user, &reset_url(), &token) {
+        Some(mail) => mail,
+        None => {
+            warn!(
+                "Password reset requested for user `{}` without email",
+                matching_user.id
+            );
+            return Ok(HttpResponse::NoContent().finish());
+        }
+    };
+
+    let expires_at = Local::now().naive_local() + Duration::minutes(PASSWORD_RESET_TTL_MINUTES);
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        discard_tokens(&conn, matching_user.id)?;
+        diesel::insert_into(password_reset_tokens::table)
+            .values(&InsertablePasswordResetToken {
+                user_id: &matching_user.id,
+                token_hash: &hash(&token),
+                expires_at: &expires_at,
+            })
+            .execute(&conn)
+    })
+    .chain_err(|| ErrorKind::InsertPasswordResetTokenErr(matching_user.id))?;
+
+    // Sent in the background: waiting for it, or for its failure, would tell
+    // that the user exists.
+    rt::spawn(async move {
+        if let Err(err) = web::block(move || mailer.send(&mail)).await {
+            error!(
+                "Could not send password reset mail to user `{}`: {}",
+                matching_user.id, err
+            );
+        }
+    });
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Sets the new password of the user of the reset link. Like any password
+/// change, it signs the user out everywhere.
+#[put("/password-reset")]
+async fn reset(
+    pool: web::Data<database::Pool>,
+    req: web::Json<PasswordReset>,
+) -> Result<HttpResponse> {
+    use crate::schema::password_reset_tokens::dsl;
+    use crate::schema::users::dsl::{password, users};
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let now = Local::now().naive_local();
+
+    if req.password.is_empty() {
+        return Err(ErrorKind::EmptyPasswdErr.into());
+    }
+
+    let passwd_hash = bcrypt::hash(&req.password, bcrypt::DEFAULT_COST)
+        .chain_err(|| ErrorKind::EncryptPasswdErr)?;
+
+    // All or nothing, so that a failure leaves the token usable again.
+    let user_id = conn.transaction::<_, Error, _>(|| {
+        // Concurrent resets with the same token: only one can use it.
+        let user_id = diesel::update(
+            dsl::password_reset_tokens
+                .filter(dsl::token_hash.eq(hash(&req.token)))
+                .filter(dsl::used_at.is_null())
+                .filter(dsl::expires_at.gt(now)),
+        )
+        .set(dsl::used_at.eq(now))
+        .returning(dsl::user_id)
+        .get_result::<i32>(&conn)
+        .optional()
+        .chain_err(|| ErrorKind::ConsumePasswordResetTokenErr)?
+        .ok_or(ErrorKind::InvalidPasswordResetTokenErr)?;
+
+        let updated_user = diesel::update(users.find(user_id))
+            .set(password.eq(&passwd_hash))
+            .get_result::<User>(&conn)
+            .chain_err(|| ErrorKind::UpdateUserPasswdErr(user_id))?;
+
+        discard_tokens(&conn, user_id).chain_err(|| ErrorKind::UpdateUserPasswdErr(user_id))?;
+        user::revoke_sessions(&conn, user_id)?;
+        sign_in_throttle::unlock(&conn, &updated_user.username)?;
+        Ok(user_id)
+    })?;
+    info!("Password of user `{}` reset", user_id);
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn pub_services(cfg: &mut web::ServiceConfig) {
+    cfg.service(request_reset).service(reset);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(email: Option<&str>) -> User {
+        User {
+            id: 1,
+            username: String::from("admin"),
+            password: String::new(),
+            token: None,
+            is_admin: true,
+            email: email.map(String::from),
+        }
+    }
+
+    #[test]
+    fn writes_reset_mails_with_the_link() {
+        let token = generate();
+        let mail = reset_mail(
+            &user(Some("admin@example.com")),
+            "https://admin.example.com/reset-password",
+            &token,
+        )
+        .unwrap();
+
+        assert_eq!(mail.to, "admin@example.com");
+        assert!(mail.body.starts_with("Bonjour admin,"));
+        assert!(mail.body.contains(&format!(
+            "https://admin.example.com/reset-password?token={}",
+            token
+        )));
+    }
+
+    #[test]
+    fn skips_users_without_email() {
+        assert!(reset_mail(&user(None), "https://admin.example.com", "token").is_none());
+    }
+}
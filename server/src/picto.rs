@@ -12,8 +12,10 @@ use serde::{Deserialize, Serialize};
 use std::ops::Deref;
 use strsim::damerau_levenshtein;
 
+use crate::app::{self, PublicApp};
 use crate::database;
 use crate::schema::pictos;
+use crate::shared::auth::{Admin, Claims};
 use crate::shared::{self, folder::Folderable};
 
 // Error management
@@ -46,6 +48,7 @@ error_chain! {
         }
     }
     links {
+        App(app::Error, app::ErrorKind);
         Folder(shared::folder::Error, shared::folder::ErrorKind);
     }
 }
@@ -110,19 +113,21 @@ struct GetPictoResponse<'a> {
     suggestion: Option<String>,
 }
 
-#[get("/picto")]
-async fn get(
-    pool: web::Data<database::Pool>,
-    query: web::Query<GetPictoQuery>,
-) -> Result<HttpResponse> {
-    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+fn get_all_pictos(conn: &database::PooledConnection) -> Result<Vec<Picto>> {
+    use crate::schema::pictos::dsl::*;
+    pictos
+        .load::<Picto>(conn)
+        .chain_err(|| ErrorKind::SelectPictosErr)
+}
 
+/// Searches the pictos by tag, or builds their folder tree when no search is
+/// given.
+fn search(
+    conn: &database::PooledConnection,
+    pictos: Vec<Picto>,
+    query: &GetPictoQuery,
+) -> Result<HttpResponse> {
     if let Some(ref pattern) = query.search {
-        let pictos = {
-            use crate::schema::pictos::dsl::*;
-            pictos.load::<Picto>(&conn)
-        }
-        .chain_err(|| ErrorKind::SelectPictosErr)?;
         let tags: Vec<&str> = pictos.iter().flat_map(|p| p.tags.split(",")).collect();
         let matching_pictos: Vec<_> = pictos
             .iter()
@@ -154,29 +159,62 @@ async fn get(
             }))
         }
     } else {
-        let pictos = {
-            use crate::schema::pictos::dsl::*;
-            pictos.load::<Picto>(&conn)
-        }
-        .chain_err(|| ErrorKind::SelectPictosErr)?;
         let folder_ids = {
             use crate::schema::folders::dsl::*;
             folders
                 .select(id)
                 .filter(category.eq("picto"))
-                .load::<i32>(&conn)
+                .load::<i32>(conn)
         }
         .chain_err(|| ErrorKind::SelectPictosErr)?;
 
-        let folders = shared::folder::get_by_ids(&conn, &folder_ids)?;
+        let folders = shared::folder::get_by_ids(conn, &folder_ids)?;
         let tree = shared::folder::build_tree(&folders, &pictos)?;
 
         Ok(HttpResponse::Ok().json(tree))
     }
 }
 
+#[get("/picto")]
+async fn get(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+    query: web::Query<GetPictoQuery>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let hidden_ids = app::get_hidden_picto_ids(&conn, app.id)?;
+
+    let pictos = get_all_pictos(&conn)?
+        .into_iter()
+        .filter(|picto| !hidden_ids.contains(&picto.id))
+        .collect();
+
+    search(&conn, pictos, &query)
+}
+
+#[get("/picto")]
+async fn get_admin(
+    pool: web::Data<database::Pool>,
+    claims: Claims,
+    query: web::Query<GetPictoQuery>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let hidden_ids = app::get_claims_hidden_picto_ids(&conn, &claims)?;
+
+    let pictos = get_all_pictos(&conn)?
+        .into_iter()
+        .filter(|picto| !hidden_ids.contains(&picto.id))
+        .collect();
+
+    search(&conn, pictos, &query)
+}
+
 #[put("/picto")]
-async fn set(pool: web::Data<database::Pool>, picto: web::Json<Picto>) -> Result<HttpResponse> {
+async fn set(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    picto: web::Json<Picto>,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let picto_id = picto.id;
 
@@ -210,6 +248,7 @@ async fn set(pool: web::Data<database::Pool>, picto: web::Json<Picto>) -> Result
 #[delete("/picto/{id}")]
 async fn del(
     pool: web::Data<database::Pool>,
+    _: Admin,
     web::Path(id): web::Path<i32>,
 ) -> Result<HttpResponse> {
     use crate::schema::pictos::dsl::pictos;
@@ -227,5 +266,5 @@ pub fn pub_services(cfg: &mut web::ServiceConfig) {
 }
 
 pub fn priv_services(cfg: &mut web::ServiceConfig) {
-    cfg.service(set).service(del);
+    cfg.service(get_admin).service(set).service(del);
 }
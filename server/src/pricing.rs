@@ -0,0 +1,470 @@
+use serde::Serialize;
+
+use crate::{
+    discount::{self, Discount},
+    fixation::{self, Fixation},
+    fixation_condition::FixationCondition,
+    material::Material,
+    order::Order,
+    shared::money::Money,
+};
+
+// Models
+
+/// The catalog entities involved in the price of an order, loaded
+/// beforehand so that the computation stays free of any I/O.
+pub struct Catalog<'a> {
+    pub material: &'a Material,
+    pub fixation: Option<&'a Fixation>,
+    pub condition: Option<&'a FixationCondition>,
+    /// The discount tiers available for the material.
+    pub discounts: &'a [Discount],
+    /// The tax rate, in percent.
+    pub tax_rate: f32,
+    pub markup: Markup,
+}
+
+/// The margins of the app selling the order, in percent, applied on the
+/// base prices of the material and of the fixation.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Markup {
+    pub material: f32,
+    pub fixation: f32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pricing {
+    pub weight: f32,
+    pub fixation_count: i32,
+    pub discount: i16,
+    pub tax_rate: f32,
+    pub area_price: Money,
+    pub unit_price_tax_excl: Money,
+    pub total_tax_excl: Money,
+    pub total_tax_incl: Money,
+    pub unit_price_tax_excl_discounted: Money,
+    pub total_tax_excl_discounted: Money,
+    pub total_tax_incl_discounted: Money,
+}
+
+// Helpers
+
+/// Area of the order in mm², dimensions being given in mm.
+fn area(order: &Order) -> i64 {
+    (order.width as f64 * order.height as f64).round() as i64
+}
+
+/// Price of the surface, the material surface price being given per m².
+pub fn area_price(material: &Material, order: &Order) -> Money {
+    material.surface_price.ratio(area(order), 1_000_000)
+}
+
+pub fn fixation_count(catalog: &Catalog) -> i32 {
+    match (catalog.fixation, catalog.condition) {
+        (Some(_), Some(condition)) => fixation::count_pos(condition),
+        _ => 0,
+    }
+}
+
+/// Percentage in basis points (hundredths of percent).
+fn percent_bp(percent: f32) -> i64 {
+    (percent as f64 * 100.0).round() as i64
+}
+
+fn with_percent(amount: Money, percent: f32) -> Money {
+    amount + amount.ratio(percent_bp(percent), 10_000)
+}
+
+fn with_tax(amount: Money, tax_rate: f32) -> Money {
+    with_percent(amount, tax_rate)
+}
+
+/// Applies a markup to a base price, rounding to the cent.
+pub fn with_markup(amount: Money, markup: f32) -> Money {
+    with_percent(amount, markup)
+}
+
+/// Computes the price of an order. The discount tier is picked with
+/// `discount_quantity`, which is the order quantity unless discounts are
+/// computed across a cart.
+///
+/// Every amount is rounded once to the cent, then multiplied: totals are
+/// always the rounded unit price times the quantity, and taxes are applied
+/// on totals. The markups apply on the base prices, before any discount.
+/// `None` if the totals overflow.
+pub fn compute(order: &Order, catalog: &Catalog, discount_quantity: i16) -> Option<Pricing> {
+    let quantity = order.quantity as i64;
+    let discount = discount::find_tier(catalog.discounts, discount_quantity)
+        .map(|d| d.amount)
+        .unwrap_or_default();
+
+    let fixation_count = fixation_count(catalog);
+    let fixations_price = match catalog.fixation {
+        Some(f) => {
+            with_markup(f.price, catalog.markup.fixation).checked_mul(fixation_count as i64)?
+        }
+        None => Money::default(),
+    };
+    let fixed_price = with_markup(catalog.material.fixed_price, catalog.markup.material);
+    let area_price = with_markup(area_price(catalog.material, order), catalog.markup.material);
+
+    let unit_price_tax_excl = fixed_price + area_price + fixations_price;
+    let total_tax_excl = unit_price_tax_excl.checked_mul(quantity)?;
+    let total_tax_incl = with_tax(total_tax_excl, catalog.tax_rate);
+
+    let unit_price_tax_excl_discounted = unit_price_tax_excl.ratio(100 - discount as i64, 100);
+    let total_tax_excl_discounted = unit_price_tax_excl_discounted.checked_mul(quantity)?;
+    let total_tax_incl_discounted = with_tax(total_tax_excl_discounted, catalog.tax_rate);
+
+    Some(Pricing {
+        weight: catalog.material.weight * order.width * order.height * 0.00001,
+        fixation_count,
+        discount,
+        tax_rate: catalog.tax_rate,
+        area_price,
+        unit_price_tax_excl,
+        total_tax_excl,
+        total_tax_incl,
+        unit_price_tax_excl_discounted,
+        total_tax_excl_discounted,
+        total_tax_incl_discounted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixation_condition::with_positions;
+
+    fn material(fixed_price: f64, surface_price: f64) -> Material {
+        Material {
+            id: 1,
+            weight: 2.0,
+            fixed_price: Money::from_euros(fixed_price),
+            surface_price: Money::from_euros(surface_price),
+            ..Material::default()
+        }
+    }
+
+    fn fixation(price: f64) -> Fixation {
+        Fixation {
+            id: 1,
+            price: Money::from_euros(price),
+            ..Fixation::default()
+        }
+    }
+
+    fn discount(amount: i16, quantity: i16) -> Discount {
+        Discount {
+            id: quantity as i32,
+            amount,
+            quantity,
+        }
+    }
+
+    fn order(width: f32, height: f32, quantity: i16) -> Order {
+        Order {
+            material_id: 1,
+            fixation_id: 0,
+            shape_id: 0,
+            quantity,
+            width,
+            height,
+            country: None,
+        }
+    }
+
+    fn catalog(material: &Material) -> Catalog<'_> {
+        Catalog {
+            material,
+            fixation: None,
+            condition: None,
+            discounts: &[],
+            tax_rate: 0.0,
+            markup: Markup::default(),
+        }
+    }
+
+    #[test]
+    fn area_price_is_per_square_meter() {
+        let material = material(0.0, 40.0);
+        let pricing = compute(&order(1000.0, 500.0, 1), &catalog(&material), 1).unwrap();
+        assert_eq!(pricing.area_price, Money::from_euros(20.0));
+        assert_eq!(pricing.unit_price_tax_excl, Money::from_euros(20.0));
+    }
+
+    #[test]
+    fn area_price_is_rounded_to_the_cent() {
+        // 0.0333 m² at 45.50 €/m² = 1.515 €
+        let material = material(0.0, 45.5);
+        let pricing = compute(&order(333.0, 100.0, 1), &catalog(&material), 1).unwrap();
+        assert_eq!(pricing.area_price, Money::from_cents(152));
+    }
+
+    #[test]
+    fn tiny_area_price_is_rounded_to_zero() {
+        // 0.0001 m² at 12.34 €/m² = 0.001234 €
+        let material = material(0.0, 12.34);
+        let pricing = compute(&order(10.0, 10.0, 1), &catalog(&material), 1).unwrap();
+        assert_eq!(pricing.area_price, Money::from_cents(0));
+    }
+
+    #[test]
+    fn unit_price_adds_the_fixed_price() {
+        let material = material(3.9, 40.0);
+        let pricing = compute(&order(1000.0, 500.0, 1), &catalog(&material), 1).unwrap();
+        assert_eq!(pricing.unit_price_tax_excl, Money::from_euros(23.9));
+    }
+
+    #[test]
+    fn weight_depends_on_the_area() {
+        let material = material(0.0, 0.0);
+        let pricing = compute(&order(1000.0, 500.0, 1), &catalog(&material), 1).unwrap();
+        assert!((pricing.weight - 10.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn no_fixation_costs_nothing() {
+        let material = material(10.0, 0.0);
+        let condition = with_positions(&["tl", "tr"]);
+        let catalog = Catalog {
+            condition: Some(&condition),
+            ..catalog(&material)
+        };
+        let pricing = compute(&order(100.0, 100.0, 1), &catalog, 1).unwrap();
+        assert_eq!(pricing.fixation_count, 0);
+        assert_eq!(pricing.unit_price_tax_excl, Money::from_euros(10.0));
+    }
+
+    #[test]
+    fn fixation_without_condition_costs_nothing() {
+        let material = material(10.0, 0.0);
+        let fixation = fixation(1.5);
+        let catalog = Catalog {
+            fixation: Some(&fixation),
+            ..catalog(&material)
+        };
+        let pricing = compute(&order(100.0, 100.0, 1), &catalog, 1).unwrap();
+        assert_eq!(pricing.fixation_count, 0);
+        assert_eq!(pricing.unit_price_tax_excl, Money::from_euros(10.0));
+    }
+
+    #[test]
+    fn fixations_are_priced_per_position() {
+        let material = material(10.0, 0.0);
+        let fixation = fixation(1.5);
+        let condition = with_positions(&["tl", "tr", "bl"]);
+        let catalog = Catalog {
+            fixation: Some(&fixation),
+            condition: Some(&condition),
+            ..catalog(&material)
+        };
+        let pricing = compute(&order(100.0, 100.0, 2), &catalog, 2).unwrap();
+        assert_eq!(pricing.fixation_count, 3);
+        assert_eq!(pricing.unit_price_tax_excl, Money::from_euros(14.5));
+        assert_eq!(pricing.total_tax_excl, Money::from_euros(29.0));
+    }
+
+    #[test]
+    fn overflowing_totals_are_refused() {
+        let material = Material {
+            fixed_price: Money::from_cents(i64::MAX / 2),
+            ..material(0.0, 0.0)
+        };
+        assert!(compute(&order(100.0, 100.0, 3), &catalog(&material), 3).is_none());
+    }
+
+    #[test]
+    fn totals_are_unit_prices_times_quantity() {
+        let material = material(0.1, 0.0);
+        let pricing = compute(&order(100.0, 100.0, 3), &catalog(&material), 3).unwrap();
+        assert_eq!(pricing.total_tax_excl, Money::from_euros(0.3));
+        assert_eq!(
+            pricing.total_tax_excl_discounted,
+            pricing.unit_price_tax_excl_discounted * 3
+        );
+    }
+
+    #[test]
+    fn no_discount_below_the_first_tier() {
+        let material = material(10.0, 0.0);
+        let discounts = [discount(5, 10), discount(10, 50)];
+        let catalog = Catalog {
+            discounts: &discounts,
+            ..catalog(&material)
+        };
+        let pricing = compute(&order(100.0, 100.0, 9), &catalog, 9).unwrap();
+        assert_eq!(pricing.discount, 0);
+        assert_eq!(pricing.total_tax_excl_discounted, pricing.total_tax_excl);
+    }
+
+    #[test]
+    fn discount_applies_from_the_tier_quantity() {
+        let material = material(10.0, 0.0);
+        let discounts = [discount(5, 10), discount(10, 50)];
+        let catalog = Catalog {
+            discounts: &discounts,
+            ..catalog(&material)
+        };
+        let pricing = compute(&order(100.0, 100.0, 10), &catalog, 10).unwrap();
+        assert_eq!(pricing.discount, 5);
+        assert_eq!(
+            pricing.unit_price_tax_excl_discounted,
+            Money::from_euros(9.5)
+        );
+        assert_eq!(pricing.total_tax_excl_discounted, Money::from_euros(95.0));
+    }
+
+    #[test]
+    fn discount_picks_the_highest_tier_reached() {
+        let material = material(10.0, 0.0);
+        let discounts = [discount(10, 50), discount(5, 10), discount(20, 100)];
+        let catalog = Catalog {
+            discounts: &discounts,
+            ..catalog(&material)
+        };
+        assert_eq!(
+            compute(&order(100.0, 100.0, 75), &catalog, 75)
+                .unwrap()
+                .discount,
+            10
+        );
+        assert_eq!(
+            compute(&order(100.0, 100.0, 100), &catalog, 100)
+                .unwrap()
+                .discount,
+            20
+        );
+    }
+
+    #[test]
+    fn discount_tier_follows_the_discount_quantity() {
+        let material = material(10.0, 0.0);
+        let discounts = [discount(5, 10)];
+        let catalog = Catalog {
+            discounts: &discounts,
+            ..catalog(&material)
+        };
+        let pricing = compute(&order(100.0, 100.0, 2), &catalog, 12).unwrap();
+        assert_eq!(pricing.discount, 5);
+        assert_eq!(pricing.total_tax_excl_discounted, Money::from_euros(19.0));
+    }
+
+    #[test]
+    fn discounted_unit_price_is_rounded_to_the_cent() {
+        let material = material(3.33, 0.0);
+        let discounts = [discount(15, 1)];
+        let catalog = Catalog {
+            discounts: &discounts,
+            ..catalog(&material)
+        };
+        // 3.33 € * 0.85 = 2.8305 €
+        let pricing = compute(&order(100.0, 100.0, 7), &catalog, 7).unwrap();
+        assert_eq!(
+            pricing.unit_price_tax_excl_discounted,
+            Money::from_cents(283)
+        );
+        assert_eq!(pricing.total_tax_excl_discounted, Money::from_cents(1981));
+    }
+
+    #[test]
+    fn tax_is_applied_on_totals() {
+        let material = material(10.0, 0.0);
+        let catalog = Catalog {
+            tax_rate: 20.0,
+            ..catalog(&material)
+        };
+        let pricing = compute(&order(100.0, 100.0, 3), &catalog, 3).unwrap();
+        assert_eq!(pricing.tax_rate, 20.0);
+        assert_eq!(pricing.total_tax_excl, Money::from_euros(30.0));
+        assert_eq!(pricing.total_tax_incl, Money::from_euros(36.0));
+    }
+
+    #[test]
+    fn reduced_tax_rates_are_rounded_to_the_cent() {
+        let material = material(9.99, 0.0);
+        let catalog = Catalog {
+            tax_rate: 5.5,
+            ..catalog(&material)
+        };
+        // 9.99 € * 1.055 = 10.53945 €
+        let pricing = compute(&order(100.0, 100.0, 1), &catalog, 1).unwrap();
+        assert_eq!(pricing.total_tax_incl, Money::from_cents(1054));
+    }
+
+    #[test]
+    fn tax_is_applied_after_discount() {
+        let material = material(10.0, 0.0);
+        let discounts = [discount(10, 1)];
+        let catalog = Catalog {
+            discounts: &discounts,
+            tax_rate: 20.0,
+            ..catalog(&material)
+        };
+        let pricing = compute(&order(100.0, 100.0, 1), &catalog, 1).unwrap();
+        assert_eq!(pricing.total_tax_excl_discounted, Money::from_euros(9.0));
+        assert_eq!(pricing.total_tax_incl_discounted, Money::from_euros(10.8));
+    }
+
+    #[test]
+    fn zero_tax_rate_keeps_totals() {
+        let material = material(10.0, 0.0);
+        let pricing = compute(&order(100.0, 100.0, 2), &catalog(&material), 2).unwrap();
+        assert_eq!(pricing.total_tax_incl, pricing.total_tax_excl);
+    }
+
+    #[test]
+    fn markup_applies_on_the_material_price() {
+        let material = material(3.9, 40.0);
+        let catalog = Catalog {
+            markup: Markup {
+                material: 25.0,
+                fixation: 0.0,
+            },
+            ..catalog(&material)
+        };
+        // (3.90 € + 20 €) * 1.25
+        let pricing = compute(&order(1000.0, 500.0, 1), &catalog, 1).unwrap();
+        assert_eq!(pricing.area_price, Money::from_euros(25.0));
+        assert_eq!(pricing.unit_price_tax_excl, Money::from_cents(2988));
+    }
+
+    #[test]
+    fn markup_applies_on_each_fixation() {
+        let material = material(10.0, 0.0);
+        let fixation = fixation(1.5);
+        let condition = with_positions(&["tl", "tr"]);
+        let catalog = Catalog {
+            fixation: Some(&fixation),
+            condition: Some(&condition),
+            markup: Markup {
+                material: 0.0,
+                fixation: 10.0,
+            },
+            ..catalog(&material)
+        };
+        // 10 € + 2 * 1.65 €
+        let pricing = compute(&order(100.0, 100.0, 1), &catalog, 1).unwrap();
+        assert_eq!(pricing.unit_price_tax_excl, Money::from_euros(13.3));
+    }
+
+    #[test]
+    fn discount_and_tax_apply_on_the_resale_price() {
+        let material = material(10.0, 0.0);
+        let discounts = [discount(10, 1)];
+        let catalog = Catalog {
+            discounts: &discounts,
+            tax_rate: 20.0,
+            markup: Markup {
+                material: 50.0,
+                fixation: 0.0,
+            },
+            ..catalog(&material)
+        };
+        let pricing = compute(&order(100.0, 100.0, 1), &catalog, 1).unwrap();
+        assert_eq!(pricing.total_tax_excl, Money::from_euros(15.0));
+        assert_eq!(pricing.total_tax_excl_discounted, Money::from_euros(13.5));
+        assert_eq!(pricing.total_tax_incl_discounted, Money::from_euros(16.2));
+    }
+}
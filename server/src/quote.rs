@@ -0,0 +1,182 @@
+use actix_web::{
+    self,
+    dev::HttpResponseBuilder,
+    http::{header, StatusCode},
+    post, web, HttpResponse,
+};
+use chrono::NaiveDate;
+use error_chain::error_chain;
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+use crate::{
+    app::{App, PublicApp},
+    carrier::{self, Parcel, ShippingOption},
+    database, holiday,
+    order::{self, Order, OrderPrice},
+    quote_event,
+    shared::money::Money,
+};
+
+// Error management
+
+error_chain! {
+    links {
+        Order(order::Error, order::ErrorKind);
+        Carrier(carrier::Error, carrier::ErrorKind);
+        Holiday(holiday::Error, holiday::ErrorKind);
+    }
+    errors {
+        GetDbConnErr {
+            description("Impossible de se connecter à la base de données")
+            display("Could not get db conn from pool")
+        }
+        EmptyQuoteErr {
+            description("Le devis ne contient aucune ligne")
+            display("Could not compute quote without lines")
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        match *self.kind() {
+            ErrorKind::Order(order::ErrorKind::InvalidOrderErr(ref violations)) => {
+                order::violations_response(violations)
+            }
+            _ => HttpResponseBuilder::new(self.status_code())
+                .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(self.description().to_owned()),
+        }
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match *self.kind() {
+            ErrorKind::EmptyQuoteErr => StatusCode::BAD_REQUEST,
+            ErrorKind::Order(ref kind) => order::status_code(kind),
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+// Models
+
+/// Defines which quantity is used to pick the discount tier of each line.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiscountScope {
+    /// The quantity of the line itself.
+    #[default]
+    Line,
+    /// The sum of the quantities of all the lines.
+    Cart,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quote {
+    pub lines: Vec<Order>,
+    #[serde(default)]
+    pub discount_scope: DiscountScope,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotePrice {
+    pub quantity: i32,
+    pub weight: f32,
+    pub total_tax_excl: Money,
+    pub total_tax_incl: Money,
+    pub total_tax_excl_discounted: Money,
+    pub total_tax_incl_discounted: Money,
+    pub lines: Vec<OrderPrice>,
+    /// The day the whole cart would leave the workshop if ordered now.
+    pub ship_date: Option<NaiveDate>,
+    /// The carriers able to ship the whole cart in a single parcel.
+    pub shipping: Vec<ShippingOption>,
+}
+
+// Helpers
+
+pub fn compute_price(
+    conn: &database::PooledConnection,
+    app: &App,
+    quote: &Quote,
+) -> Result<QuotePrice> {
+    let quantity: i32 = quote.lines.iter().map(|line| line.quantity as i32).sum();
+    let cart_discount_quantity = i16::try_from(quantity).unwrap_or(i16::MAX);
+
+    let lines = order::compute_prices(
+        conn,
+        app,
+        quote.lines.iter().map(|line| {
+            let discount_quantity = match quote.discount_scope {
+                DiscountScope::Line => line.quantity,
+                DiscountScope::Cart => cart_discount_quantity,
+            };
+            (line, discount_quantity)
+        }),
+    )?;
+
+    let mut price = QuotePrice {
+        quantity,
+        ..QuotePrice::default()
+    };
+
+    let mut parcel = Parcel::default();
+
+    for (line, line_price) in quote.lines.iter().zip(lines) {
+        // The line weight is the weight of a single sign.
+        let weight = line_price.pricing.weight * line.quantity as f32;
+        parcel.add(weight, line.width, line.height);
+        price.weight += weight;
+        price.total_tax_excl += line_price.pricing.total_tax_excl;
+        price.total_tax_incl += line_price.pricing.total_tax_incl;
+        price.total_tax_excl_discounted += line_price.pricing.total_tax_excl_discounted;
+        price.total_tax_incl_discounted += line_price.pricing.total_tax_incl_discounted;
+        price.ship_date = price.ship_date.max(line_price.ship_date);
+        price.lines.push(line_price);
+    }
+
+    if let Some(ship_date) = price.ship_date {
+        price.shipping = carrier::shipping_options(
+            &carrier::get_all(conn, Some(app.id))?,
+            &parcel,
+            &holiday::get_calendar(conn)?,
+            ship_date,
+        );
+    }
+
+    Ok(price)
+}
+
+// Services
+
+#[post("/quote")]
+async fn create(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+    quote: web::Json<Quote>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    if quote.lines.is_empty() {
+        return Err(ErrorKind::EmptyQuoteErr.into());
+    }
+
+    let price = compute_price(&conn, &app, &quote)?;
+    let lines = quote
+        .lines
+        .iter()
+        .zip(price.lines.iter())
+        .collect::<Vec<_>>();
+    quote_event::record(&conn, &app, quote_event::Source::Quote, &lines);
+
+    Ok(HttpResponse::Ok().json(price))
+}
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(create);
+}
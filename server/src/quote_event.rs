@@ -0,0 +1,546 @@
+use std::{cmp::Ordering, collections::HashMap, env, str::FromStr};
+
+use actix_web::{
+    self,
+    dev::HttpResponseBuilder,
+    get,
+    http::{header, StatusCode},
+    web, HttpResponse,
+};
+use chrono::{Duration, Local, NaiveDate};
+use diesel::{
+    dsl::sql,
+    prelude::*,
+    sql_types::{BigInt, Double},
+};
+use error_chain::error_chain;
+use log::error;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+use crate::app::App;
+use crate::database;
+use crate::order::{Order, OrderPrice};
+use crate::schema::{apps, order_items, orders, quote_events};
+use crate::shared::auth::{self, Claims};
+
+/// Env var holding the share of the quotes to record, between 0 and 1.
+const SAMPLE_RATE_VAR: &str = "QUOTE_EVENTS_SAMPLE_RATE";
+
+// Error management
+
+error_chain! {
+    links {
+        Auth(auth::Error, auth::ErrorKind);
+    }
+    errors {
+        GetDbConnErr {
+            description("Impossible de se connecter à la base de données")
+            display("Could not get db conn from pool")
+        }
+        InsertQuoteEventsErr(app_id: i32) {
+            description("Impossible d'enregistrer le devis")
+            display("Could not insert quote events of app `{}`", app_id)
+        }
+        SelectQuoteStatsErr {
+            description("Impossible de calculer les statistiques des devis")
+            display("Could not select quote stats")
+        }
+        InvalidDimensionErr(dimension: String) {
+            description("Critère de regroupement inconnu")
+            display("Unknown quote stats dimension `{}`", dimension)
+        }
+        InvalidDateRangeErr(from: NaiveDate, to: NaiveDate) {
+            description("La date de début doit précéder la date de fin")
+            display("Invalid date range from `{}` to `{}`", from, to)
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        HttpResponseBuilder::new(self.status_code())
+            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.description().to_owned())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match *self.kind() {
+            ErrorKind::InvalidDimensionErr(_) | ErrorKind::InvalidDateRangeErr(_, _) => {
+                StatusCode::BAD_REQUEST
+            }
+            ErrorKind::Auth(ref kind) => auth::status_code(kind),
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+// Models
+
+/// The endpoint a quote was computed by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    /// A single line priced with `GET /order`.
+    Order,
+    /// A cart priced with `POST /quote`.
+    Quote,
+}
+
+impl Source {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Source::Order => "order",
+            Source::Quote => "quote",
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "quote_events"]
+struct InsertableQuoteEvent<'a> {
+    pub app_id: &'a i32,
+    pub material_id: Option<&'a i32>,
+    pub fixation_id: Option<&'a i32>,
+    pub shape_id: Option<&'a i32>,
+    pub quantity: &'a i16,
+    pub width: &'a f32,
+    pub height: &'a f32,
+    pub sample_weight: &'a f32,
+    pub source: &'a str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    App,
+    Material,
+    Shape,
+    Fixation,
+}
+
+impl FromStr for Dimension {
+    type Err = Error;
+
+    fn from_str(dimension: &str) -> Result<Self> {
+        match dimension.trim() {
+            "app" => Ok(Dimension::App),
+            "material" => Ok(Dimension::Material),
+            "shape" => Ok(Dimension::Shape),
+            "fixation" => Ok(Dimension::Fixation),
+            _ => Err(ErrorKind::InvalidDimensionErr(dimension.to_owned()).into()),
+        }
+    }
+}
+
+/// The identifiers a stat is grouped by. They are `None` when not grouped
+/// by, or when the entity does not exist anymore.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsKey {
+    pub app_id: Option<i32>,
+    pub material_id: Option<i32>,
+    pub shape_id: Option<i32>,
+    pub fixation_id: Option<i32>,
+}
+
+impl StatsKey {
+    fn project(&self, group_by: &[Dimension]) -> Self {
+        let keep = |dimension, id: Option<i32>| id.filter(|_| group_by.contains(&dimension));
+        StatsKey {
+            app_id: keep(Dimension::App, self.app_id),
+            material_id: keep(Dimension::Material, self.material_id),
+            shape_id: keep(Dimension::Shape, self.shape_id),
+            fixation_id: keep(Dimension::Fixation, self.fixation_id),
+        }
+    }
+}
+
+/// The quote events of a key, sums being weighted by the sample weight.
+#[derive(Debug, Clone, Queryable)]
+pub struct QuoteTotals {
+    pub app_id: i32,
+    pub material_id: Option<i32>,
+    pub shape_id: Option<i32>,
+    pub fixation_id: Option<i32>,
+    pub quotes: f64,
+    pub width: f64,
+    pub height: f64,
+    pub quantity: f64,
+}
+
+impl QuoteTotals {
+    fn key(&self) -> StatsKey {
+        StatsKey {
+            app_id: Some(self.app_id),
+            material_id: self.material_id,
+            shape_id: self.shape_id,
+            fixation_id: self.fixation_id,
+        }
+    }
+}
+
+/// The order lines of a key.
+#[derive(Debug, Clone, Queryable)]
+pub struct OrderTotals {
+    pub app_id: Option<i32>,
+    pub material_id: Option<i32>,
+    pub shape_id: Option<i32>,
+    pub fixation_id: Option<i32>,
+    pub orders: i64,
+}
+
+impl OrderTotals {
+    fn key(&self) -> StatsKey {
+        StatsKey {
+            app_id: self.app_id,
+            material_id: self.material_id,
+            shape_id: self.shape_id,
+            fixation_id: self.fixation_id,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteStats {
+    #[serde(flatten)]
+    pub key: StatsKey,
+    /// Estimated number of quoted lines, sampling included.
+    pub quotes: f64,
+    pub avg_width: f64,
+    pub avg_height: f64,
+    pub avg_quantity: f64,
+    /// Number of ordered lines.
+    pub orders: i64,
+    /// Share of the quoted lines that were ordered.
+    pub conversion: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub app_id: Option<i32>,
+    /// Comma separated dimensions, all of them by default.
+    pub group_by: Option<String>,
+}
+
+// Helpers
+
+fn sample_rate() -> f32 {
+    env::var(SAMPLE_RATE_VAR)
+        .ok()
+        .and_then(|rate| rate.parse::<f32>().ok())
+        .filter(|rate| *rate > 0.0 && *rate <= 1.0)
+        .unwrap_or(1.0)
+}
+
+fn is_sampled(rate: f32) -> bool {
+    if rate >= 1.0 {
+        return true;
+    }
+
+    let mut bytes = [0; 4];
+    SystemRandom::new().fill(&mut bytes).is_ok()
+        && (u32::from_le_bytes(bytes) as f64) < rate as f64 * u32::MAX as f64
+}
+
+/// Records the priced lines of a quote, if sampled. Recording is best
+/// effort: a failure is logged and does not fail the quote.
+pub fn record(
+    conn: &database::PooledConnection,
+    app: &App,
+    source: Source,
+    lines: &[(&Order, &OrderPrice)],
+) {
+    let rate = sample_rate();
+    if !is_sampled(rate) {
+        return;
+    }
+
+    // Each recorded quote stands for the ones skipped by the sampling.
+    let sample_weight = 1.0 / rate;
+    let new_events = lines
+        .iter()
+        .filter(|(_, price)| price.product.id != 0)
+        .map(|(order, price)| InsertableQuoteEvent {
+            app_id: &app.id,
+            material_id: Some(&price.product.id),
+            fixation_id: price.fixation.as_ref().map(|f| &f.id),
+            shape_id: Some(&order.shape_id).filter(|&&id| id != 0),
+            quantity: &order.quantity,
+            width: &order.width,
+            height: &order.height,
+            sample_weight: &sample_weight,
+            source: source.as_str(),
+        })
+        .collect::<Vec<_>>();
+
+    if new_events.is_empty() {
+        return;
+    }
+
+    let res = diesel::insert_into(quote_events::table)
+        .values(&new_events)
+        .execute(conn)
+        .chain_err(|| ErrorKind::InsertQuoteEventsErr(app.id));
+
+    if let Err(err) = res {
+        error!("{}", err);
+    }
+}
+
+fn parse_group_by(group_by: Option<&str>) -> Result<Vec<Dimension>> {
+    match group_by.filter(|group_by| !group_by.trim().is_empty()) {
+        Some(group_by) => group_by.split(',').map(str::parse).collect(),
+        None => Ok(vec![
+            Dimension::App,
+            Dimension::Material,
+            Dimension::Shape,
+            Dimension::Fixation,
+        ]),
+    }
+}
+
+fn ratio(value: f64, total: f64) -> f64 {
+    if total > 0.0 {
+        value / total
+    } else {
+        0.0
+    }
+}
+
+/// Rolls the totals up to the given dimensions. Order lines without quote
+/// are kept, so that their conversion shows as unknown (zero quotes).
+pub fn aggregate(
+    group_by: &[Dimension],
+    quotes: &[QuoteTotals],
+    orders: &[OrderTotals],
+) -> Vec<QuoteStats> {
+    let mut stats: HashMap<StatsKey, QuoteStats> = HashMap::new();
+
+    for totals in quotes {
+        let key = totals.key().project(group_by);
+        let stat = stats.entry(key).or_insert_with(|| QuoteStats {
+            key,
+            ..QuoteStats::default()
+        });
+        stat.quotes += totals.quotes;
+        stat.avg_width += totals.width;
+        stat.avg_height += totals.height;
+        stat.avg_quantity += totals.quantity;
+    }
+
+    for totals in orders {
+        let key = totals.key().project(group_by);
+        let stat = stats.entry(key).or_insert_with(|| QuoteStats {
+            key,
+            ..QuoteStats::default()
+        });
+        stat.orders += totals.orders;
+    }
+
+    let mut stats = stats
+        .into_values()
+        .map(|stat| QuoteStats {
+            quotes: stat.quotes.round(),
+            avg_width: ratio(stat.avg_width, stat.quotes),
+            avg_height: ratio(stat.avg_height, stat.quotes),
+            avg_quantity: ratio(stat.avg_quantity, stat.quotes),
+            conversion: ratio(stat.orders as f64, stat.quotes).min(1.0),
+            ..stat
+        })
+        .collect::<Vec<_>>();
+
+    stats.sort_by(|a, b| {
+        b.quotes
+            .partial_cmp(&a.quotes)
+            .unwrap_or(Ordering::Equal)
+            .then(b.orders.cmp(&a.orders))
+    });
+    stats
+}
+
+/// Gets the ids of the apps the stats are computed for.
+fn get_app_ids(
+    conn: &database::PooledConnection,
+    claims: &Claims,
+    app_id: Option<i32>,
+) -> Result<Vec<i32>> {
+    match app_id {
+        Some(app_id) => {
+            claims.check_app(app_id)?;
+            Ok(vec![app_id])
+        }
+        None if claims.is_admin() => apps::table
+            .select(apps::id)
+            .load::<i32>(conn)
+            .chain_err(|| ErrorKind::SelectQuoteStatsErr),
+        None => Ok(claims.apps.clone()),
+    }
+}
+
+// Services
+
+#[get("/quote-event/stats")]
+async fn get_stats(
+    pool: web::Data<database::Pool>,
+    claims: Claims,
+    query: web::Query<StatsQuery>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let group_by = parse_group_by(query.group_by.as_deref())?;
+    let app_ids = get_app_ids(&conn, &claims, query.app_id)?;
+
+    let to = query
+        .to
+        .unwrap_or_else(|| Local::now().naive_local().date());
+    let from = query.from.unwrap_or(to - Duration::days(30));
+    if from > to {
+        return Err(ErrorKind::InvalidDateRangeErr(from, to).into());
+    }
+    let (from, to) = (
+        from.and_hms(0, 0, 0),
+        (to + Duration::days(1)).and_hms(0, 0, 0),
+    );
+
+    let quotes = {
+        use crate::schema::quote_events::dsl::*;
+        quote_events
+            .filter(created_at.ge(from))
+            .filter(created_at.lt(to))
+            .filter(app_id.eq_any(&app_ids))
+            .group_by((app_id, material_id, shape_id, fixation_id))
+            .select((
+                app_id,
+                material_id,
+                shape_id,
+                fixation_id,
+                sql::<Double>("SUM(sample_weight)::FLOAT8"),
+                sql::<Double>("SUM(sample_weight * width)::FLOAT8"),
+                sql::<Double>("SUM(sample_weight * height)::FLOAT8"),
+                sql::<Double>("SUM(sample_weight * quantity)::FLOAT8"),
+            ))
+            .load::<QuoteTotals>(&conn)
+    }
+    .chain_err(|| ErrorKind::SelectQuoteStatsErr)?;
+
+    let orders = order_items::table
+        .inner_join(orders::table)
+        .filter(orders::created_at.ge(from))
+        .filter(orders::created_at.lt(to))
+        .filter(orders::app_id.eq_any(&app_ids))
+        .filter(orders::status.ne("cancelled"))
+        .group_by((
+            orders::app_id,
+            order_items::material_id,
+            order_items::shape_id,
+            order_items::fixation_id,
+        ))
+        .select((
+            orders::app_id,
+            order_items::material_id,
+            order_items::shape_id,
+            order_items::fixation_id,
+            sql::<BigInt>("COUNT(*)"),
+        ))
+        .load::<OrderTotals>(&conn)
+        .chain_err(|| ErrorKind::SelectQuoteStatsErr)?;
+
+    Ok(HttpResponse::Ok().json(aggregate(&group_by, &quotes, &orders)))
+}
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_stats);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quotes(app_id: i32, material_id: i32, shape_id: i32, quotes: f64) -> QuoteTotals {
+        QuoteTotals {
+            app_id,
+            material_id: Some(material_id),
+            shape_id: Some(shape_id),
+            fixation_id: None,
+            quotes,
+            width: quotes * 100.0,
+            height: quotes * 50.0,
+            quantity: quotes * 2.0,
+        }
+    }
+
+    fn orders(app_id: i32, material_id: i32, shape_id: i32, orders: i64) -> OrderTotals {
+        OrderTotals {
+            app_id: Some(app_id),
+            material_id: Some(material_id),
+            shape_id: Some(shape_id),
+            fixation_id: None,
+            orders,
+        }
+    }
+
+    #[test]
+    fn parses_group_by() {
+        assert_eq!(parse_group_by(None).unwrap().len(), 4);
+        assert_eq!(
+            parse_group_by(Some("material, shape")).unwrap(),
+            vec![Dimension::Material, Dimension::Shape]
+        );
+        assert!(parse_group_by(Some("material,size")).is_err());
+    }
+
+    #[test]
+    fn samples_everything_by_default() {
+        assert!(is_sampled(1.0));
+        assert!(!(0..100).any(|_| is_sampled(0.0)));
+    }
+
+    #[test]
+    fn rolls_up_to_the_given_dimensions() {
+        let stats = aggregate(
+            &[Dimension::Material],
+            &[
+                quotes(1, 1, 1, 6.0),
+                quotes(2, 1, 2, 4.0),
+                quotes(1, 2, 1, 1.0),
+            ],
+            &[orders(1, 1, 1, 2), orders(2, 1, 1, 1), orders(1, 3, 1, 1)],
+        );
+
+        assert_eq!(stats.len(), 3);
+        assert_eq!(
+            stats[0],
+            QuoteStats {
+                key: StatsKey {
+                    material_id: Some(1),
+                    ..StatsKey::default()
+                },
+                quotes: 10.0,
+                avg_width: 100.0,
+                avg_height: 50.0,
+                avg_quantity: 2.0,
+                orders: 3,
+                conversion: 0.3,
+            }
+        );
+        assert_eq!(stats[1].key.material_id, Some(2));
+        assert_eq!(stats[1].orders, 0);
+        assert_eq!(stats[2].key.material_id, Some(3));
+        assert_eq!(stats[2].conversion, 0.0);
+    }
+
+    #[test]
+    fn keeps_all_dimensions_by_default() {
+        let group_by = parse_group_by(None).unwrap();
+        let stats = aggregate(
+            &group_by,
+            &[quotes(1, 1, 1, 2.0), quotes(1, 1, 2, 2.0)],
+            &[],
+        );
+        assert_eq!(stats.len(), 2);
+        assert!(stats.iter().all(|stat| stat.key.app_id == Some(1)));
+    }
+}
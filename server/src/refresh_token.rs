@@ -0,0 +1,317 @@
+use actix_web::{
+    self,
+    dev::HttpResponseBuilder,
+    http::{header, StatusCode},
+    HttpResponse,
+};
+use chrono::{Duration, Local, NaiveDateTime};
+use diesel::prelude::*;
+use error_chain::error_chain;
+use log::{error, warn};
+use uuid::Uuid;
+
+use crate::database;
+use crate::schema::refresh_tokens;
+use crate::shared::token::hash;
+use crate::user::User;
+
+/// Lifetime of a refresh token, in days. Each refresh gives a new one.
+pub const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
+// Error management
+
+error_chain! {
+    errors {
+        InsertRefreshTokenErr(user_id: i32) {
+            description("Impossible de générer le jeton de rafraîchissement")
+            display("Could not insert refresh token for user `{}`", user_id)
+        }
+        FindRefreshTokenErr {
+            description("Impossible de récupérer le jeton de rafraîchissement")
+            display("Could not find refresh token")
+        }
+        InvalidRefreshTokenErr {
+            description("Jeton de rafraîchissement invalide")
+            display("Unknown refresh token")
+        }
+        ExpiredRefreshTokenErr(id: i32) {
+            description("Jeton de rafraîchissement expiré")
+            display("Refresh token `{}` has expired", id)
+        }
+        RevokedRefreshTokenErr(id: i32) {
+            description("Jeton de rafraîchissement révoqué")
+            display("Refresh token `{}` has been revoked", id)
+        }
+        ReusedRefreshTokenErr(id: i32) {
+            description("Jeton de rafraîchissement déjà utilisé")
+            display("Refresh token `{}` has already been used", id)
+        }
+        RotateRefreshTokenErr(id: i32) {
+            description("Impossible de renouveler le jeton de rafraîchissement")
+            display("Could not rotate refresh token `{}`", id)
+        }
+        RevokeRefreshTokensErr(family: String) {
+            description("Impossible de révoquer les jetons de rafraîchissement")
+            display("Could not revoke refresh tokens of family `{}`", family)
+        }
+        RevokeUserRefreshTokensErr(user_id: i32) {
+            description("Impossible de révoquer les sessions de l'utilisateur")
+            display("Could not revoke refresh tokens of user `{}`", user_id)
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        HttpResponseBuilder::new(self.status_code())
+            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.description().to_owned())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        status_code(self.kind())
+    }
+}
+
+pub fn status_code(kind: &ErrorKind) -> StatusCode {
+    match *kind {
+        ErrorKind::InvalidRefreshTokenErr
+        | ErrorKind::ExpiredRefreshTokenErr(_)
+        | ErrorKind::RevokedRefreshTokenErr(_)
+        | ErrorKind::ReusedRefreshTokenErr(_) => StatusCode::UNAUTHORIZED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+// Models
+
+/// A single use token giving a new access token. Refreshing uses the token
+/// and gives a new one of the same `family`, which stands for a sign in.
+/// Only the hash of the token is stored.
+#[derive(Debug, Clone, Identifiable, Queryable, Associations)]
+#[belongs_to(User)]
+pub struct RefreshToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub family: String,
+    pub token_hash: String,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub used_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+/// A token given to a user, with the session it belongs to.
+#[derive(Debug)]
+pub struct IssuedToken {
+    pub user_id: i32,
+    pub family: String,
+    pub token: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "refresh_tokens"]
+struct InsertableRefreshToken<'a> {
+    pub user_id: &'a i32,
+    pub family: &'a str,
+    pub token_hash: &'a str,
+    pub expires_at: &'a NaiveDateTime,
+}
+
+// Helpers
+
+fn generate() -> String {
+    format!(
+        "{}{}",
+        Uuid::new_v4().to_simple(),
+        Uuid::new_v4().to_simple()
+    )
+}
+
+/// Checks that the token can be exchanged at `now`.
+fn check(token: &RefreshToken, now: NaiveDateTime) -> Result<()> {
+    if token.revoked_at.is_some() {
+        Err(ErrorKind::RevokedRefreshTokenErr(token.id).into())
+    } else if token.used_at.is_some() {
+        Err(ErrorKind::ReusedRefreshTokenErr(token.id).into())
+    } else if token.expires_at <= now {
+        Err(ErrorKind::ExpiredRefreshTokenErr(token.id).into())
+    } else {
+        Ok(())
+    }
+}
+
+fn insert(
+    conn: &database::PooledConnection,
+    user_id: i32,
+    family: &str,
+) -> QueryResult<IssuedToken> {
+    let token = generate();
+    let expires_at = Local::now().naive_local() + Duration::days(REFRESH_TOKEN_TTL_DAYS);
+
+    diesel::insert_into(refresh_tokens::table)
+        .values(&InsertableRefreshToken {
+            user_id: &user_id,
+            family,
+            token_hash: &hash(&token),
+            expires_at: &expires_at,
+        })
+        .execute(conn)?;
+
+    Ok(IssuedToken {
+        user_id,
+        family: family.to_owned(),
+        token,
+    })
+}
+
+/// Gives a refresh token to a user signing in, starting a new family.
+pub fn issue(conn: &database::PooledConnection, user_id: i32) -> Result<IssuedToken> {
+    let family = Uuid::new_v4().to_simple().to_string();
+    insert(conn, user_id, &family).chain_err(|| ErrorKind::InsertRefreshTokenErr(user_id))
+}
+
+/// Revokes all the tokens of a family.
+pub fn revoke_family(conn: &database::PooledConnection, family: &str) -> Result<()> {
+    use crate::schema::refresh_tokens::dsl;
+
+    diesel::update(
+        dsl::refresh_tokens
+            .filter(dsl::family.eq(family))
+            .filter(dsl::revoked_at.is_null()),
+    )
+    .set(dsl::revoked_at.eq(Local::now().naive_local()))
+    .execute(conn)
+    .chain_err(|| ErrorKind::RevokeRefreshTokensErr(family.to_owned()))?;
+
+    Ok(())
+}
+
+/// Revokes all the tokens of a user, signing them out everywhere.
+pub fn revoke_user(conn: &database::PooledConnection, user_id: i32) -> Result<()> {
+    use crate::schema::refresh_tokens::dsl;
+
+    diesel::update(
+        dsl::refresh_tokens
+            .filter(dsl::user_id.eq(user_id))
+            .filter(dsl::revoked_at.is_null()),
+    )
+    .set(dsl::revoked_at.eq(Local::now().naive_local()))
+    .execute(conn)
+    .chain_err(|| ErrorKind::RevokeUserRefreshTokensErr(user_id))?;
+
+    Ok(())
+}
+
+/// Exchanges a refresh token for a new one of the same family. A token used
+/// twice has most likely been stolen: its whole family is revoked, signing
+/// both holders out.
+pub fn rotate(conn: &database::PooledConnection, token: &str) -> Result<IssuedToken> {
+    use crate::schema::refresh_tokens::dsl;
+    let now = Local::now().naive_local();
+
+    let prev = dsl::refresh_tokens
+        .filter(dsl::token_hash.eq(hash(token)))
+        .first::<RefreshToken>(conn)
+        .optional()
+        .chain_err(|| ErrorKind::FindRefreshTokenErr)?
+        .ok_or(ErrorKind::InvalidRefreshTokenErr)?;
+
+    if let Err(err) = check(&prev, now) {
+        if let ErrorKind::ReusedRefreshTokenErr(_) = err.kind() {
+            warn!(
+                "Refresh token `{}` of user `{}` reused, revoking family `{}`",
+                prev.id, prev.user_id, prev.family
+            );
+            revoke_family(conn, &prev.family)?;
+        }
+        return Err(err);
+    }
+
+    let next = conn
+        .transaction::<_, diesel::result::Error, _>(|| {
+            // Concurrent refreshes with the same token: only one can use it.
+            let used = diesel::update(
+                dsl::refresh_tokens
+                    .find(prev.id)
+                    .filter(dsl::used_at.is_null()),
+            )
+            .set(dsl::used_at.eq(now))
+            .execute(conn)?;
+
+            match used {
+                0 => Ok(None),
+                _ => insert(conn, prev.user_id, &prev.family).map(Some),
+            }
+        })
+        .chain_err(|| ErrorKind::RotateRefreshTokenErr(prev.id))?
+        .ok_or(ErrorKind::ReusedRefreshTokenErr(prev.id))?;
+
+    Ok(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(expires_in: Duration) -> RefreshToken {
+        let now = Local::now().naive_local();
+        RefreshToken {
+            id: 1,
+            user_id: 1,
+            family: String::from("family"),
+            token_hash: hash("token"),
+            created_at: now,
+            expires_at: now + expires_in,
+            used_at: None,
+            revoked_at: None,
+        }
+    }
+
+    #[test]
+    fn generates_unique_tokens() {
+        let (a, b) = (generate(), generate());
+        assert_eq!(a.len(), 64);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn accepts_fresh_tokens() {
+        let now = Local::now().naive_local();
+        assert!(check(&token(Duration::days(1)), now).is_ok());
+    }
+
+    #[test]
+    fn refuses_expired_used_and_revoked_tokens() {
+        let now = Local::now().naive_local();
+        let kind = |token: &RefreshToken| check(token, now).unwrap_err().kind().to_string();
+
+        assert!(matches!(
+            check(&token(Duration::seconds(-1)), now)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::ExpiredRefreshTokenErr(1)
+        ));
+
+        let used = RefreshToken {
+            used_at: Some(now),
+            ..token(Duration::days(1))
+        };
+        assert_eq!(kind(&used), ErrorKind::ReusedRefreshTokenErr(1).to_string());
+
+        let revoked = RefreshToken {
+            used_at: Some(now),
+            revoked_at: Some(now),
+            ..token(Duration::days(1))
+        };
+        assert_eq!(
+            kind(&revoked),
+            ErrorKind::RevokedRefreshTokenErr(1).to_string()
+        );
+        assert_eq!(
+            status_code(check(&revoked, now).unwrap_err().kind()),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
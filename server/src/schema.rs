@@ -1,3 +1,31 @@
+table! {
+    app_api_keys (id) {
+        id -> Int4,
+        app_id -> Int4,
+        name -> Text,
+        prefix -> Text,
+        secret_hash -> Text,
+        scope -> Text,
+        created_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    app_carriers (app_id, carrier_id) {
+        app_id -> Int4,
+        carrier_id -> Int4,
+    }
+}
+
+table! {
+    app_fixation_markups (app_id, fixation_id) {
+        app_id -> Int4,
+        fixation_id -> Int4,
+        markup -> Float4,
+    }
+}
+
 table! {
     app_fonts (app_id, font_id) {
         app_id -> Int4,
@@ -5,6 +33,14 @@ table! {
     }
 }
 
+table! {
+    app_material_markups (app_id, material_id) {
+        app_id -> Int4,
+        material_id -> Int4,
+        markup -> Float4,
+    }
+}
+
 table! {
     app_materials (app_id, material_id) {
         app_id -> Int4,
@@ -12,6 +48,45 @@ table! {
     }
 }
 
+table! {
+    app_origins (app_id, origin) {
+        app_id -> Int4,
+        origin -> Text,
+    }
+}
+
+table! {
+    app_pictos (app_id, picto_id) {
+        app_id -> Int4,
+        picto_id -> Int4,
+    }
+}
+
+table! {
+    app_settings (app_id) {
+        app_id -> Int4,
+        logo_url -> Nullable<Text>,
+        palette -> Array<Text>,
+        template_id -> Nullable<Int4>,
+        product_id -> Nullable<Int4>,
+        steps -> Array<Text>,
+    }
+}
+
+table! {
+    app_shapes (app_id, shape_id) {
+        app_id -> Int4,
+        shape_id -> Int4,
+    }
+}
+
+table! {
+    app_templates (app_id, template_id) {
+        app_id -> Int4,
+        template_id -> Int4,
+    }
+}
+
 table! {
     app_users (app_id, user_id) {
         app_id -> Int4,
@@ -23,6 +98,24 @@ table! {
     apps (id) {
         id -> Int4,
         name -> Text,
+        public_key -> Text,
+        host -> Nullable<Text>,
+        markup -> Float4,
+    }
+}
+
+table! {
+    audit_logs (id) {
+        id -> Int4,
+        user_id -> Nullable<Int4>,
+        subject -> Text,
+        entity -> Text,
+        entity_id -> Nullable<Int4>,
+        action -> Text,
+        method -> Text,
+        path -> Text,
+        changes -> Jsonb,
+        created_at -> Timestamp,
     }
 }
 
@@ -34,6 +127,16 @@ table! {
     }
 }
 
+table! {
+    carriers (id) {
+        id -> Int4,
+        name -> Text,
+        max_length -> Float4,
+        max_width -> Float4,
+        transit_days -> Int2,
+    }
+}
+
 table! {
     dimensions (id) {
         id -> Int4,
@@ -79,7 +182,7 @@ table! {
         preview_url -> Text,
         icon_url -> Text,
         video_url -> Nullable<Text>,
-        price -> Float4,
+        price -> Int8,
         diameter -> Float4,
         drill_diameter -> Float4,
     }
@@ -102,6 +205,14 @@ table! {
     }
 }
 
+table! {
+    holidays (id) {
+        id -> Int4,
+        date -> Date,
+        name -> Text,
+    }
+}
+
 table! {
     material_badges (material_id, badge_id) {
         material_id -> Int4,
@@ -149,14 +260,62 @@ table! {
         max_width -> Float4,
         max_height -> Float4,
         weight -> Float4,
-        fixed_price -> Float4,
-        surface_price -> Float4,
+        fixed_price -> Int8,
+        surface_price -> Int8,
         manufacturing_time -> Int2,
         more -> Nullable<Text>,
         transparency -> Int4,
     }
 }
 
+table! {
+    order_items (id) {
+        id -> Int4,
+        order_id -> Int4,
+        material_id -> Nullable<Int4>,
+        fixation_id -> Nullable<Int4>,
+        shape_id -> Nullable<Int4>,
+        quantity -> Int2,
+        width -> Float4,
+        height -> Float4,
+        weight -> Float4,
+        discount -> Int2,
+        unit_price_tax_excl -> Int8,
+        total_tax_excl -> Int8,
+        total_tax_incl -> Int8,
+        material -> Jsonb,
+        fixation -> Nullable<Jsonb>,
+        condition -> Nullable<Jsonb>,
+        svg -> Text,
+        config -> Text,
+        preview -> Text,
+        tax_rate -> Float4,
+        holes -> Jsonb,
+    }
+}
+
+table! {
+    orders (id) {
+        id -> Int4,
+        status -> Text,
+        total_tax_excl -> Int8,
+        total_tax_incl -> Int8,
+        created_at -> Timestamp,
+        app_id -> Nullable<Int4>,
+    }
+}
+
+table! {
+    password_reset_tokens (id) {
+        id -> Int4,
+        user_id -> Int4,
+        token_hash -> Text,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+    }
+}
+
 table! {
     pictos (id) {
         id -> Int4,
@@ -166,6 +325,35 @@ table! {
     }
 }
 
+table! {
+    quote_events (id) {
+        id -> Int4,
+        app_id -> Int4,
+        material_id -> Nullable<Int4>,
+        fixation_id -> Nullable<Int4>,
+        shape_id -> Nullable<Int4>,
+        quantity -> Int2,
+        width -> Float4,
+        height -> Float4,
+        sample_weight -> Float4,
+        source -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    refresh_tokens (id) {
+        id -> Int4,
+        user_id -> Int4,
+        family -> Text,
+        token_hash -> Text,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
 table! {
     shapes (id) {
         id -> Int4,
@@ -175,6 +363,35 @@ table! {
     }
 }
 
+table! {
+    shipping_brackets (id) {
+        id -> Int4,
+        carrier_id -> Int4,
+        max_weight -> Float4,
+        price -> Int8,
+    }
+}
+
+table! {
+    sign_in_throttles (scope, subject) {
+        scope -> Text,
+        subject -> Text,
+        failures -> Int4,
+        last_failed_at -> Timestamp,
+        locked_until -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    tax_rates (id) {
+        id -> Int4,
+        app_id -> Nullable<Int4>,
+        material_id -> Nullable<Int4>,
+        country -> Text,
+        rate -> Float4,
+    }
+}
+
 table! {
     templates (id) {
         id -> Int4,
@@ -193,13 +410,59 @@ table! {
         password -> Text,
         token -> Nullable<Text>,
         is_admin -> Bool,
+        email -> Nullable<Text>,
+    }
+}
+
+table! {
+    webhook_deliveries (id) {
+        id -> Int4,
+        webhook_id -> Int4,
+        event -> Text,
+        payload -> Jsonb,
+        status -> Text,
+        attempts -> Int4,
+        next_attempt_at -> Nullable<Timestamp>,
+        last_status_code -> Nullable<Int4>,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamp,
+        delivered_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    webhooks (id) {
+        id -> Int4,
+        app_id -> Int4,
+        url -> Text,
+        secret -> Text,
+        events -> Array<Text>,
+        active -> Bool,
+        created_at -> Timestamp,
     }
 }
 
+joinable!(app_api_keys -> apps (app_id));
+joinable!(app_carriers -> apps (app_id));
+joinable!(app_carriers -> carriers (carrier_id));
+joinable!(app_fixation_markups -> apps (app_id));
+joinable!(app_fixation_markups -> fixations (fixation_id));
 joinable!(app_fonts -> apps (app_id));
 joinable!(app_fonts -> fonts (font_id));
+joinable!(app_material_markups -> apps (app_id));
+joinable!(app_material_markups -> materials (material_id));
 joinable!(app_materials -> apps (app_id));
 joinable!(app_materials -> materials (material_id));
+joinable!(app_origins -> apps (app_id));
+joinable!(app_pictos -> apps (app_id));
+joinable!(app_pictos -> pictos (picto_id));
+joinable!(app_settings -> apps (app_id));
+joinable!(app_settings -> materials (product_id));
+joinable!(app_settings -> templates (template_id));
+joinable!(app_shapes -> apps (app_id));
+joinable!(app_shapes -> shapes (shape_id));
+joinable!(app_templates -> apps (app_id));
+joinable!(app_templates -> templates (template_id));
 joinable!(app_users -> apps (app_id));
 joinable!(app_users -> users (user_id));
 joinable!(fixation_conditions -> fixations (fixation_id));
@@ -214,30 +477,68 @@ joinable!(material_fixations -> fixations (fixation_id));
 joinable!(material_fixations -> materials (material_id));
 joinable!(material_shapes -> materials (material_id));
 joinable!(material_shapes -> shapes (shape_id));
+joinable!(order_items -> fixations (fixation_id));
+joinable!(order_items -> materials (material_id));
+joinable!(order_items -> orders (order_id));
+joinable!(order_items -> shapes (shape_id));
+joinable!(orders -> apps (app_id));
+joinable!(password_reset_tokens -> users (user_id));
 joinable!(pictos -> folders (folder_id));
+joinable!(quote_events -> apps (app_id));
+joinable!(quote_events -> fixations (fixation_id));
+joinable!(quote_events -> materials (material_id));
+joinable!(quote_events -> shapes (shape_id));
+joinable!(refresh_tokens -> users (user_id));
 joinable!(shapes -> folders (folder_id));
+joinable!(shipping_brackets -> carriers (carrier_id));
+joinable!(tax_rates -> apps (app_id));
+joinable!(tax_rates -> materials (material_id));
 joinable!(templates -> folders (folder_id));
+joinable!(webhook_deliveries -> webhooks (webhook_id));
+joinable!(webhooks -> apps (app_id));
 
 allow_tables_to_appear_in_same_query!(
+    app_api_keys,
+    app_carriers,
+    app_fixation_markups,
     app_fonts,
+    app_material_markups,
     app_materials,
+    app_origins,
+    app_pictos,
+    app_settings,
+    app_shapes,
+    app_templates,
     app_users,
     apps,
+    audit_logs,
     badges,
+    carriers,
     dimensions,
     discounts,
     fixation_conditions,
     fixations,
     folders,
     fonts,
+    holidays,
     material_badges,
     material_dimensions,
     material_discounts,
     material_fixations,
     material_shapes,
     materials,
+    order_items,
+    orders,
+    password_reset_tokens,
     pictos,
+    quote_events,
+    refresh_tokens,
     shapes,
+    shipping_brackets,
+    sign_in_throttles,
+    tax_rates,
     templates,
     users,
+    webhook_deliveries,
+    webhooks,
 );
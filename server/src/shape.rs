@@ -11,8 +11,10 @@ use log::error;
 use serde::{Deserialize, Serialize};
 use std::ops::Deref;
 
+use crate::app::{self, PublicApp};
 use crate::database;
 use crate::schema::shapes;
+use crate::shared::auth::{Admin, Claims};
 use crate::shared::{self, folder::Folderable};
 
 // Error management
@@ -31,6 +33,10 @@ error_chain! {
             description("Impossible de récupérer la forme")
             display("Could not find shape `{}`", id)
         }
+        ShapeNotInAppErr(id: i32) {
+            description("Cette forme n'est pas disponible pour l'application")
+            display("Shape `{}` is private to another app", id)
+        }
         InsertShapeErr(id: i32) {
             description("Impossible d'ajouter la forme")
             display("Could not insert shape `{}`", id)
@@ -45,6 +51,7 @@ error_chain! {
         }
     }
     links {
+        App(app::Error, app::ErrorKind);
         Folder(shared::folder::Error, shared::folder::ErrorKind);
     }
 }
@@ -59,6 +66,7 @@ impl actix_web::error::ResponseError for Error {
 
     fn status_code(&self) -> StatusCode {
         match *self.kind() {
+            ErrorKind::ShapeNotInAppErr(_) => StatusCode::NOT_FOUND,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -99,26 +107,59 @@ struct InsertableShape<'a> {
     pub url: &'a str,
 }
 
+// Helpers
+
+fn get_all_shapes(conn: &database::PooledConnection) -> Result<Vec<Shape>> {
+    use crate::schema::shapes::dsl::*;
+    shapes
+        .load::<Shape>(conn)
+        .chain_err(|| ErrorKind::SelectShapesErr)
+}
+
 // Services
 
 #[get("/shape")]
-async fn get_all(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
+async fn get_all(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let hidden_ids = app::get_hidden_shape_ids(&conn, app.id)?;
 
-    let shapes = {
-        use crate::schema::shapes::dsl::*;
-        shapes.load::<Shape>(&conn)
-    }
-    .chain_err(|| ErrorKind::SelectShapesErr)?;
+    let shapes: Vec<Shape> = get_all_shapes(&conn)?
+        .into_iter()
+        .filter(|shape| !hidden_ids.contains(&shape.id))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(shapes))
+}
+
+#[get("/shape")]
+async fn get_all_admin(pool: web::Data<database::Pool>, claims: Claims) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let hidden_ids = app::get_claims_hidden_shape_ids(&conn, &claims)?;
+
+    let shapes: Vec<Shape> = get_all_shapes(&conn)?
+        .into_iter()
+        .filter(|shape| !hidden_ids.contains(&shape.id))
+        .collect();
 
     Ok(HttpResponse::Ok().json(shapes))
 }
 
 #[get("/shape/{id}")]
-async fn get(pool: web::Data<database::Pool>, shape_id: web::Path<i32>) -> Result<HttpResponse> {
+async fn get(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+    shape_id: web::Path<i32>,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let shape_id = shape_id.into_inner();
 
+    if app::get_hidden_shape_ids(&conn, app.id)?.contains(&shape_id) {
+        return Err(ErrorKind::ShapeNotInAppErr(shape_id).into());
+    }
+
     let shape = if shape_id == 0 {
         Shape::default()
     } else {
@@ -133,14 +174,14 @@ async fn get(pool: web::Data<database::Pool>, shape_id: web::Path<i32>) -> Resul
 }
 
 #[get("/folded-shape")]
-async fn get_folded(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
+async fn get_folded(pool: web::Data<database::Pool>, claims: Claims) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let hidden_ids = app::get_claims_hidden_shape_ids(&conn, &claims)?;
 
-    let shapes = {
-        use crate::schema::shapes::dsl::*;
-        shapes.load::<Shape>(&conn)
-    }
-    .chain_err(|| ErrorKind::SelectShapesErr)?;
+    let shapes: Vec<Shape> = get_all_shapes(&conn)?
+        .into_iter()
+        .filter(|shape| !hidden_ids.contains(&shape.id))
+        .collect();
 
     let folder_ids = {
         use crate::schema::folders::dsl::*;
@@ -158,7 +199,11 @@ async fn get_folded(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
 }
 
 #[put("/shape")]
-async fn set(pool: web::Data<database::Pool>, shape: web::Json<Shape>) -> Result<HttpResponse> {
+async fn set(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    shape: web::Json<Shape>,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let shape_id = shape.id;
 
@@ -192,6 +237,7 @@ async fn set(pool: web::Data<database::Pool>, shape: web::Json<Shape>) -> Result
 #[delete("/shape/{id}")]
 async fn del(
     pool: web::Data<database::Pool>,
+    _: Admin,
     web::Path(id): web::Path<i32>,
 ) -> Result<HttpResponse> {
     use crate::schema::shapes::dsl::shapes;
@@ -209,5 +255,8 @@ pub fn pub_services(cfg: &mut web::ServiceConfig) {
 }
 
 pub fn priv_services(cfg: &mut web::ServiceConfig) {
-    cfg.service(get_folded).service(set).service(del);
+    cfg.service(get_all_admin)
+        .service(get_folded)
+        .service(set)
+        .service(del);
 }
@@ -1,29 +1,71 @@
 use actix_web::{
     self,
-    dev::{HttpResponseBuilder, ServiceRequest},
+    dev::{HttpResponseBuilder, Payload, ServiceRequest},
     get,
-    http::{header, StatusCode},
-    web, HttpResponse,
+    http::{header, Method, StatusCode},
+    web, FromRequest, HttpMessage, HttpRequest, HttpResponse,
 };
 use actix_web_httpauth::extractors::bearer::BearerAuth;
+use chrono::Utc;
+use diesel::{dsl::exists, prelude::*};
 use error_chain::error_chain;
 use jsonwebtoken as jwt;
 use log::error;
 use serde::{Deserialize, Serialize};
-use std::{env, result};
+use std::{
+    env,
+    future::{ready, Ready},
+    result,
+    str::FromStr,
+};
+
+use crate::{
+    api_key, database,
+    schema::{refresh_tokens, users},
+};
+
+/// Lifetime of an access token, in seconds.
+pub const ACCESS_TOKEN_TTL: i64 = 15 * 60;
 
 // Error management
 
 error_chain! {
+    links {
+        ApiKey(api_key::Error, api_key::ErrorKind);
+    }
     errors {
+        GetDbConnErr {
+            description("Impossible de se connecter à la base de données")
+            display("Could not get db conn from pool")
+        }
         DecodeJwtErr(jwt: String) {
             description("Jeton d'authentification invalide")
             display("Could not decode JWT `{}`", jwt)
         }
+        ExpiredJwtErr(sub: String) {
+            description("Jeton d'authentification expiré")
+            display("JWT of `{}` has expired", sub)
+        }
         CreateJwtErr {
             description("Impossible de générer le jeton d'authentification")
             display("Could not generate JWT")
         }
+        MissingClaimsErr {
+            description("Authentification requise")
+            display("Could not find claims in request")
+        }
+        CheckSessionErr(sub: String) {
+            description("Impossible de vérifier la session")
+            display("Could not check session of `{}`", sub)
+        }
+        RevokedSessionErr(sub: String) {
+            description("Session expirée, veuillez vous reconnecter")
+            display("Session of `{}` has been revoked", sub)
+        }
+        ForbiddenErr(sub: String) {
+            description("Accès refusé")
+            display("User `{}` is not allowed to perform this action", sub)
+        }
     }
 }
 
@@ -36,25 +78,208 @@ impl actix_web::error::ResponseError for Error {
     }
 
     fn status_code(&self) -> StatusCode {
-        match *self.kind() {
-            _ => StatusCode::INTERNAL_SERVER_ERROR,
-        }
+        status_code(self.kind())
+    }
+}
+
+pub fn status_code(kind: &ErrorKind) -> StatusCode {
+    match *kind {
+        ErrorKind::DecodeJwtErr(_)
+        | ErrorKind::ExpiredJwtErr(_)
+        | ErrorKind::RevokedSessionErr(_)
+        | ErrorKind::MissingClaimsErr => StatusCode::UNAUTHORIZED,
+        ErrorKind::ForbiddenErr(_) => StatusCode::FORBIDDEN,
+        ErrorKind::ApiKey(ref kind) => api_key::status_code(kind),
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
     }
 }
 
 // Models
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Admin,
+    #[default]
+    Member,
+    /// A backend authenticated by an API key, see `api_key::ApiKey`.
+    App,
+}
+
+/// What an API key is allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scope {
+    Quote,
+    OrderCreate,
+    CatalogRead,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Quote => "quote",
+            Self::OrderCreate => "order-create",
+            Self::CatalogRead => "catalog-read",
+        }
+    }
+
+    /// Finds the scope needed to call a service of the `/api` scope, which
+    /// exposes the catalog services to API keys. Other services cannot be
+    /// called with an API key.
+    pub fn required_for(method: &Method, path: &str) -> Option<Self> {
+        let path = path.strip_prefix("/api/")?;
+
+        match (method, path) {
+            (&Method::POST, "quote") | (&Method::GET, "order") => Some(Self::Quote),
+            (&Method::POST, "order") => Some(Self::OrderCreate),
+            (&Method::GET, _) => Some(Self::CatalogRead),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Scope {
+    type Err = ();
+
+    fn from_str(scope: &str) -> result::Result<Self, Self::Err> {
+        match scope {
+            "quote" => Ok(Self::Quote),
+            "order-create" => Ok(Self::OrderCreate),
+            "catalog-read" => Ok(Self::CatalogRead),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The JWT claims. Admins manage everything, members only manage the
+/// catalog of the `apps` they belong to. The memberships are the ones at
+/// sign in or refresh time. API keys get claims too, bound to their app and
+/// `scope`, without `iat` nor `exp` since they are not JWTs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default)]
+    pub apps: Vec<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Scope>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    /// The session of the JWT, which is its refresh token family.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// The sessions version of the user (`users.token`). Changing it revokes
+    /// all the sessions of the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ver: Option<String>,
 }
 
 impl Claims {
-    pub fn new(sub: i32) -> Self {
+    pub fn new(sub: i32, role: Role, apps: Vec<i32>) -> Self {
         Self {
             sub: sub.to_string(),
+            role,
+            apps,
+            scope: None,
+            iat: None,
+            exp: None,
+            sid: None,
+            ver: None,
+        }
+    }
+
+    pub fn with_session(self, sid: String, ver: String) -> Self {
+        Self {
+            sid: Some(sid),
+            ver: Some(ver),
+            ..self
+        }
+    }
+
+    /// The user id, the session and the sessions version of a JWT.
+    fn session(&self) -> Option<(i32, &str, &str)> {
+        let user_id = self.sub.parse::<i32>().ok()?;
+        Some((user_id, self.sid.as_deref()?, self.ver.as_deref()?))
+    }
+
+    /// Makes the claims valid for `ACCESS_TOKEN_TTL` seconds from `now`.
+    pub fn issued_at(self, now: i64) -> Self {
+        Self {
+            iat: Some(now),
+            exp: Some(now + ACCESS_TOKEN_TTL),
+            ..self
         }
     }
+
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::Admin
+    }
+
+    pub fn can_access_app(&self, app_id: i32) -> bool {
+        self.is_admin() || (self.role == Role::Member && self.apps.contains(&app_id))
+    }
+
+    /// The app of the API key the claims come from.
+    pub fn key_app_id(&self) -> Option<i32> {
+        match self.role {
+            Role::App => self.apps.first().copied(),
+            _ => None,
+        }
+    }
+
+    /// Refuses the action with a 403 unless `allowed`.
+    pub fn check(&self, allowed: bool) -> Result<()> {
+        match allowed {
+            true => Ok(()),
+            false => Err(ErrorKind::ForbiddenErr(self.sub.to_owned()).into()),
+        }
+    }
+
+    pub fn check_admin(&self) -> Result<()> {
+        self.check(self.is_admin())
+    }
+
+    pub fn check_app(&self, app_id: i32) -> Result<()> {
+        self.check(self.can_access_app(app_id))
+    }
+}
+
+/// The claims of the authenticated user, set by `bearer_validator`.
+impl FromRequest for Claims {
+    type Error = Error;
+    type Future = Ready<Result<Self>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<Claims>()
+                .cloned()
+                .ok_or_else(|| ErrorKind::MissingClaimsErr.into()),
+        )
+    }
+}
+
+/// Guards a service so that only admins can call it, refusing the request
+/// with a 403 otherwise.
+pub struct Admin;
+
+impl FromRequest for Admin {
+    type Error = Error;
+    type Future = Ready<Result<Self>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        ready(
+            Claims::from_request(req, payload)
+                .into_inner()
+                .and_then(|claims| claims.check_admin().map(|_| Admin)),
+        )
+    }
 }
 
 // Utils
@@ -63,40 +288,217 @@ pub fn secret() -> String {
     env::var("JWT_SECRET").unwrap_or("SECRET".to_string())
 }
 
-pub fn generate_jwt(sub: i32) -> Result<String> {
+/// Generates a short lived access token, to be renewed with a refresh token.
+pub fn generate_jwt(claims: Claims) -> Result<String> {
+    encode_jwt(&claims.issued_at(Utc::now().timestamp()))
+}
+
+fn encode_jwt(claims: &Claims) -> Result<String> {
     jwt::encode(
         &jwt::Header::default(),
-        &Claims::new(sub),
+        claims,
         &jwt::EncodingKey::from_secret(secret().as_bytes()),
     )
     .chain_err(|| ErrorKind::CreateJwtErr)
 }
 
+fn decode_jwt(token: &str) -> Result<Claims> {
+    let claims = jwt::decode::<Claims>(
+        token,
+        &jwt::DecodingKey::from_secret(secret().as_bytes()),
+        &jwt::Validation::default(),
+    )
+    .map_err(|err| match err.kind() {
+        jwt::errors::ErrorKind::ExpiredSignature => {
+            let sub = jwt::dangerous_insecure_decode::<Claims>(token)
+                .map(|data| data.claims.sub)
+                .unwrap_or_default();
+            Error::from(ErrorKind::ExpiredJwtErr(sub))
+        }
+        _ => Error::with_chain(err, ErrorKind::DecodeJwtErr(token.to_owned())),
+    })?
+    .claims;
+
+    // Only API keys can bear app claims.
+    match claims.role {
+        Role::App => Err(ErrorKind::DecodeJwtErr(token.to_owned()).into()),
+        _ => Ok(claims),
+    }
+}
+
+fn get_conn(req: &ServiceRequest) -> Result<database::PooledConnection> {
+    req.app_data::<web::Data<database::Pool>>()
+        .ok_or(ErrorKind::GetDbConnErr)?
+        .get()
+        .chain_err(|| ErrorKind::GetDbConnErr)
+}
+
+/// Refuses the JWTs of the sessions signed out, and the ones issued before
+/// the sessions version of the user changed. Deleted users have no
+/// sessions version anymore.
+fn check_session(req: &ServiceRequest, claims: &Claims) -> Result<()> {
+    let revoked = || Error::from(ErrorKind::RevokedSessionErr(claims.sub.to_owned()));
+    let (user_id, sid, ver) = claims.session().ok_or_else(revoked)?;
+    let conn = get_conn(req)?;
+
+    let user_ver = users::table
+        .find(user_id)
+        .select(users::token)
+        .first::<Option<String>>(&conn)
+        .optional()
+        .chain_err(|| ErrorKind::CheckSessionErr(claims.sub.to_owned()))?
+        .flatten();
+
+    if user_ver.as_deref() != Some(ver) {
+        return Err(revoked());
+    }
+
+    let signed_out = diesel::select(exists(
+        refresh_tokens::table
+            .filter(refresh_tokens::family.eq(sid))
+            .filter(refresh_tokens::revoked_at.is_not_null()),
+    ))
+    .get_result::<bool>(&conn)
+    .chain_err(|| ErrorKind::CheckSessionErr(claims.sub.to_owned()))?;
+
+    match signed_out {
+        true => Err(revoked()),
+        false => Ok(()),
+    }
+}
+
+fn decode_api_key(req: &ServiceRequest, token: &str) -> Result<Claims> {
+    let conn = get_conn(req)?;
+    let claims = api_key::authenticate(&conn, token)?.claims()?;
+
+    let scope = Scope::required_for(req.method(), req.path());
+    claims.check(scope.is_some() && scope == claims.scope)?;
+    Ok(claims)
+}
+
 // Services
 
+/// Authenticates the private services, either with a JWT or with an API
+/// key.
 pub async fn bearer_validator(
     req: ServiceRequest,
     credentials: BearerAuth,
 ) -> result::Result<ServiceRequest, actix_web::Error> {
     let token = credentials.token();
-    let mut validation = jwt::Validation::default();
-    validation.validate_exp = false;
 
-    jwt::decode::<Claims>(
-        &token,
-        &jwt::DecodingKey::from_secret(secret().as_bytes()),
-        &validation,
-    )
-    .chain_err(|| ErrorKind::DecodeJwtErr(token.to_owned()))?;
+    let claims = if api_key::is_api_key(token) {
+        decode_api_key(&req, token)?
+    } else {
+        let claims = decode_jwt(token)?;
+        check_session(&req, &claims)?;
+        claims
+    };
 
+    req.extensions_mut().insert(claims);
     Ok(req)
 }
 
 #[get("/auth-check")]
-async fn check_service() -> HttpResponse {
-    HttpResponse::NoContent().finish()
+async fn check_service(claims: Claims) -> HttpResponse {
+    HttpResponse::Ok().json(claims)
 }
 
 pub fn services(cfg: &mut web::ServiceConfig) {
     cfg.service(check_service);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admins_access_every_app() {
+        let claims = Claims::new(1, Role::Admin, vec![]);
+        assert!(claims.check_admin().is_ok());
+        assert!(claims.check_app(42).is_ok());
+    }
+
+    #[test]
+    fn members_only_access_their_apps() {
+        let claims = Claims::new(2, Role::Member, vec![1, 3]);
+        assert!(claims.check_app(3).is_ok());
+        assert!(matches!(
+            claims.check_app(2).unwrap_err().kind(),
+            ErrorKind::ForbiddenErr(sub) if sub == "2"
+        ));
+        assert_eq!(
+            status_code(claims.check_admin().unwrap_err().kind()),
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[test]
+    fn api_keys_only_access_their_scope() {
+        let scope = |method: &Method, path: &str| Scope::required_for(method, path);
+        assert_eq!(scope(&Method::POST, "/api/quote"), Some(Scope::Quote));
+        assert_eq!(scope(&Method::GET, "/api/order"), Some(Scope::Quote));
+        assert_eq!(scope(&Method::POST, "/api/order"), Some(Scope::OrderCreate));
+        assert_eq!(
+            scope(&Method::GET, "/api/material/1"),
+            Some(Scope::CatalogRead)
+        );
+        assert_eq!(scope(&Method::GET, "/material/1"), None);
+        assert_eq!(scope(&Method::DELETE, "/api/material/1"), None);
+        assert_eq!("order-create".parse::<Scope>(), Ok(Scope::OrderCreate));
+    }
+
+    #[test]
+    fn api_keys_are_not_members() {
+        let claims = Claims {
+            scope: Some(Scope::CatalogRead),
+            ..Claims::new(4, Role::App, vec![1])
+        };
+        assert_eq!(claims.key_app_id(), Some(1));
+        assert!(!claims.can_access_app(1));
+        assert_eq!(Claims::new(2, Role::Member, vec![1]).key_app_id(), None);
+    }
+
+    #[test]
+    fn decodes_fresh_jwts() {
+        let token = generate_jwt(Claims::new(5, Role::Member, vec![2])).unwrap();
+        let claims = decode_jwt(&token).unwrap();
+        assert_eq!(claims.sub, "5");
+        assert_eq!(claims.apps, vec![2]);
+        assert_eq!(claims.exp, claims.iat.map(|iat| iat + ACCESS_TOKEN_TTL));
+    }
+
+    #[test]
+    fn refuses_expired_jwts() {
+        let issued_at = Utc::now().timestamp() - 2 * ACCESS_TOKEN_TTL;
+        let claims = Claims::new(5, Role::Admin, vec![]).issued_at(issued_at);
+        let err = decode_jwt(&encode_jwt(&claims).unwrap()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::ExpiredJwtErr(sub) if sub == "5"));
+        assert_eq!(status_code(err.kind()), StatusCode::UNAUTHORIZED);
+
+        // Tokens issued before expiry was enforced have no `exp`.
+        let claims = Claims::new(5, Role::Admin, vec![]);
+        assert!(decode_jwt(&encode_jwt(&claims).unwrap()).is_err());
+    }
+
+    #[test]
+    fn jwts_without_session_are_revoked() {
+        let claims = Claims::new(5, Role::Admin, vec![]);
+        assert_eq!(claims.session(), None);
+
+        let claims = claims.with_session(String::from("sid"), String::from("ver"));
+        assert_eq!(claims.session(), Some((5, "sid", "ver")));
+
+        let claims = Claims {
+            sub: String::from("ck_prefix"),
+            ..claims
+        };
+        assert_eq!(claims.session(), None);
+    }
+
+    #[test]
+    fn defaults_to_member_without_apps() {
+        let claims: Claims = serde_json::from_str(r#"{"sub":"3"}"#).unwrap();
+        assert_eq!(claims.role, Role::Member);
+        assert!(!claims.can_access_app(1));
+    }
+}
@@ -0,0 +1,112 @@
+use diesel::prelude::*;
+use error_chain::error_chain;
+use std::{
+    env,
+    sync::{Arc, RwLock},
+};
+
+use crate::database;
+
+// Error management
+
+error_chain! {
+    errors {
+        SelectAppOriginsErr {
+            description("Impossible de récupérer la liste des origines autorisées")
+            display("Could not select app_origins")
+        }
+    }
+}
+
+// Models
+
+/// In-memory cache of the origins allowed to call the private API: the
+/// origins attached to the apps, plus the ones listed in the `CORS_ORIGINS`
+/// env var (comma separated, e.g. for the admin). It must be refreshed each
+/// time the apps change.
+#[derive(Debug, Clone, Default)]
+pub struct AllowedOrigins(Arc<RwLock<Vec<String>>>);
+
+impl AllowedOrigins {
+    pub fn load(conn: &database::PooledConnection) -> Result<Self> {
+        let origins = Self::default();
+        origins.refresh(conn)?;
+        Ok(origins)
+    }
+
+    pub fn refresh(&self, conn: &database::PooledConnection) -> Result<()> {
+        let mut origins = {
+            use crate::schema::app_origins::dsl;
+            dsl::app_origins
+                .select(dsl::origin)
+                .distinct()
+                .load::<String>(conn)
+        }
+        .chain_err(|| ErrorKind::SelectAppOriginsErr)?;
+
+        origins.extend(env_origins());
+        *self.0.write().unwrap() = origins;
+        Ok(())
+    }
+
+    pub fn is_allowed(&self, origin: &[u8]) -> bool {
+        match std::str::from_utf8(origin) {
+            Ok(origin) => is_allowed(&self.0.read().unwrap(), origin),
+            Err(_) => false,
+        }
+    }
+}
+
+// Helpers
+
+fn env_origins() -> Vec<String> {
+    env::var("CORS_ORIGINS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Checks the host of the `Origin` header against the allowed domains. A
+/// domain also allows its subdomains.
+pub fn is_allowed(domains: &[String], origin: &str) -> bool {
+    let host = origin.split("://").last().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default();
+
+    domains.iter().any(|domain| {
+        let domain = domain.split("://").last().unwrap_or_default();
+        host == domain
+            || host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.ends_with('.'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn domains() -> Vec<String> {
+        vec![
+            String::from("pictosigns.com"),
+            String::from("https://shop.fr"),
+        ]
+    }
+
+    #[test]
+    fn allows_domains_and_subdomains() {
+        assert!(is_allowed(&domains(), "https://pictosigns.com"));
+        assert!(is_allowed(&domains(), "https://admin.pictosigns.com"));
+        assert!(is_allowed(&domains(), "http://shop.fr:8080"));
+    }
+
+    #[test]
+    fn refuses_other_domains() {
+        assert!(!is_allowed(&domains(), "https://fakepictosigns.com"));
+        assert!(!is_allowed(&domains(), "https://pictosigns.com.evil.io"));
+        assert!(!is_allowed(&domains(), "https://other.fr"));
+        assert!(!is_allowed(&[], "https://pictosigns.com"));
+    }
+}
@@ -13,6 +13,7 @@ use std::ops::Deref;
 
 use crate::database;
 use crate::schema::folders;
+use crate::shared::auth::Admin;
 
 // Error management
 
@@ -166,7 +167,11 @@ async fn get(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
 }
 
 #[put("/folder")]
-async fn set(pool: web::Data<database::Pool>, folder: web::Json<Folder>) -> Result<HttpResponse> {
+async fn set(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    folder: web::Json<Folder>,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let folder_id = folder.id;
 
@@ -200,6 +205,7 @@ async fn set(pool: web::Data<database::Pool>, folder: web::Json<Folder>) -> Resu
 #[delete("/folder/{id}")]
 async fn del(
     pool: web::Data<database::Pool>,
+    _: Admin,
     web::Path(id): web::Path<i32>,
 ) -> Result<HttpResponse> {
     use crate::schema::folders::dsl::folders;
@@ -46,7 +46,20 @@ impl actix_err::ResponseError for Error {
 
 // Services
 
+/// Limit of JSON bodies, actix's default.
+pub const PAYLOAD_LIMIT: usize = 32 * 1024;
+
+/// Orders embed the SVG, the config and the PNG preview of each design,
+/// which do not fit in `PAYLOAD_LIMIT`.
+pub const ORDER_PAYLOAD_LIMIT: usize = 16 * 1024 * 1024;
+
 fn error_handler(err: actix_err::JsonPayloadError, _req: &HttpRequest) -> actix_err::Error {
+    payload_error(err)
+}
+
+/// Turns a JSON payload error into the response of the JSON extractor, for
+/// the middlewares reading bodies.
+pub fn payload_error(err: actix_err::JsonPayloadError) -> actix_err::Error {
     use actix_err::JsonPayloadError::*;
 
     let detail = err.to_string();
@@ -61,5 +74,17 @@ fn error_handler(err: actix_err::JsonPayloadError, _req: &HttpRequest) -> actix_
 }
 
 pub fn payload_error_management(cfg: &mut web::ServiceConfig) {
-    cfg.app_data(web::JsonConfig::default().error_handler(error_handler));
+    cfg.app_data(
+        web::JsonConfig::default()
+            .limit(PAYLOAD_LIMIT)
+            .error_handler(error_handler),
+    );
+}
+
+/// Raises the limit of the routes receiving designs only, see
+/// `ORDER_PAYLOAD_LIMIT`.
+pub fn order_payload_config() -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(ORDER_PAYLOAD_LIMIT)
+        .error_handler(error_handler)
 }
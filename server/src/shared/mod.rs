@@ -1,5 +1,9 @@
 pub(crate) mod auth;
+pub(crate) mod cors;
 pub(crate) mod folder;
 pub(crate) mod json;
+pub(crate) mod mailer;
+pub(crate) mod money;
 pub(crate) mod subset;
+pub(crate) mod token;
 pub(crate) mod upload;
@@ -0,0 +1,160 @@
+use diesel::{
+    deserialize::{self, FromSql},
+    pg::Pg,
+    serialize::{self, Output, ToSql},
+    sql_types::BigInt,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    io::Write,
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, Sub},
+};
+
+/// An amount of money stored as an integer number of cents, so that sums
+/// and products never drift. It is exchanged in JSON as a decimal number of
+/// euros, which keeps the API compatible with the admin and the editor.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, AsExpression, FromSqlRow,
+)]
+#[sql_type = "BigInt"]
+pub struct Money(pub i64);
+
+impl Money {
+    pub fn from_cents(cents: i64) -> Self {
+        Self(cents)
+    }
+
+    pub fn from_euros(euros: f64) -> Self {
+        Self((euros * 100.0).round() as i64)
+    }
+
+    pub fn cents(self) -> i64 {
+        self.0
+    }
+
+    pub fn euros(self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+
+    /// Applies a ratio `num / den` to the amount, rounding half away from
+    /// zero to the nearest cent.
+    pub fn ratio(self, num: i64, den: i64) -> Self {
+        Self(div_round(self.0 as i128 * num as i128, den as i128) as i64)
+    }
+
+    /// Multiplies the amount, `None` if it overflows.
+    pub fn checked_mul(self, rhs: i64) -> Option<Self> {
+        self.0.checked_mul(rhs).map(Self)
+    }
+}
+
+pub(crate) fn div_round(num: i128, den: i128) -> i128 {
+    let half = den.abs() / 2;
+    if (num < 0) == (den < 0) {
+        (num.abs() + half) / den.abs()
+    } else {
+        -((num.abs() + half) / den.abs())
+    }
+}
+
+impl Add for Money {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl Mul<i64> for Money {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self {
+        Self(self.0 * rhs)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.euros())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Self::from_euros)
+    }
+}
+
+impl ToSql<BigInt, Pg> for Money {
+    fn to_sql<W: Write>(&self, out: &mut Output<W, Pg>) -> serialize::Result {
+        ToSql::<BigInt, Pg>::to_sql(&self.0, out)
+    }
+}
+
+impl FromSql<BigInt, Pg> for Money {
+    fn from_sql(bytes: Option<&[u8]>) -> deserialize::Result<Self> {
+        <i64 as FromSql<BigInt, Pg>>::from_sql(bytes).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_euros_to_the_nearest_cent() {
+        assert_eq!(Money::from_euros(12.34), Money(1234));
+        assert_eq!(Money::from_euros(0.29), Money(29));
+        assert_eq!(Money::from_euros(0.005), Money(1));
+        assert_eq!(Money::from_euros(-1.5), Money(-150));
+    }
+
+    #[test]
+    fn applies_ratios_rounding_half_away_from_zero() {
+        assert_eq!(Money(999).ratio(550, 10000), Money(55));
+        assert_eq!(Money(10).ratio(1, 4), Money(3));
+        assert_eq!(Money(10).ratio(1, 3), Money(3));
+        assert_eq!(Money(-10).ratio(1, 4), Money(-3));
+    }
+
+    #[test]
+    fn serializes_as_euros() {
+        assert_eq!(serde_json::to_string(&Money(1234)).unwrap(), "12.34");
+        assert_eq!(serde_json::to_string(&Money(5)).unwrap(), "0.05");
+        assert_eq!(serde_json::from_str::<Money>("12.3").unwrap(), Money(1230));
+        assert_eq!(serde_json::from_str::<Money>("7").unwrap(), Money(700));
+    }
+
+    #[test]
+    fn sums_without_drift() {
+        let total: Money = [Money::from_euros(0.1); 10].iter().sum();
+        assert_eq!(total, Money::from_euros(1.0));
+    }
+}
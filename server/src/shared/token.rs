@@ -0,0 +1,25 @@
+use ring::digest;
+
+/// Hashes a random token (API key secret, refresh token, reset token) for
+/// storage. The tokens are random, a fast hash is enough to protect them.
+pub fn hash(token: &str) -> String {
+    digest::digest(&digest::SHA256, token.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_to_hex_sha256() {
+        assert_eq!(
+            hash("secret"),
+            "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b"
+        );
+        assert_ne!(hash("secret"), hash("Secret"));
+    }
+}
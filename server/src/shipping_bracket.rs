@@ -0,0 +1,85 @@
+use diesel::prelude::*;
+use error_chain::error_chain;
+use serde::{Deserialize, Serialize};
+
+use crate::carrier::Carrier;
+use crate::database;
+use crate::schema::shipping_brackets;
+use crate::shared::money::Money;
+
+// Error management
+
+error_chain! {
+    errors {
+        SelectShippingBracketsErr(id: i32) {
+            description("Impossible de récupérer la grille tarifaire du transporteur")
+            display("Could not select shipping_brackets `{}`", id)
+        }
+        InsertShippingBracketsErr(id: i32) {
+            description("Impossible d'enregistrer la grille tarifaire du transporteur")
+            display("Could not insert shipping_brackets `{}`", id)
+        }
+        DeleteShippingBracketsErr(id: i32) {
+            description("Impossible de supprimer la grille tarifaire du transporteur")
+            display("Could not delete shipping_brackets `{}`", id)
+        }
+    }
+}
+
+// Models
+
+/// The price of a parcel weighing up to `max_weight` kg (included).
+#[derive(Debug, Clone, Identifiable, Queryable, Associations, Serialize, Deserialize)]
+#[belongs_to(Carrier)]
+#[serde(rename_all = "camelCase")]
+pub struct ShippingBracket {
+    #[serde(default)]
+    pub id: i32,
+    #[serde(default)]
+    pub carrier_id: i32,
+    pub max_weight: f32,
+    pub price: Money,
+}
+
+#[derive(Insertable)]
+#[table_name = "shipping_brackets"]
+struct InsertableShippingBracket<'a> {
+    pub carrier_id: &'a i32,
+    pub max_weight: &'a f32,
+    pub price: &'a Money,
+}
+
+// Services
+
+pub fn set(
+    conn: &database::PooledConnection,
+    carrier_id: i32,
+    brackets: &[ShippingBracket],
+) -> Result<()> {
+    let brackets: Vec<InsertableShippingBracket> = brackets
+        .iter()
+        .map(|bracket| InsertableShippingBracket {
+            carrier_id: &carrier_id,
+            max_weight: &bracket.max_weight,
+            price: &bracket.price,
+        })
+        .collect();
+
+    diesel::insert_into(shipping_brackets::table)
+        .values(&brackets)
+        .execute(conn)
+        .chain_err(|| ErrorKind::InsertShippingBracketsErr(carrier_id))?;
+
+    Ok(())
+}
+
+pub fn del(conn: &database::PooledConnection, carrier_id: i32) -> Result<()> {
+    use crate::schema::shipping_brackets::dsl;
+
+    diesel::delete(shipping_brackets::table)
+        .filter(dsl::carrier_id.eq(carrier_id))
+        .execute(conn)
+        .chain_err(|| ErrorKind::DeleteShippingBracketsErr(carrier_id))?;
+
+    Ok(())
+}
@@ -0,0 +1,404 @@
+use actix_web::{
+    self,
+    dev::HttpResponseBuilder,
+    http::{header, StatusCode},
+    HttpRequest, HttpResponse,
+};
+use chrono::{Duration, Local, NaiveDateTime};
+use diesel::prelude::*;
+use error_chain::error_chain;
+use log::{error, info, warn};
+use std::env;
+use std::fmt;
+use std::net::IpAddr;
+
+use crate::database;
+use crate::schema::sign_in_throttles;
+
+/// Failures allowed before slowing sign ins down.
+const FREE_FAILURES: i32 = 3;
+/// Failures after which the subject is locked out.
+const LOCKOUT_FAILURES: i32 = 10;
+const LOCKOUT_MINUTES: i64 = 15;
+/// Failures older than that are forgotten.
+const FAILURES_WINDOW_MINUTES: i64 = 60;
+
+// Error management
+
+error_chain! {
+    errors {
+        CheckSignInThrottleErr(subject: String) {
+            description("Impossible de vérifier les tentatives de connexion")
+            display("Could not check sign in throttle of `{}`", subject)
+        }
+        LockedSignInErr(scope: Scope, subject: String, until: NaiveDateTime) {
+            description("Trop de tentatives, veuillez réessayer plus tard")
+            display("Attempts of {} `{}` locked until {}", scope, subject, until)
+        }
+        RecordSignInFailureErr(scope: Scope, subject: String) {
+            description("Impossible d'enregistrer la tentative de connexion")
+            display("Could not record sign in failure of {} `{}`", scope, subject)
+        }
+        UnlockSignInErr(subject: String) {
+            description("Impossible de déverrouiller l'utilisateur")
+            display("Could not unlock sign in of `{}`", subject)
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        HttpResponseBuilder::new(self.status_code())
+            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.description().to_owned())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        status_code(self.kind())
+    }
+}
+
+pub fn status_code(kind: &ErrorKind) -> StatusCode {
+    match *kind {
+        ErrorKind::LockedSignInErr(..) => StatusCode::TOO_MANY_REQUESTS,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+// Models
+
+/// What failed sign ins are counted by: the username tried, and the address
+/// trying it. Password reset requests are counted apart, by the login asked
+/// and the address asking.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    Username,
+    Ip,
+    ResetLogin,
+    ResetIp,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Username => "username",
+            Scope::Ip => "ip",
+            Scope::ResetLogin => "reset_login",
+            Scope::ResetIp => "reset_ip",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "sign_in_throttles"]
+struct InsertableSignInThrottle<'a> {
+    pub scope: &'a str,
+    pub subject: &'a str,
+}
+
+// Helpers
+
+/// Gets the proxies listed in the `TRUSTED_PROXIES` env var (comma
+/// separated addresses), whose `X-Forwarded-For` header is believed.
+fn trusted_proxies() -> Vec<IpAddr> {
+    env::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|addr| addr.trim().parse::<IpAddr>().ok())
+        .collect()
+}
+
+/// Finds the client among the peer and the addresses it forwards for: the
+/// last one not being a trusted proxy, since the ones before it may be
+/// forged by the client.
+fn resolve_ip(peer: Option<IpAddr>, forwarded_for: Option<&str>, trusted: &[IpAddr]) -> String {
+    let peer = match peer {
+        Some(peer) => peer,
+        None => return String::from("unknown"),
+    };
+
+    if !trusted.contains(&peer) {
+        return peer.to_string();
+    }
+
+    forwarded_for
+        .unwrap_or_default()
+        .rsplit(',')
+        .map_while(|addr| addr.trim().parse::<IpAddr>().ok())
+        .find(|addr| !trusted.contains(addr))
+        .unwrap_or(peer)
+        .to_string()
+}
+
+fn request_ip(req: &HttpRequest, trusted: &[IpAddr]) -> String {
+    let forwarded_for = req
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|header| header.to_str().ok());
+
+    resolve_ip(
+        req.peer_addr().map(|addr| addr.ip()),
+        forwarded_for,
+        trusted,
+    )
+}
+
+/// Gets the address of the client: the peer, or the address it forwards for
+/// if it is a trusted proxy.
+pub fn client_ip(req: &HttpRequest) -> String {
+    request_ip(req, &trusted_proxies())
+}
+
+/// Gets the delay to wait after the given number of consecutive failures:
+/// none for the first ones, then doubling from a second, then a lockout.
+fn delay(failures: i32) -> Option<Duration> {
+    if failures >= LOCKOUT_FAILURES {
+        Some(Duration::minutes(LOCKOUT_MINUTES))
+    } else if failures > FREE_FAILURES {
+        Some(Duration::seconds(1 << (failures - FREE_FAILURES - 1)))
+    } else {
+        None
+    }
+}
+
+/// Computes the failures count and the lock of a subject failing at `now`,
+/// given its previous failures.
+fn next_failure(
+    (failures, last_failed_at): (i32, NaiveDateTime),
+    now: NaiveDateTime,
+) -> (i32, Option<NaiveDateTime>) {
+    let window = Duration::minutes(FAILURES_WINDOW_MINUTES);
+    let failures = if last_failed_at + window < now {
+        1
+    } else {
+        failures + 1
+    };
+
+    (failures, delay(failures).map(|delay| now + delay))
+}
+
+type Subjects<'a> = [(Scope, &'a str); 2];
+
+fn sign_in_subjects<'a>(username: &'a str, ip: &'a str) -> Subjects<'a> {
+    [(Scope::Username, username), (Scope::Ip, ip)]
+}
+
+fn reset_subjects<'a>(login: &'a str, ip: &'a str) -> Subjects<'a> {
+    [(Scope::ResetLogin, login), (Scope::ResetIp, ip)]
+}
+
+/// Refuses the sign in if the username or the address is locked.
+pub fn check(conn: &database::PooledConnection, username: &str, ip: &str) -> Result<()> {
+    check_subjects(conn, sign_in_subjects(username, ip))
+}
+
+/// Counts a failed sign in against both the username and the address.
+pub fn record_failure(conn: &database::PooledConnection, username: &str, ip: &str) -> Result<()> {
+    record_subjects(conn, sign_in_subjects(username, ip))
+}
+
+/// Refuses the password reset request if the login or the address asked for
+/// too many already.
+pub fn check_reset(conn: &database::PooledConnection, login: &str, ip: &str) -> Result<()> {
+    check_subjects(conn, reset_subjects(login, ip))
+}
+
+/// Counts a password reset request, whether the login exists or not, so that
+/// throttling does not tell either.
+pub fn record_reset(conn: &database::PooledConnection, login: &str, ip: &str) -> Result<()> {
+    record_subjects(conn, reset_subjects(login, ip))
+}
+
+fn check_subjects(conn: &database::PooledConnection, subjects: Subjects) -> Result<()> {
+    use crate::schema::sign_in_throttles::dsl;
+    let now = Local::now().naive_local();
+
+    for (scope, subject) in subjects.iter() {
+        let locked_until = dsl::sign_in_throttles
+            .select(dsl::locked_until)
+            .find((scope.as_str(), subject))
+            .first::<Option<NaiveDateTime>>(conn)
+            .optional()
+            .chain_err(|| ErrorKind::CheckSignInThrottleErr(subject.to_string()))?
+            .flatten();
+
+        if let Some(until) = locked_until.filter(|until| *until > now) {
+            return Err(ErrorKind::LockedSignInErr(*scope, subject.to_string(), until).into());
+        }
+    }
+
+    Ok(())
+}
+
+fn record_subjects(conn: &database::PooledConnection, subjects: Subjects) -> Result<()> {
+    use crate::schema::sign_in_throttles::dsl;
+    let now = Local::now().naive_local();
+
+    for (scope, subject) in subjects.iter() {
+        let (failures, locked_until) = conn
+            .transaction::<_, diesel::result::Error, _>(|| {
+                diesel::insert_into(dsl::sign_in_throttles)
+                    .values(&InsertableSignInThrottle {
+                        scope: scope.as_str(),
+                        subject,
+                    })
+                    .on_conflict((dsl::scope, dsl::subject))
+                    .do_nothing()
+                    .execute(conn)?;
+
+                // Locked, so that concurrent failures are all counted.
+                let prev = dsl::sign_in_throttles
+                    .select((dsl::failures, dsl::last_failed_at))
+                    .find((scope.as_str(), subject))
+                    .for_update()
+                    .first::<(i32, NaiveDateTime)>(conn)?;
+
+                let (failures, locked_until) = next_failure(prev, now);
+                diesel::update(dsl::sign_in_throttles.find((scope.as_str(), subject)))
+                    .set((
+                        dsl::failures.eq(failures),
+                        dsl::last_failed_at.eq(now),
+                        dsl::locked_until.eq(locked_until),
+                    ))
+                    .execute(conn)?;
+
+                Ok((failures, locked_until))
+            })
+            .chain_err(|| ErrorKind::RecordSignInFailureErr(*scope, subject.to_string()))?;
+
+        if let Some(until) = locked_until.filter(|_| failures >= LOCKOUT_FAILURES) {
+            warn!(
+                "Attempts of {} `{}` locked until {} after {} failures",
+                scope, subject, until, failures
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Forgets the failures of a username and of the address it signed in
+/// from, after a successful sign in.
+pub fn record_success(conn: &database::PooledConnection, username: &str, ip: &str) -> Result<()> {
+    unlock_subjects(conn, &sign_in_subjects(username, ip))
+}
+
+/// Forgets the failures of a username, when an admin unlocks it or when its
+/// password is reset.
+pub fn unlock(conn: &database::PooledConnection, username: &str) -> Result<()> {
+    unlock_subjects(conn, &[(Scope::Username, username)])
+}
+
+fn unlock_subjects(conn: &database::PooledConnection, subjects: &[(Scope, &str)]) -> Result<()> {
+    use crate::schema::sign_in_throttles::dsl;
+
+    for (scope, subject) in subjects.iter() {
+        let unlocked = diesel::delete(dsl::sign_in_throttles.find((scope.as_str(), subject)))
+            .execute(conn)
+            .chain_err(|| ErrorKind::UnlockSignInErr(subject.to_string()))?;
+
+        if unlocked > 0 {
+            info!("Sign in of {} `{}` unlocked", scope, subject);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use std::net::SocketAddr;
+
+    #[test]
+    fn backs_off_exponentially_then_locks_out() {
+        let delays = (1..=11)
+            .map(|failures| delay(failures).map(|delay| delay.num_seconds()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            delays,
+            vec![
+                None,
+                None,
+                None,
+                Some(1),
+                Some(2),
+                Some(4),
+                Some(8),
+                Some(16),
+                Some(32),
+                Some(LOCKOUT_MINUTES * 60),
+                Some(LOCKOUT_MINUTES * 60),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_recent_failures_only() {
+        let now = Local::now().naive_local();
+
+        let recent = (LOCKOUT_FAILURES - 1, now - Duration::minutes(1));
+        assert_eq!(
+            next_failure(recent, now),
+            (
+                LOCKOUT_FAILURES,
+                Some(now + Duration::minutes(LOCKOUT_MINUTES))
+            )
+        );
+
+        let old = (LOCKOUT_FAILURES - 1, now - Duration::hours(2));
+        assert_eq!(next_failure(old, now), (1, None));
+    }
+
+    #[test]
+    fn gets_client_ip_without_port() {
+        let req = TestRequest::default()
+            .peer_addr("127.0.0.1:4242".parse::<SocketAddr>().unwrap())
+            .header("x-forwarded-for", "203.0.113.7")
+            .to_http_request();
+        assert_eq!(request_ip(&req, &[]), "127.0.0.1");
+        assert_eq!(
+            request_ip(&req, &["127.0.0.1".parse().unwrap()]),
+            "203.0.113.7"
+        );
+    }
+
+    #[test]
+    fn believes_trusted_proxies_only() {
+        let ip = |addr: &str| addr.parse::<IpAddr>().unwrap();
+        let trusted = [ip("10.0.0.1"), ip("10.0.0.2")];
+
+        assert_eq!(
+            resolve_ip(Some(ip("198.51.100.4")), Some("203.0.113.7"), &trusted),
+            "198.51.100.4"
+        );
+        assert_eq!(
+            resolve_ip(Some(ip("10.0.0.1")), Some("203.0.113.7"), &trusted),
+            "203.0.113.7"
+        );
+        // Addresses prepended by the client are ignored.
+        assert_eq!(
+            resolve_ip(
+                Some(ip("10.0.0.1")),
+                Some("192.0.2.1, 203.0.113.7, 10.0.0.2"),
+                &trusted
+            ),
+            "203.0.113.7"
+        );
+        assert_eq!(
+            resolve_ip(Some(ip("10.0.0.1")), Some("garbage"), &trusted),
+            "10.0.0.1"
+        );
+        assert_eq!(resolve_ip(Some(ip("10.0.0.1")), None, &trusted), "10.0.0.1");
+        assert_eq!(resolve_ip(None, Some("203.0.113.7"), &trusted), "unknown");
+    }
+}
@@ -0,0 +1,239 @@
+use actix_web::{
+    self, delete,
+    dev::HttpResponseBuilder,
+    get,
+    http::{header, StatusCode},
+    put, web, HttpResponse,
+};
+use diesel::prelude::*;
+use error_chain::error_chain;
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::env;
+
+use crate::database;
+use crate::schema::tax_rates;
+use crate::shared::auth::{self, Claims};
+
+// Error management
+
+error_chain! {
+    links {
+        Auth(auth::Error, auth::ErrorKind);
+    }
+    errors {
+        GetDbConnErr {
+            description("Impossible de se connecter à la base de données")
+            display("Could not get db conn from pool")
+        }
+        SelectTaxRatesErr {
+            description("Impossible de récupérer la liste des taux de taxe")
+            display("Could not select tax rates")
+        }
+        FindTaxRateByIdErr(id: i32) {
+            description("Impossible de récupérer le taux de taxe")
+            display("Could not find tax rate `{}`", id)
+        }
+        FindTaxRateErr(country: String) {
+            description("Aucun taux de taxe n'est défini pour le pays de destination")
+            display("Could not find tax rate for country `{}`", country)
+        }
+        InsertTaxRateErr(id: i32) {
+            description("Impossible de créer le taux de taxe")
+            display("Could not insert tax rate `{}`", id)
+        }
+        UpdateTaxRateErr(id: i32) {
+            description("Impossible de modifier le taux de taxe")
+            display("Could not update tax rate `{}`", id)
+        }
+        DeleteTaxRateErr(id: i32) {
+            description("Impossible de supprimer le taux de taxe")
+            display("Could not delete tax rate `{}`", id)
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        HttpResponseBuilder::new(self.status_code())
+            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.description().to_owned())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match *self.kind() {
+            ErrorKind::FindTaxRateErr(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorKind::Auth(ref kind) => auth::status_code(kind),
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+// Models
+
+/// A tax rate (in percent) applied to orders shipped to `country`. Rates
+/// without app apply to every app, rates without material apply to every
+/// material of the app.
+#[derive(
+    Debug, Clone, Identifiable, Queryable, Associations, AsChangeset, Serialize, Deserialize,
+)]
+#[changeset_options(treat_none_as_null = "true")]
+#[serde(rename_all = "camelCase")]
+pub struct TaxRate {
+    pub id: i32,
+    pub app_id: Option<i32>,
+    pub material_id: Option<i32>,
+    pub country: String,
+    pub rate: f32,
+}
+
+#[derive(Insertable)]
+#[table_name = "tax_rates"]
+struct InsertableTaxRate<'a> {
+    pub app_id: Option<&'a i32>,
+    pub material_id: Option<&'a i32>,
+    pub country: &'a str,
+    pub rate: &'a f32,
+}
+
+// Helpers
+
+pub fn default_country() -> String {
+    env::var("DEFAULT_COUNTRY").unwrap_or("FR".to_string())
+}
+
+/// Finds the most specific rate matching the destination country: a rate
+/// bound to the material wins over a rate bound to the app only, which wins
+/// over a global rate.
+pub fn find(
+    conn: &database::PooledConnection,
+    app_id: Option<i32>,
+    material_id: i32,
+    country: &str,
+) -> Result<TaxRate> {
+    let country = country.to_uppercase();
+
+    let rates = {
+        use crate::schema::tax_rates::dsl;
+        let rates = dsl::tax_rates.filter(dsl::country.eq(&country)).filter(
+            dsl::material_id
+                .is_null()
+                .or(dsl::material_id.eq(material_id)),
+        );
+
+        match app_id {
+            Some(app_id) => rates
+                .filter(dsl::app_id.is_null().or(dsl::app_id.eq(app_id)))
+                .load::<TaxRate>(conn),
+            None => rates.filter(dsl::app_id.is_null()).load::<TaxRate>(conn),
+        }
+    }
+    .chain_err(|| ErrorKind::SelectTaxRatesErr)?;
+
+    rates
+        .into_iter()
+        .max_by_key(|rate| (rate.material_id.is_some(), rate.app_id.is_some()))
+        .ok_or_else(|| ErrorKind::FindTaxRateErr(country).into())
+}
+
+/// Global rates are managed by admins only, app rates by the members of
+/// the app.
+fn check_claims(claims: &Claims, tax_rate: &TaxRate) -> Result<()> {
+    match tax_rate.app_id {
+        Some(app_id) => claims.check_app(app_id)?,
+        None => claims.check_admin()?,
+    }
+
+    Ok(())
+}
+
+fn find_by_id(conn: &database::PooledConnection, id: i32) -> Result<TaxRate> {
+    use crate::schema::tax_rates::dsl::tax_rates;
+    tax_rates
+        .find(id)
+        .first::<TaxRate>(conn)
+        .chain_err(|| ErrorKind::FindTaxRateByIdErr(id))
+}
+
+// Services
+
+#[get("/tax-rate")]
+async fn get(pool: web::Data<database::Pool>, claims: Claims) -> Result<HttpResponse> {
+    use crate::schema::tax_rates::dsl;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    let all_tax_rates: Vec<TaxRate> = web::block(move || {
+        dsl::tax_rates
+            .order((dsl::country, dsl::id))
+            .load::<TaxRate>(&conn)
+    })
+    .await
+    .chain_err(|| ErrorKind::SelectTaxRatesErr)?
+    .into_iter()
+    .filter(|rate| rate.app_id.is_none_or(|id| claims.can_access_app(id)))
+    .collect();
+
+    Ok(HttpResponse::Ok().json(all_tax_rates))
+}
+
+#[put("/tax-rate")]
+async fn set(
+    pool: web::Data<database::Pool>,
+    claims: Claims,
+    tax_rate: web::Json<TaxRate>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let mut tax_rate = tax_rate.into_inner();
+    let tax_rate_id = tax_rate.id;
+    tax_rate.country = tax_rate.country.to_uppercase();
+
+    check_claims(&claims, &tax_rate)?;
+    if tax_rate_id != 0 {
+        check_claims(&claims, &find_by_id(&conn, tax_rate_id)?)?;
+    }
+
+    if tax_rate_id == 0 {
+        web::block(move || {
+            let new_tax_rate = InsertableTaxRate {
+                app_id: tax_rate.app_id.as_ref(),
+                material_id: tax_rate.material_id.as_ref(),
+                country: &tax_rate.country,
+                rate: &tax_rate.rate,
+            };
+
+            diesel::insert_into(tax_rates::table)
+                .values(&new_tax_rate)
+                .execute(&conn)
+        })
+        .await
+        .chain_err(|| ErrorKind::InsertTaxRateErr(tax_rate_id))
+    } else {
+        web::block(move || diesel::update(&tax_rate).set(&tax_rate).execute(&conn))
+            .await
+            .chain_err(|| ErrorKind::UpdateTaxRateErr(tax_rate_id))
+    }?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[delete("/tax-rate/{id}")]
+async fn del(
+    pool: web::Data<database::Pool>,
+    claims: Claims,
+    web::Path(id): web::Path<i32>,
+) -> Result<HttpResponse> {
+    use crate::schema::tax_rates::dsl::tax_rates;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    check_claims(&claims, &find_by_id(&conn, id)?)?;
+
+    web::block(move || diesel::delete(tax_rates.find(id)).execute(&conn))
+        .await
+        .chain_err(|| ErrorKind::DeleteTaxRateErr(id))?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get).service(set).service(del);
+}
@@ -11,8 +11,10 @@ use log::error;
 use serde::{Deserialize, Serialize};
 use std::fs;
 
+use crate::app::{self, PublicApp};
 use crate::database;
 use crate::schema::templates;
+use crate::shared::auth::{Admin, Claims};
 use crate::shared::upload::dir;
 use crate::shared::{self, folder::Folderable};
 
@@ -48,8 +50,13 @@ error_chain! {
             description("Impossible de supprimer le template")
             display("Could not delete template `{}`", id)
         }
+        TemplateNotInAppErr(id: i32) {
+            description("Le template n'est pas disponible pour cette application")
+            display("Template `{}` is not enabled for app", id)
+        }
     }
     links {
+        App(app::Error, app::ErrorKind);
         Folder(shared::folder::Error, shared::folder::ErrorKind);
     }
 }
@@ -64,6 +71,7 @@ impl actix_web::error::ResponseError for Error {
 
     fn status_code(&self) -> StatusCode {
         match *self.kind() {
+            ErrorKind::TemplateNotInAppErr(_) => StatusCode::NOT_FOUND,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -103,27 +111,45 @@ struct InsertableTemplate<'a> {
 // Services
 
 #[get("/template")]
-async fn get_all(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
+async fn get_all(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let material_ids = app::get_material_ids(&conn, app.id)?;
+    let hidden_ids = app::get_hidden_template_ids(&conn, app.id)?;
 
-    let templates = {
-        use crate::schema::templates::dsl::*;
-        templates.order_by(name).load::<Template>(&conn)
-    }
-    .chain_err(|| ErrorKind::SelectTemplatesErr)?;
+    let templates: Vec<Template> = get_all_templates(&conn)?
+        .into_iter()
+        .filter(|template| !hidden_ids.contains(&template.id))
+        .filter(|template| is_available(template, &material_ids))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(templates))
+}
+
+#[get("/template")]
+async fn get_all_admin(pool: web::Data<database::Pool>, claims: Claims) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let hidden_ids = app::get_claims_hidden_template_ids(&conn, &claims)?;
+
+    let templates: Vec<Template> = get_all_templates(&conn)?
+        .into_iter()
+        .filter(|template| !hidden_ids.contains(&template.id))
+        .collect();
 
     Ok(HttpResponse::Ok().json(templates))
 }
 
 #[get("/folded-template")]
-async fn get_folded(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
+async fn get_folded(pool: web::Data<database::Pool>, claims: Claims) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let hidden_ids = app::get_claims_hidden_template_ids(&conn, &claims)?;
 
-    let templates = {
-        use crate::schema::templates::dsl::*;
-        templates.load::<Template>(&conn)
-    }
-    .chain_err(|| ErrorKind::SelectTemplatesErr)?;
+    let templates: Vec<Template> = get_all_templates(&conn)?
+        .into_iter()
+        .filter(|template| !hidden_ids.contains(&template.id))
+        .collect();
 
     let folder_ids = {
         use crate::schema::folders::dsl::*;
@@ -141,33 +167,46 @@ async fn get_folded(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
 }
 
 #[get("/template/{id}")]
-async fn get(pool: web::Data<database::Pool>, template_id: web::Path<i32>) -> Result<HttpResponse> {
+async fn get(
+    pool: web::Data<database::Pool>,
+    PublicApp(app): PublicApp,
+    template_id: web::Path<i32>,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let template_id = template_id.into_inner();
+    let template = find_or_default(&conn, template_id)?;
 
-    let template = if template_id == 0 {
-        Template {
-            id: 0,
-            folder_id: None,
-            name: String::new(),
-            tags: String::new(),
-            preview_url: None,
-            config: None,
-        }
-    } else {
-        use crate::schema::templates::dsl;
-        dsl::templates
-            .filter(dsl::id.eq(template_id))
-            .first::<Template>(&conn)
-            .chain_err(|| ErrorKind::FindTemplateErr(template_id))?
-    };
+    if template_id != 0
+        && (app::get_hidden_template_ids(&conn, app.id)?.contains(&template_id)
+            || !is_available(&template, &app::get_material_ids(&conn, app.id)?))
+    {
+        return Err(ErrorKind::TemplateNotInAppErr(template_id).into());
+    }
+
+    Ok(HttpResponse::Ok().json(template))
+}
+
+#[get("/template/{id}")]
+async fn get_admin(
+    pool: web::Data<database::Pool>,
+    claims: Claims,
+    template_id: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let template_id = template_id.into_inner();
+
+    if app::get_claims_hidden_template_ids(&conn, &claims)?.contains(&template_id) {
+        return Err(ErrorKind::TemplateNotInAppErr(template_id).into());
+    }
 
+    let template = find_or_default(&conn, template_id)?;
     Ok(HttpResponse::Ok().json(template))
 }
 
 #[put("/template")]
 async fn set(
     pool: web::Data<database::Pool>,
+    _: Admin,
     template: web::Json<Template>,
 ) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
@@ -222,6 +261,7 @@ async fn set(
 #[delete("/template/{id}")]
 async fn del(
     pool: web::Data<database::Pool>,
+    _: Admin,
     web::Path(id): web::Path<i32>,
 ) -> Result<HttpResponse> {
     use crate::schema::templates::dsl::templates;
@@ -239,5 +279,83 @@ pub fn pub_services(cfg: &mut web::ServiceConfig) {
 }
 
 pub fn priv_services(cfg: &mut web::ServiceConfig) {
-    cfg.service(get_folded).service(set).service(del);
+    cfg.service(get_all_admin)
+        .service(get_admin)
+        .service(get_folded)
+        .service(set)
+        .service(del);
+}
+
+// Helpers
+
+fn get_all_templates(conn: &database::PooledConnection) -> Result<Vec<Template>> {
+    use crate::schema::templates::dsl::*;
+    templates
+        .order_by(name)
+        .load::<Template>(conn)
+        .chain_err(|| ErrorKind::SelectTemplatesErr)
+}
+
+fn find_or_default(conn: &database::PooledConnection, template_id: i32) -> Result<Template> {
+    if template_id == 0 {
+        return Ok(Template {
+            id: 0,
+            folder_id: None,
+            name: String::new(),
+            tags: String::new(),
+            preview_url: None,
+            config: None,
+        });
+    }
+
+    use crate::schema::templates::dsl;
+    dsl::templates
+        .filter(dsl::id.eq(template_id))
+        .first::<Template>(conn)
+        .chain_err(|| ErrorKind::FindTemplateErr(template_id))
+}
+
+/// Reads the product the template was designed on from its editor config.
+fn product_id(template: &Template) -> Option<i32> {
+    let config = template.config.as_ref()?;
+    let config = serde_json::from_str::<serde_json::Value>(config).ok()?;
+    config.get("productId")?.as_i64().map(|id| id as i32)
+}
+
+/// A template is available to an app when it is not bound to a product, or
+/// when its product is enabled for the app.
+fn is_available(template: &Template, material_ids: &[i32]) -> bool {
+    product_id(template).is_none_or(|id| material_ids.contains(&id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(config: Option<&str>) -> Template {
+        Template {
+            id: 1,
+            folder_id: None,
+            name: String::from("Template"),
+            tags: String::new(),
+            preview_url: None,
+            config: config.map(String::from),
+        }
+    }
+
+    #[test]
+    fn keeps_templates_of_enabled_products() {
+        assert!(is_available(&template(Some(r#"{"productId":2}"#)), &[1, 2]));
+        assert!(!is_available(
+            &template(Some(r#"{"productId":3}"#)),
+            &[1, 2]
+        ));
+    }
+
+    #[test]
+    fn keeps_templates_without_product() {
+        assert!(is_available(&template(None), &[]));
+        assert!(is_available(&template(Some("{}")), &[]));
+        assert!(is_available(&template(Some("not json")), &[]));
+    }
 }
@@ -3,7 +3,7 @@ use actix_web::{
     dev::HttpResponseBuilder,
     get,
     http::{header, StatusCode},
-    post, put, web, HttpResponse,
+    post, put, web, HttpRequest, HttpResponse,
 };
 use bcrypt;
 use diesel::prelude::*;
@@ -11,10 +11,13 @@ use error_chain::error_chain;
 use log::error;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use uuid::Uuid;
 
 use crate::database;
+use crate::refresh_token::{self, IssuedToken};
 use crate::schema::users;
-use crate::shared::auth;
+use crate::shared::auth::{self, Admin, Claims, Role};
+use crate::sign_in_throttle;
 
 // TODO: make use of `web::block`
 
@@ -58,9 +61,23 @@ error_chain! {
             description("Impossible de supprimer l'utilisateur")
             display("Could not delete user `{}`", id)
         }
+        SelectUserAppsErr(id: i32) {
+            description("Impossible de récupérer les applications de l'utilisateur")
+            display("Could not select apps of user `{}`", id)
+        }
+        FindUserErr(id: i32) {
+            description("Utilisateur introuvable")
+            display("Could not find user `{}`", id)
+        }
+        UpdateUserSessionsErr(id: i32) {
+            description("Impossible de révoquer les sessions de l'utilisateur")
+            display("Could not update sessions version of user `{}`", id)
+        }
     }
     links {
         Jwt(auth::Error, auth::ErrorKind);
+        RefreshToken(refresh_token::Error, refresh_token::ErrorKind);
+        SignInThrottle(sign_in_throttle::Error, sign_in_throttle::ErrorKind);
     }
 }
 
@@ -74,6 +91,8 @@ impl actix_web::error::ResponseError for Error {
 
     fn status_code(&self) -> StatusCode {
         match *self.kind() {
+            ErrorKind::RefreshToken(ref kind) => refresh_token::status_code(kind),
+            ErrorKind::SignInThrottle(ref kind) => sign_in_throttle::status_code(kind),
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -91,6 +110,8 @@ pub struct User {
     #[serde(skip_serializing, skip_deserializing)]
     pub token: Option<String>,
     pub is_admin: bool,
+    /// Where password reset links are sent.
+    pub email: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -99,67 +120,183 @@ struct SigningInUser {
     pub password: String,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RefreshingUser {
+    pub refresh_token: String,
+}
+
 #[derive(Insertable)]
 #[table_name = "users"]
 struct InsertableUser<'a> {
     pub username: &'a str,
     pub password: &'a str,
     pub is_admin: &'a bool,
+    pub email: Option<&'a str>,
 }
 
 #[derive(Identifiable, AsChangeset)]
 #[table_name = "users"]
+#[changeset_options(treat_none_as_null = "true")]
 struct UpdatableUser<'a> {
     pub id: &'a i32,
     pub username: &'a str,
     pub password: &'a str,
     pub is_admin: &'a bool,
+    pub email: Option<&'a str>,
 }
 
 #[derive(Identifiable, AsChangeset)]
 #[table_name = "users"]
+#[changeset_options(treat_none_as_null = "true")]
 struct UpdatablePasswordlessUser<'a> {
     pub id: &'a i32,
     pub username: &'a str,
     pub is_admin: &'a bool,
+    pub email: Option<&'a str>,
 }
 
-// Public services
+// Helpers
 
-#[post("/sign-in")]
-async fn sign_in(
-    pool: web::Data<database::Pool>,
-    user: web::Json<SigningInUser>,
+/// Gets the sessions version of the user, initializing it at first sign in.
+fn get_sessions_version(conn: &database::PooledConnection, user: &User) -> Result<String> {
+    if let Some(ref version) = user.token {
+        return Ok(version.to_owned());
+    }
+
+    let version = Uuid::new_v4().to_simple().to_string();
+    diesel::update(users::table.find(user.id))
+        .set(users::token.eq(&version))
+        .execute(conn)
+        .chain_err(|| ErrorKind::UpdateUserSessionsErr(user.id))?;
+
+    Ok(version)
+}
+
+/// Signs the user out everywhere: the sessions version changes, so that
+/// access tokens are refused, and refresh tokens are revoked.
+pub fn revoke_sessions(conn: &database::PooledConnection, user_id: i32) -> Result<()> {
+    diesel::update(users::table.find(user_id))
+        .set(users::token.eq(Uuid::new_v4().to_simple().to_string()))
+        .execute(conn)
+        .chain_err(|| ErrorKind::UpdateUserSessionsErr(user_id))?;
+
+    refresh_token::revoke_user(conn, user_id)?;
+    Ok(())
+}
+
+fn tokens_response(
+    conn: &database::PooledConnection,
+    user: &User,
+    refresh_token: IssuedToken,
 ) -> Result<HttpResponse> {
+    let role = if user.is_admin {
+        Role::Admin
+    } else {
+        Role::Member
+    };
+
+    let app_ids = {
+        use crate::schema::app_users::dsl;
+        dsl::app_users
+            .select(dsl::app_id)
+            .filter(dsl::user_id.eq(user.id))
+            .load::<i32>(conn)
+    }
+    .chain_err(|| ErrorKind::SelectUserAppsErr(user.id))?;
+
+    let version = get_sessions_version(conn, user)?;
+    let claims = Claims::new(user.id, role, app_ids).with_session(refresh_token.family, version);
+
+    Ok(HttpResponse::Ok().json(json!({
+        "userId": user.id,
+        "isAdmin": user.is_admin,
+        "token": auth::generate_jwt(claims)?,
+        "expiresIn": auth::ACCESS_TOKEN_TTL,
+        "refreshToken": refresh_token.token,
+    })))
+}
+
+/// Finds the user matching the credentials.
+fn authenticate(conn: &database::PooledConnection, user: &SigningInUser) -> Result<User> {
     use crate::schema::users::dsl::*;
-    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
 
     let matching_user = users
         .filter(username.eq(&user.username))
-        .first::<User>(&conn)
+        .first::<User>(conn)
         .chain_err(|| ErrorKind::FindUserByUsernameErr(user.username.to_owned()))?;
 
     let passwords_match = bcrypt::verify(&user.password, &matching_user.password)
         .chain_err(|| ErrorKind::FindUserPasswdMismatchErr(user.username.to_owned()))?;
 
     if passwords_match {
-        Ok(HttpResponse::Ok().json(json!({
-            "userId": matching_user.id,
-            "token": auth::generate_jwt(matching_user.id)?,
-        })))
+        Ok(matching_user)
     } else {
         Err(ErrorKind::FindUserPasswdMismatchErr(user.username.to_owned()).into())
     }
 }
 
+// Public services
+
+/// Signs the user in. Failures are counted by username and by address, so
+/// that guessing passwords gets slower and slower until a lockout.
+#[post("/sign-in")]
+async fn sign_in(
+    req: HttpRequest,
+    pool: web::Data<database::Pool>,
+    user: web::Json<SigningInUser>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let ip = sign_in_throttle::client_ip(&req);
+    sign_in_throttle::check(&conn, &user.username, &ip)?;
+
+    let matching_user = match authenticate(&conn, &user) {
+        Ok(matching_user) => matching_user,
+        Err(err) => {
+            sign_in_throttle::record_failure(&conn, &user.username, &ip)?;
+            return Err(err);
+        }
+    };
+
+    sign_in_throttle::record_success(&conn, &user.username, &ip)?;
+    let refresh_token = refresh_token::issue(&conn, matching_user.id)?;
+    tokens_response(&conn, &matching_user, refresh_token)
+}
+
+/// Exchanges a refresh token for a new access token and a new refresh
+/// token. The role and the apps are read again, so that changes made since
+/// the sign in apply.
+#[post("/refresh")]
+async fn refresh(
+    pool: web::Data<database::Pool>,
+    user: web::Json<RefreshingUser>,
+) -> Result<HttpResponse> {
+    use crate::schema::users::dsl::users;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let refresh_token = refresh_token::rotate(&conn, &user.refresh_token)?;
+    let user_id = refresh_token.user_id;
+
+    // A deleted user is refused like a revoked token.
+    let matching_user = users
+        .find(user_id)
+        .first::<User>(&conn)
+        .optional()
+        .chain_err(|| ErrorKind::FindUserErr(user_id))?
+        .ok_or_else(|| {
+            refresh_token::Error::from(refresh_token::ErrorKind::InvalidRefreshTokenErr)
+        })?;
+
+    tokens_response(&conn, &matching_user, refresh_token)
+}
+
 pub fn sign_in_service(cfg: &mut web::ServiceConfig) {
-    cfg.service(sign_in);
+    cfg.service(sign_in).service(refresh);
 }
 
 // Private services
 
 #[get("/user")]
-async fn get(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
+async fn get(pool: web::Data<database::Pool>, _: Admin) -> Result<HttpResponse> {
     use crate::schema::users::dsl::*;
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
 
@@ -171,10 +308,19 @@ async fn get(pool: web::Data<database::Pool>) -> Result<HttpResponse> {
 }
 
 #[put("/user")]
-async fn set(pool: web::Data<database::Pool>, user: web::Json<User>) -> Result<HttpResponse> {
+async fn set(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    user: web::Json<User>,
+) -> Result<HttpResponse> {
     let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
     let username = user.username.to_owned();
     let password = user.password.to_owned();
+    let email = user
+        .email
+        .as_deref()
+        .map(str::trim)
+        .filter(|email| !email.is_empty());
 
     if user.id == 0 {
         let hash = bcrypt::hash(&password, bcrypt::DEFAULT_COST)
@@ -184,6 +330,7 @@ async fn set(pool: web::Data<database::Pool>, user: web::Json<User>) -> Result<H
             username: &user.username,
             password: &hash,
             is_admin: &user.is_admin,
+            email,
         };
 
         diesel::insert_into(users::table)
@@ -191,17 +338,23 @@ async fn set(pool: web::Data<database::Pool>, user: web::Json<User>) -> Result<H
             .execute(&conn)
             .chain_err(|| ErrorKind::InsertUserErr(username.to_owned()))
     } else {
-        if password.is_empty() {
+        let prev_user = users::table
+            .find(user.id)
+            .first::<User>(&conn)
+            .chain_err(|| ErrorKind::FindUserErr(user.id))?;
+
+        let updated = if password.is_empty() {
             let next_user = UpdatablePasswordlessUser {
                 id: &user.id,
                 username: &user.username,
                 is_admin: &user.is_admin,
+                email,
             };
 
             diesel::update(&next_user)
                 .set(&next_user)
                 .execute(&conn)
-                .chain_err(|| ErrorKind::UpdateUserWithoutPasswdErr(username.to_owned()))
+                .chain_err(|| ErrorKind::UpdateUserWithoutPasswdErr(username.to_owned()))?
         } else {
             let hash = bcrypt::hash(&password, bcrypt::DEFAULT_COST)
                 .chain_err(|| ErrorKind::EncryptPasswdErr(username.to_owned()))?;
@@ -211,13 +364,21 @@ async fn set(pool: web::Data<database::Pool>, user: web::Json<User>) -> Result<H
                 username: &user.username,
                 password: &hash,
                 is_admin: &user.is_admin,
+                email,
             };
 
             diesel::update(&next_user)
                 .set(&next_user)
                 .execute(&conn)
-                .chain_err(|| ErrorKind::UpdateUserErr(username.to_owned()))
+                .chain_err(|| ErrorKind::UpdateUserErr(username.to_owned()))?
+        };
+
+        // A new password or role signs the user out everywhere, the tokens
+        // carry the role.
+        if !password.is_empty() || prev_user.is_admin != user.is_admin {
+            revoke_sessions(&conn, user.id)?;
         }
+        Ok(updated)
     }?;
 
     Ok(HttpResponse::NoContent().finish())
@@ -226,6 +387,7 @@ async fn set(pool: web::Data<database::Pool>, user: web::Json<User>) -> Result<H
 #[delete("/user/{id}")]
 async fn del(
     pool: web::Data<database::Pool>,
+    _: Admin,
     web::Path(id): web::Path<i32>,
 ) -> Result<HttpResponse> {
     use crate::schema::users::dsl::users;
@@ -238,6 +400,53 @@ async fn del(
     Ok(HttpResponse::NoContent().finish())
 }
 
+#[delete("/user/{id}/session")]
+async fn del_sessions(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    web::Path(id): web::Path<i32>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    revoke_sessions(&conn, id)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Lets a locked out user sign in again.
+#[delete("/user/{id}/lock")]
+async fn del_lock(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    web::Path(id): web::Path<i32>,
+) -> Result<HttpResponse> {
+    use crate::schema::users::dsl::users;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    let user = users
+        .find(id)
+        .first::<User>(&conn)
+        .chain_err(|| ErrorKind::FindUserErr(id))?;
+
+    sign_in_throttle::unlock(&conn, &user.username)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Signs out the session of the JWT.
+#[post("/logout")]
+async fn logout(pool: web::Data<database::Pool>, claims: Claims) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    if let Some(ref sid) = claims.sid {
+        refresh_token::revoke_family(&conn, sid)?;
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
 pub fn services(cfg: &mut web::ServiceConfig) {
-    cfg.service(get).service(set).service(del);
+    cfg.service(get)
+        .service(set)
+        .service(del)
+        .service(del_sessions)
+        .service(del_lock)
+        .service(logout);
 }
@@ -0,0 +1,794 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use actix_web::{
+    self,
+    client::Client,
+    delete,
+    dev::HttpResponseBuilder,
+    get,
+    http::{header, StatusCode},
+    post, put, rt, web, HttpResponse,
+};
+use chrono::{Local, NaiveDateTime};
+use diesel::{pg::expression::dsl::any, prelude::*};
+use error_chain::error_chain;
+use log::{error, warn};
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::app::App;
+use crate::database;
+use crate::schema::{webhook_deliveries, webhooks};
+use crate::shared::auth::Admin;
+
+/// Delay between two polls of the due deliveries.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Time given to a receiver to answer.
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+/// Maximum number of deliveries sent per poll.
+const BATCH_SIZE: i64 = 50;
+/// A delivery is given up after this many failed attempts.
+pub const MAX_ATTEMPTS: i32 = 8;
+
+// Error management
+
+error_chain! {
+    errors {
+        GetDbConnErr {
+            description("Impossible de se connecter à la base de données")
+            display("Could not get db conn from pool")
+        }
+        SelectWebhooksErr(app_id: i32) {
+            description("Impossible de récupérer les webhooks de l'application")
+            display("Could not select webhooks of app `{}`", app_id)
+        }
+        FindWebhookErr(id: i32) {
+            description("Impossible de récupérer le webhook")
+            display("Could not find webhook `{}`", id)
+        }
+        InvalidWebhookErr(reason: String) {
+            description("Le webhook est invalide")
+            display("Invalid webhook: {}", reason)
+        }
+        InsertWebhookErr(app_id: i32) {
+            description("Impossible de créer le webhook")
+            display("Could not insert webhook for app `{}`", app_id)
+        }
+        UpdateWebhookErr(id: i32) {
+            description("Impossible de modifier le webhook")
+            display("Could not update webhook `{}`", id)
+        }
+        DeleteWebhookErr(id: i32) {
+            description("Impossible de supprimer le webhook")
+            display("Could not delete webhook `{}`", id)
+        }
+        SelectDeliveriesErr(webhook_id: i32) {
+            description("Impossible de récupérer les envois du webhook")
+            display("Could not select deliveries of webhook `{}`", webhook_id)
+        }
+        FindDeliveryErr(id: i32) {
+            description("Impossible de récupérer l'envoi du webhook")
+            display("Could not find webhook delivery `{}`", id)
+        }
+        InsertDeliveryErr(webhook_id: i32) {
+            description("Impossible de programmer l'envoi du webhook")
+            display("Could not insert delivery for webhook `{}`", webhook_id)
+        }
+        ClaimDeliveriesErr {
+            description("Impossible de récupérer les envois de webhook à effectuer")
+            display("Could not claim due webhook deliveries")
+        }
+        UpdateDeliveryErr(id: i32) {
+            description("Impossible d'enregistrer l'envoi du webhook")
+            display("Could not update webhook delivery `{}`", id)
+        }
+    }
+}
+
+impl actix_web::error::ResponseError for Error {
+    fn error_response(&self) -> HttpResponse {
+        error!("{}", self.to_string());
+        HttpResponseBuilder::new(self.status_code())
+            .set_header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.description().to_owned())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match *self.kind() {
+            ErrorKind::FindWebhookErr(_) | ErrorKind::FindDeliveryErr(_) => StatusCode::NOT_FOUND,
+            ErrorKind::InvalidWebhookErr(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+// Models
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Event {
+    #[serde(rename = "order.created")]
+    OrderCreated,
+    #[serde(rename = "order.status_changed")]
+    OrderStatusChanged,
+    #[serde(rename = "material.updated")]
+    MaterialUpdated,
+    #[serde(rename = "material.deleted")]
+    MaterialDeleted,
+}
+
+impl Event {
+    pub const ALL: [Event; 4] = [
+        Event::OrderCreated,
+        Event::OrderStatusChanged,
+        Event::MaterialUpdated,
+        Event::MaterialDeleted,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Event::OrderCreated => "order.created",
+            Event::OrderStatusChanged => "order.status_changed",
+            Event::MaterialUpdated => "material.updated",
+            Event::MaterialDeleted => "material.deleted",
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Event {
+    type Err = String;
+
+    fn from_str(event: &str) -> std::result::Result<Self, Self::Err> {
+        Event::ALL
+            .iter()
+            .find(|e| e.as_str() == event)
+            .copied()
+            .ok_or_else(|| event.to_owned())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+/// A subscription of an app to some events. Payloads are signed with the
+/// secret, so that the receiver can check they come from us.
+#[derive(Debug, Clone, Identifiable, Queryable, Associations, Serialize)]
+#[belongs_to(App)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub id: i32,
+    pub app_id: i32,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[table_name = "webhooks"]
+struct InsertableWebhook<'a> {
+    pub app_id: &'a i32,
+    pub url: &'a str,
+    pub secret: &'a str,
+    pub events: &'a [String],
+    pub active: &'a bool,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "webhooks"]
+struct UpdatableWebhook<'a> {
+    pub url: &'a str,
+    pub events: &'a [String],
+    pub active: &'a bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookJson {
+    #[serde(default)]
+    pub id: i32,
+    pub url: String,
+    pub events: Vec<Event>,
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+/// An event to send to a webhook. Deliveries are inserted along with the
+/// change they describe, then sent by the worker until the receiver
+/// acknowledges them with a 2xx.
+#[derive(Debug, Clone, Identifiable, Queryable, Associations, Serialize)]
+#[belongs_to(Webhook)]
+#[table_name = "webhook_deliveries"]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub webhook_id: i32,
+    pub event: String,
+    pub payload: Value,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: Option<NaiveDateTime>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub delivered_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[table_name = "webhook_deliveries"]
+struct InsertableDelivery<'a> {
+    pub webhook_id: &'a i32,
+    pub event: &'a str,
+    pub payload: &'a Value,
+}
+
+/// The outcome of a single attempt to send a delivery.
+#[derive(Debug, PartialEq)]
+pub struct Attempt {
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+}
+
+impl Attempt {
+    pub fn is_success(&self) -> bool {
+        matches!(self.status_code, Some(code) if (200..300).contains(&code))
+    }
+}
+
+// Helpers
+
+fn generate_secret() -> String {
+    format!("whsec_{}", Uuid::new_v4().to_simple())
+}
+
+/// Signs the body with the webhook secret (hex encoded HMAC-SHA256), sent in
+/// the `X-Webhook-Signature` header as `sha256=<signature>`.
+pub fn sign(secret: &str, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::sign(&key, body.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Delay before the next attempt: 30s after the first failure, doubled after
+/// each new one, up to 6h.
+pub fn backoff(attempts: i32) -> chrono::Duration {
+    let exp = attempts.clamp(1, 16) as u32 - 1;
+    let secs = 30_i64.saturating_mul(2_i64.pow(exp));
+    chrono::Duration::seconds(secs.min(6 * 60 * 60))
+}
+
+/// Computes the state of a delivery after its `attempts`-th attempt, with
+/// the delay before the next one when it has to be retried.
+pub fn next_state(attempts: i32, success: bool) -> (DeliveryStatus, Option<chrono::Duration>) {
+    if success {
+        (DeliveryStatus::Delivered, None)
+    } else if attempts >= MAX_ATTEMPTS {
+        (DeliveryStatus::Failed, None)
+    } else {
+        (DeliveryStatus::Pending, Some(backoff(attempts)))
+    }
+}
+
+fn check(webhook: &WebhookJson) -> Result<()> {
+    let url = webhook.url.trim();
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        let reason = format!("url `{}` is not http(s)", url);
+        return Err(ErrorKind::InvalidWebhookErr(reason).into());
+    }
+
+    if webhook.events.is_empty() {
+        let reason = String::from("no event subscribed");
+        return Err(ErrorKind::InvalidWebhookErr(reason).into());
+    }
+
+    Ok(())
+}
+
+fn find(conn: &database::PooledConnection, app_id: i32, id: i32) -> Result<Webhook> {
+    use crate::schema::webhooks::dsl;
+    dsl::webhooks
+        .filter(dsl::id.eq(id))
+        .filter(dsl::app_id.eq(app_id))
+        .first::<Webhook>(conn)
+        .chain_err(|| ErrorKind::FindWebhookErr(id))
+}
+
+/// Queues the event for the active webhooks of the given apps subscribed to
+/// it. Meant to be called in the transaction of the change, so that no event
+/// is lost nor sent for a rolled back change.
+pub fn enqueue(
+    conn: &database::PooledConnection,
+    app_ids: &[i32],
+    event: Event,
+    data: &Value,
+) -> QueryResult<usize> {
+    if app_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let subscribed = {
+        use crate::schema::webhooks::dsl;
+        dsl::webhooks
+            .filter(dsl::app_id.eq_any(app_ids))
+            .filter(dsl::active.eq(true))
+            .filter(
+                event
+                    .as_str()
+                    .into_sql::<diesel::sql_types::Text>()
+                    .eq(any(dsl::events)),
+            )
+            .load::<Webhook>(conn)?
+    };
+
+    let created_at = Local::now().naive_local();
+    let payloads = subscribed
+        .iter()
+        .map(|webhook| {
+            json!({
+                "event": event,
+                "appId": webhook.app_id,
+                "createdAt": created_at,
+                "data": data,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let new_deliveries = subscribed
+        .iter()
+        .zip(payloads.iter())
+        .map(|(webhook, payload)| InsertableDelivery {
+            webhook_id: &webhook.id,
+            event: event.as_str(),
+            payload,
+        })
+        .collect::<Vec<_>>();
+
+    diesel::insert_into(webhook_deliveries::table)
+        .values(&new_deliveries)
+        .execute(conn)
+}
+
+/// Sends a delivery to the webhook url. Never fails: errors are reported in
+/// the attempt, to be stored with the delivery.
+pub async fn send(client: &Client, webhook: &Webhook, delivery: &WebhookDelivery) -> Attempt {
+    let body = delivery.payload.to_string();
+
+    let res = client
+        .post(&webhook.url)
+        .timeout(SEND_TIMEOUT)
+        .header("X-Webhook-Event", delivery.event.as_str())
+        .header("X-Webhook-Delivery", delivery.id.to_string())
+        .header(
+            "X-Webhook-Signature",
+            format!("sha256={}", sign(&webhook.secret, &body)),
+        )
+        .content_type("application/json")
+        .send_body(body)
+        .await;
+
+    match res {
+        Ok(res) if res.status().is_success() => Attempt {
+            status_code: Some(res.status().as_u16() as i32),
+            error: None,
+        },
+        Ok(res) => Attempt {
+            status_code: Some(res.status().as_u16() as i32),
+            error: Some(format!("Receiver answered {}", res.status())),
+        },
+        Err(err) => Attempt {
+            status_code: None,
+            error: Some(err.to_string()),
+        },
+    }
+}
+
+/// Takes the due deliveries of active webhooks. Their next attempt is pushed
+/// back while they are being sent, so that they are not taken twice.
+fn claim_due(conn: &database::PooledConnection) -> Result<Vec<(WebhookDelivery, Webhook)>> {
+    use crate::schema::webhook_deliveries::dsl;
+    let now = Local::now().naive_local();
+
+    let due_ids = dsl::webhook_deliveries
+        .inner_join(webhooks::table)
+        .filter(webhooks::active.eq(true))
+        .filter(dsl::status.eq(DeliveryStatus::Pending.as_str()))
+        .filter(dsl::next_attempt_at.le(now))
+        .order(dsl::next_attempt_at.asc())
+        .limit(BATCH_SIZE)
+        .select(dsl::id)
+        .load::<i32>(conn)
+        .chain_err(|| ErrorKind::ClaimDeliveriesErr)?;
+
+    if due_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let lease = SEND_TIMEOUT.as_secs() as i64 * 3;
+    let claimed = diesel::update(
+        dsl::webhook_deliveries
+            .filter(dsl::id.eq_any(&due_ids))
+            .filter(dsl::status.eq(DeliveryStatus::Pending.as_str()))
+            .filter(dsl::next_attempt_at.le(now)),
+    )
+    .set(dsl::next_attempt_at.eq(now + chrono::Duration::seconds(lease)))
+    .get_results::<WebhookDelivery>(conn)
+    .chain_err(|| ErrorKind::ClaimDeliveriesErr)?;
+
+    let webhook_ids = claimed.iter().map(|d| d.webhook_id).collect::<Vec<_>>();
+    let webhooks = webhooks::table
+        .filter(webhooks::id.eq_any(&webhook_ids))
+        .load::<Webhook>(conn)
+        .chain_err(|| ErrorKind::ClaimDeliveriesErr)?;
+
+    Ok(claimed
+        .into_iter()
+        .filter_map(|delivery| {
+            let webhook = webhooks.iter().find(|w| w.id == delivery.webhook_id)?;
+            Some((delivery, webhook.clone()))
+        })
+        .collect())
+}
+
+fn record(
+    conn: &database::PooledConnection,
+    delivery: &WebhookDelivery,
+    attempt: &Attempt,
+) -> Result<()> {
+    use crate::schema::webhook_deliveries::dsl;
+    let now = Local::now().naive_local();
+    let attempts = delivery.attempts + 1;
+    let (status, delay) = next_state(attempts, attempt.is_success());
+
+    if status == DeliveryStatus::Failed {
+        warn!(
+            "Giving up webhook delivery `{}` after {} attempts",
+            delivery.id, attempts
+        );
+    }
+
+    diesel::update(dsl::webhook_deliveries.find(delivery.id))
+        .set((
+            dsl::status.eq(status.as_str()),
+            dsl::attempts.eq(attempts),
+            dsl::next_attempt_at.eq(delay.map(|delay| now + delay)),
+            dsl::last_status_code.eq(attempt.status_code),
+            dsl::last_error.eq(attempt.error.as_deref()),
+            dsl::delivered_at.eq(Some(now).filter(|_| status == DeliveryStatus::Delivered)),
+        ))
+        .execute(conn)
+        .chain_err(|| ErrorKind::UpdateDeliveryErr(delivery.id))?;
+
+    Ok(())
+}
+
+async fn deliver_due(pool: &database::Pool, client: &Client) -> Result<()> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    for (delivery, webhook) in claim_due(&conn)? {
+        let attempt = send(client, &webhook, &delivery).await;
+        record(&conn, &delivery, &attempt)?;
+    }
+
+    Ok(())
+}
+
+/// Sends the due deliveries forever. Spawned once at startup.
+pub async fn run_worker(pool: database::Pool) {
+    let client = Client::new();
+    let mut interval = rt::time::interval(POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        if let Err(err) = deliver_due(&pool, &client).await {
+            error!("{}", err);
+        }
+    }
+}
+
+// Services
+
+#[get("/app/{id}/webhook")]
+async fn get(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    web::Path(app_id): web::Path<i32>,
+) -> Result<HttpResponse> {
+    use crate::schema::webhooks::dsl;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    let webhooks = dsl::webhooks
+        .filter(dsl::app_id.eq(app_id))
+        .order(dsl::created_at.asc())
+        .load::<Webhook>(&conn)
+        .chain_err(|| ErrorKind::SelectWebhooksErr(app_id))?;
+
+    Ok(HttpResponse::Ok().json(webhooks))
+}
+
+#[put("/app/{id}/webhook")]
+async fn set(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    web::Path(app_id): web::Path<i32>,
+    webhook: web::Json<WebhookJson>,
+) -> Result<HttpResponse> {
+    use crate::schema::webhooks::dsl;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let webhook = webhook.into_inner();
+    check(&webhook)?;
+
+    let events = webhook
+        .events
+        .iter()
+        .map(|event| event.as_str().to_owned())
+        .collect::<Vec<_>>();
+
+    let webhook = if webhook.id == 0 {
+        let new_webhook = InsertableWebhook {
+            app_id: &app_id,
+            url: webhook.url.trim(),
+            secret: &generate_secret(),
+            events: &events,
+            active: &webhook.active,
+        };
+
+        diesel::insert_into(webhooks::table)
+            .values(&new_webhook)
+            .get_result::<Webhook>(&conn)
+            .chain_err(|| ErrorKind::InsertWebhookErr(app_id))?
+    } else {
+        find(&conn, app_id, webhook.id)?;
+
+        let next_webhook = UpdatableWebhook {
+            url: webhook.url.trim(),
+            events: &events,
+            active: &webhook.active,
+        };
+
+        diesel::update(dsl::webhooks.find(webhook.id))
+            .set(&next_webhook)
+            .get_result::<Webhook>(&conn)
+            .chain_err(|| ErrorKind::UpdateWebhookErr(webhook.id))?
+    };
+
+    Ok(HttpResponse::Ok().json(webhook))
+}
+
+#[delete("/app/{app_id}/webhook/{id}")]
+async fn del(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    web::Path((app_id, id)): web::Path<(i32, i32)>,
+) -> Result<HttpResponse> {
+    use crate::schema::webhooks::dsl;
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+
+    diesel::delete(
+        dsl::webhooks
+            .filter(dsl::id.eq(id))
+            .filter(dsl::app_id.eq(app_id)),
+    )
+    .execute(&conn)
+    .chain_err(|| ErrorKind::DeleteWebhookErr(id))?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/app/{app_id}/webhook/{id}/delivery")]
+async fn get_deliveries(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    web::Path((app_id, id)): web::Path<(i32, i32)>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let webhook = find(&conn, app_id, id)?;
+
+    let deliveries = WebhookDelivery::belonging_to(&webhook)
+        .order(webhook_deliveries::created_at.desc())
+        .limit(100)
+        .load::<WebhookDelivery>(&conn)
+        .chain_err(|| ErrorKind::SelectDeliveriesErr(id))?;
+
+    Ok(HttpResponse::Ok().json(deliveries))
+}
+
+/// Sends a delivery again, as a new pending delivery with the same payload,
+/// whatever the status of the original one.
+#[post("/app/{app_id}/webhook/{webhook_id}/delivery/{id}/redeliver")]
+async fn redeliver(
+    pool: web::Data<database::Pool>,
+    _: Admin,
+    web::Path((app_id, webhook_id, id)): web::Path<(i32, i32, i32)>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().chain_err(|| ErrorKind::GetDbConnErr)?;
+    let webhook = find(&conn, app_id, webhook_id)?;
+
+    let delivery = WebhookDelivery::belonging_to(&webhook)
+        .filter(webhook_deliveries::id.eq(id))
+        .first::<WebhookDelivery>(&conn)
+        .chain_err(|| ErrorKind::FindDeliveryErr(id))?;
+
+    let new_delivery = InsertableDelivery {
+        webhook_id: &webhook.id,
+        event: &delivery.event,
+        payload: &delivery.payload,
+    };
+
+    let delivery = diesel::insert_into(webhook_deliveries::table)
+        .values(&new_delivery)
+        .get_result::<WebhookDelivery>(&conn)
+        .chain_err(|| ErrorKind::InsertDeliveryErr(webhook.id))?;
+
+    Ok(HttpResponse::Accepted().json(delivery))
+}
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get)
+        .service(set)
+        .service(del)
+        .service(get_deliveries)
+        .service(redeliver);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App as HttpApp, HttpRequest};
+    use std::sync::Mutex;
+
+    type Received = Mutex<Vec<(String, String, String)>>;
+
+    fn webhook(url: String) -> Webhook {
+        Webhook {
+            id: 1,
+            app_id: 1,
+            url,
+            secret: String::from("whsec_test"),
+            events: vec![String::from("order.created")],
+            active: true,
+            created_at: Local::now().naive_local(),
+        }
+    }
+
+    fn delivery() -> WebhookDelivery {
+        WebhookDelivery {
+            id: 42,
+            webhook_id: 1,
+            event: String::from("order.created"),
+            payload: json!({"event": "order.created", "appId": 1, "data": {"id": 7}}),
+            status: String::from("pending"),
+            attempts: 0,
+            next_attempt_at: None,
+            last_status_code: None,
+            last_error: None,
+            created_at: Local::now().naive_local(),
+            delivered_at: None,
+        }
+    }
+
+    async fn receive(
+        req: HttpRequest,
+        body: String,
+        received: web::Data<Received>,
+    ) -> HttpResponse {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_owned()
+        };
+        received.lock().unwrap().push((
+            header("X-Webhook-Event"),
+            header("X-Webhook-Signature"),
+            body,
+        ));
+        HttpResponse::NoContent().finish()
+    }
+
+    async fn unavailable() -> HttpResponse {
+        HttpResponse::ServiceUnavailable().finish()
+    }
+
+    #[test]
+    fn parses_events() {
+        for event in Event::ALL.iter() {
+            assert_eq!(event.as_str().parse::<Event>(), Ok(*event));
+            assert_eq!(serde_json::to_value(event).unwrap(), json!(event.as_str()));
+        }
+        assert!("order.deleted".parse::<Event>().is_err());
+    }
+
+    #[test]
+    fn signs_with_hmac_sha256() {
+        // Test case 2 of RFC 4231.
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        assert_eq!(backoff(1), chrono::Duration::seconds(30));
+        assert_eq!(backoff(2), chrono::Duration::seconds(60));
+        assert_eq!(backoff(4), chrono::Duration::seconds(240));
+        assert_eq!(backoff(20), chrono::Duration::hours(6));
+    }
+
+    #[test]
+    fn retries_until_max_attempts() {
+        assert_eq!(next_state(1, true), (DeliveryStatus::Delivered, None));
+        assert_eq!(
+            next_state(1, false),
+            (DeliveryStatus::Pending, Some(backoff(1)))
+        );
+        assert_eq!(
+            next_state(MAX_ATTEMPTS, false),
+            (DeliveryStatus::Failed, None)
+        );
+    }
+
+    #[test]
+    fn sends_signed_payloads_to_receiver() {
+        rt::System::new("test").block_on(async {
+            let received = web::Data::new(Received::default());
+            let app_received = received.clone();
+            let srv = test::start(move || {
+                HttpApp::new()
+                    .app_data(app_received.clone())
+                    .route("/hook", web::post().to(receive))
+                    .route("/down", web::post().to(unavailable))
+            });
+            let client = Client::new();
+            let delivery = delivery();
+
+            let attempt = send(&client, &webhook(srv.url("/hook")), &delivery).await;
+            assert!(attempt.is_success());
+            assert_eq!(attempt.status_code, Some(204));
+
+            let (event, signature, body) = received.lock().unwrap().pop().unwrap();
+            assert_eq!(event, "order.created");
+            assert_eq!(signature, format!("sha256={}", sign("whsec_test", &body)));
+            assert_eq!(
+                serde_json::from_str::<Value>(&body).unwrap(),
+                delivery.payload
+            );
+
+            let attempt = send(&client, &webhook(srv.url("/down")), &delivery).await;
+            assert!(!attempt.is_success());
+            assert_eq!(attempt.status_code, Some(503));
+
+            srv.stop().await;
+        });
+    }
+}